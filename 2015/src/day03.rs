@@ -1,32 +1,30 @@
-use anyhow::{Result, bail};
+use anyhow::Result;
 use register::register;
-use utils::hash::FastHashSet;
+use utils::{
+    geom::{Direction, Point},
+    grid::InfiniteGrid,
+};
 
 #[register]
 fn run(input: &str) -> Result<(usize, usize)> {
-    let mut pos1 = (0, 0);
-    let mut pos2 = [(0, 0), (0, 0)];
-    let mut part1 = FastHashSet::from_iter([(0, 0)]);
+    let mut pos1 = Point::new(0, 0);
+    let mut pos2 = [Point::new(0, 0); 2];
+    let mut part1 = InfiniteGrid::new(false);
+    part1[pos1] = true;
     let mut part2 = part1.clone();
     for c in input.bytes() {
-        pos1 = apply_move(pos1, c)?;
-        part1.insert(pos1);
-        pos2 = [pos2[1], apply_move(pos2[0], c)?];
-        part2.insert(pos2[1]);
+        let dir = Direction::try_from(c)?;
+        pos1 = dir.apply(pos1);
+        part1[pos1] = true;
+        pos2 = [pos2[1], dir.apply(pos2[0])];
+        part2[pos2[1]] = true;
     }
 
-    Ok((part1.len(), part2.len()))
+    Ok((count_visited(&part1), count_visited(&part2)))
 }
 
-fn apply_move(pos: (i32, i32), c: u8) -> Result<(i32, i32)> {
-    let pos = match c {
-        b'^' => (pos.0, pos.1 + 1),
-        b'v' => (pos.0, pos.1 - 1),
-        b'>' => (pos.0 + 1, pos.1),
-        b'<' => (pos.0 - 1, pos.1),
-        _ => bail!("invalid move: {}", c as char),
-    };
-    Ok(pos)
+fn count_visited(visited: &InfiniteGrid<bool>) -> usize {
+    visited.iter().filter(|&(_, &cell)| cell).count()
 }
 
 #[cfg(test)]
@@ -1,4 +1,6 @@
 use std::{
+    array,
+    ops::Range,
     sync::atomic::{AtomicBool, AtomicUsize, Ordering},
     thread,
 };
@@ -6,20 +8,23 @@ use std::{
 use anyhow::{Result, anyhow};
 use panic_message::panic_message;
 use register::register;
-use utils::md5::SingleBlock;
+use utils::{
+    md5::{self, Digest, SingleBlock},
+    threads,
+};
 
 const BLOCK_SIZE: usize = 1000;
+const LANES: usize = 8;
 
 #[register]
 fn run(input: &str) -> Result<(usize, usize)> {
     let state = State::new();
     for num in 1..BLOCK_SIZE {
-        let bytes = prepare_block(input, num);
-        state.consume(num, &bytes);
+        state.consume(num, prepare_block(input, num).digest());
     }
 
     thread::scope(|scope| {
-        let handles: Vec<_> = (0..num_cpus::get())
+        let handles: Vec<_> = (0..threads::count())
             .map(|_| scope.spawn(|| search_thread(input, &state)))
             .collect();
         handles.into_iter().try_for_each(|handle| {
@@ -47,6 +52,18 @@ fn prepare_block(input: &str, mut num: usize) -> SingleBlock {
     block
 }
 
+/// Increments the decimal number stored in `block[digits]`.
+fn increment(block: &mut SingleBlock, digits: Range<usize>) {
+    for i in digits.rev() {
+        if block[i] == b'9' {
+            block[i] = b'0';
+        } else {
+            block[i] += 1;
+            break;
+        }
+    }
+}
+
 fn search_thread(input: &str, state: &State) {
     while !state.stop.load(Ordering::Relaxed) {
         let block_start = state.next_block.fetch_add(BLOCK_SIZE, Ordering::Relaxed);
@@ -55,18 +72,17 @@ fn search_thread(input: &str, state: &State) {
         debug_assert_eq!(num_digits, (block_end - 1).ilog10() as usize + 1);
 
         let mut block = prepare_block(input, block_start);
-        for num in block_start..block_end {
-            debug_assert_eq!(block, prepare_block(input, num));
-            if state.consume(num, &block) {
-                break;
-            }
-
-            for i in (input.len()..input.len() + num_digits).rev() {
-                if block[i] == b'9' {
-                    block[i] = b'0';
-                } else {
-                    block[i] += 1;
-                    break;
+        'block: for start in (block_start..block_end).step_by(LANES) {
+            let blocks: [_; LANES] = array::from_fn(|i| {
+                debug_assert_eq!(block, prepare_block(input, start + i));
+                let current = block;
+                increment(&mut block, input.len()..input.len() + num_digits);
+                current
+            });
+
+            for (num, digest) in (start..).zip(md5::digest_lanes(&blocks)) {
+                if state.consume(num, digest) {
+                    break 'block;
                 }
             }
         }
@@ -90,11 +106,11 @@ impl State {
         }
     }
 
-    fn consume(&self, num: usize, block: &SingleBlock) -> bool {
-        let digest = block.digest();
-        if digest[0] & 0xFF_FF_F0_00 == 0 {
+    fn consume(&self, num: usize, digest: Digest) -> bool {
+        let [first, ..] = digest.words();
+        if first & 0xFF_FF_F0_00 == 0 {
             self.part1.fetch_min(num, Ordering::SeqCst);
-            if digest[0] & 0xFF_FF_FF_00 == 0 {
+            if first & 0xFF_FF_FF_00 == 0 {
                 self.part2.fetch_min(num, Ordering::SeqCst);
                 self.stop.store(true, Ordering::Relaxed);
                 return true;
@@ -1,15 +1,16 @@
-use anyhow::{Context, Result, bail};
+use std::str::FromStr;
+
+use anyhow::{Result, bail};
 use register::register;
+use utils::input::{Parse, parse_lines};
 
 const MAX_WIRE: usize = 26 * 27;
 
 #[register]
 fn run(input: &str) -> Result<(u16, u16)> {
     let mut instr = [Instruction::Assign(Input::Literal(0)); MAX_WIRE];
-    for line in input.lines() {
-        let (head, tail) = line.split_once(" -> ").context("invalid input")?;
-        let wire = parse_wire(tail)?;
-        instr[usize::from(wire)] = parse_instruction(head)?;
+    for connection in parse_lines::<Connection>(input)? {
+        instr[usize::from(connection.wire.0)] = connection.instruction;
     }
 
     let mut cache = [None; MAX_WIRE];
@@ -21,20 +22,36 @@ fn run(input: &str) -> Result<(u16, u16)> {
     Ok((part1, part2))
 }
 
+#[derive(Debug, Parse)]
+#[parse("{instruction} -> {wire}")]
+struct Connection {
+    instruction: Instruction,
+    wire: Wire,
+}
+
+#[derive(Debug, Copy, Clone)]
+struct Wire(u16);
+
 #[derive(Debug, Copy, Clone)]
 enum Input {
     Literal(u16),
     Wire(u16),
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, Parse)]
 enum Instruction {
-    Assign(Input),
+    #[parse("{0} AND {1}")]
     And(Input, Input),
+    #[parse("{0} OR {1}")]
     Or(Input, Input),
+    #[parse("{0} LSHIFT {1}")]
     LShift(Input, Input),
+    #[parse("{0} RSHIFT {1}")]
     RShift(Input, Input),
+    #[parse("NOT {0}")]
     Not(Input),
+    #[parse("{0}")]
+    Assign(Input),
 }
 
 fn eval(wire: usize, instr: &[Instruction; MAX_WIRE], cache: &mut [Option<u16>; MAX_WIRE]) -> u16 {
@@ -73,39 +90,29 @@ fn eval_input(
     }
 }
 
-fn parse_instruction(s: &str) -> Result<Instruction> {
-    let Some((head, tail)) = s.split_once(' ') else {
-        return Ok(Instruction::Assign(parse_input(s)?));
-    };
-    let Some((mid, tail)) = tail.split_once(' ') else {
-        debug_assert_eq!(head, "NOT");
-        return Ok(Instruction::Not(parse_input(tail)?));
-    };
-    let left = parse_input(head)?;
-    let right = parse_input(tail)?;
-    let instr = match mid {
-        "AND" => Instruction::And(left, right),
-        "OR" => Instruction::Or(left, right),
-        "LSHIFT" => Instruction::LShift(left, right),
-        "RSHIFT" => Instruction::RShift(left, right),
-        _ => bail!("Invalid instruction: {mid}"),
-    };
-    Ok(instr)
-}
+impl FromStr for Input {
+    type Err = anyhow::Error;
 
-fn parse_input(s: &str) -> Result<Input> {
-    if s.as_bytes()[0].is_ascii_digit() {
-        Ok(Input::Literal(s.parse()?))
-    } else {
-        parse_wire(s).map(Input::Wire)
+    fn from_str(s: &str) -> Result<Self> {
+        if s.starts_with(|c: char| c.is_ascii_digit()) {
+            Ok(Self::Literal(s.parse()?))
+        } else {
+            Ok(Self::Wire(s.parse::<Wire>()?.0))
+        }
     }
 }
 
-fn parse_wire(s: &str) -> Result<u16> {
-    let wire = match s.as_bytes() {
-        [x] => u16::from(x - b'a'),
-        [x, y] => u16::from(x - b'a' + 1) * 26 + u16::from(y - b'a'),
-        _ => bail!("Invalid wire: {s}"),
-    };
-    Ok(wire)
+impl FromStr for Wire {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let wire = match s.as_bytes() {
+            [x @ b'a'..=b'z'] => u16::from(x - b'a'),
+            [x @ b'a'..=b'z', y @ b'a'..=b'z'] => {
+                u16::from(x - b'a' + 1) * 26 + u16::from(y - b'a')
+            }
+            _ => bail!("Invalid wire: {s}"),
+        };
+        Ok(Self(wire))
+    }
 }
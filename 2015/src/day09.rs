@@ -1,54 +1,36 @@
 use anyhow::{Context, Result};
 use register::register;
-use utils::num::bits;
+use utils::{
+    graph::bitset,
+    hash::Indexer,
+    input::{Parse, parse_lines},
+};
+
+#[derive(Debug, Parse)]
+#[parse("{from} to {to} = {distance}")]
+struct Route {
+    from: String,
+    to: String,
+    distance: u32,
+}
 
 #[register]
 fn run(input: &str) -> Result<(u32, u32)> {
+    let routes: Vec<Route> = parse_lines(input)?;
+    let mut cities = Indexer::new();
     let mut adj = [[0; 8]; 8];
-    let mut cities = [""; 8];
-    let mut num_cities = 0;
-    for line in input.lines() {
-        let (head, tail) = line.split_once(" = ").context("invalid input")?;
-        let (city1, city2) = head.split_once(" to ").context("invalid input")?;
-        let city1 = cities.iter().position(|&c| c == city1).unwrap_or_else(|| {
-            cities[num_cities] = city1;
-            num_cities += 1;
-            num_cities - 1
-        });
-        let city2 = cities.iter().position(|&c| c == city2).unwrap_or_else(|| {
-            cities[num_cities] = city2;
-            num_cities += 1;
-            num_cities - 1
-        });
-        adj[city1][city2] = tail.parse().context("invalid input")?;
-        adj[city2][city1] = adj[city1][city2];
-    }
-
-    let mut dp = [[(u32::MAX, 0_u32); 8]; 1 << 8];
-    for c in 0..num_cities {
-        dp[1 << c][c] = (0, 0);
-    }
-
-    for m in 1_usize..(1 << num_cities) {
-        if m.is_power_of_two() {
-            continue;
-        }
-
-        for c1 in bits(m) {
-            let m2 = m ^ (1 << c1);
-            for c2 in bits(m2) {
-                dp[m][c1].0 = dp[m][c1].0.min(dp[m2][c2].0.saturating_add(adj[c2][c1]));
-                dp[m][c1].1 = dp[m][c1].1.max(dp[m2][c2].1.saturating_add(adj[c2][c1]));
-            }
-        }
+    for route in &routes {
+        let city1 = cities.index_of(route.from.as_str());
+        let city2 = cities.index_of(route.to.as_str());
+        adj[city1][city2] = route.distance;
+        adj[city2][city1] = route.distance;
     }
+    let num_cities = cities.len();
 
-    let solution = dp[(1 << num_cities) - 1]
-        .into_iter()
-        .fold((u32::MAX, 0), |(part1, part2), (mn, mx)| {
-            (part1.min(mn), part2.max(mx))
-        });
-    Ok(solution)
+    let weight = |c1: usize, c2: usize| adj[c1][c2];
+    let part1 = bitset::hamiltonian_path(num_cities, None, weight, u32::min);
+    let part2 = bitset::hamiltonian_path(num_cities, None, weight, u32::max);
+    Ok((part1.context("no cities")?, part2.context("no cities")?))
 }
 
 #[cfg(test)]
@@ -1,48 +1,18 @@
 use anyhow::{Context, Result};
 use arrayvec::ArrayVec;
-use itertools::Itertools;
 use register::register;
-use utils::num::bits;
+use utils::graph::bitset;
 
 const MAX: usize = 8;
 
 #[register]
 fn run(input: &str) -> Result<(i64, i64)> {
     let (n, matrix) = parse(input)?;
-    assert!(n > 2);
-    let mut dp = [[[i64::MIN; MAX]; MAX]; 1 << MAX];
-    for i in 0..n {
-        dp[1 << i][i][i] = 0;
-    }
-
-    for m in 3_usize..(1 << n) {
-        if m.count_ones() < 2 {
-            continue;
-        }
-
-        for last in bits(m) {
-            let m2 = m ^ (1 << last);
-            for first in bits(m2) {
-                for second_last in bits(m2) {
-                    dp[m][first][last] = dp[m][first][last].max(
-                        dp[m2][first][second_last]
-                            .saturating_add(i64::from(matrix[second_last][last])),
-                    );
-                }
-            }
-        }
-    }
-
-    let all = (1 << n) - 1;
-    let solution = (0..n).cartesian_product(0..n).fold(
-        (i64::MIN, i64::MIN),
-        |(part1, part2), (first, last)| {
-            let val1 = dp[all][first][last].saturating_add(i64::from(matrix[last][first]));
-            let val2 = dp[all][last][first];
-            (part1.max(val1), part2.max(val2))
-        },
-    );
-    Ok(solution)
+    let weight = |from: usize, to: usize| i64::from(matrix[from][to]);
+    let part1 = bitset::hamiltonian_cycle(n, weight, i64::max).context("no guests")?;
+    // Sitting down between two guests breaks the table into a path
+    let part2 = bitset::hamiltonian_path(n, None, weight, i64::max).context("no guests")?;
+    Ok((part1, part2))
 }
 
 fn parse<'a>(input: &'a str) -> Result<(usize, [[i32; MAX]; MAX])> {
@@ -1,11 +1,10 @@
 use anyhow::Result;
-use itertools::Itertools;
 use register::register;
 use utils::input::Input;
 
 #[register]
 fn run(input: &str) -> Result<(i64, i64)> {
-    let ingredients: Vec<[i32; 5]> = input.lines().map(Input::signed_integers_n).try_collect()?;
+    let ingredients: Vec<[i32; 5]> = input.lines_integers_n()?;
     let [ing1, ing2, ing3, ing4] = ingredients.try_into().expect("4 ingredients required");
 
     let mut part1 = 0;
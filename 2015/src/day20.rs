@@ -1,6 +1,7 @@
 use anyhow::Result;
 use num::integer::Roots;
 use register::register;
+use utils::num::primes_up_to;
 
 const BLOCK: usize = 1 << 16;
 
@@ -14,11 +15,12 @@ fn run(input: &str) -> Result<(usize, usize)> {
 }
 
 fn part1(target: usize, sieve: &mut [usize]) -> usize {
-    let mut small_sieve = SmallSieve::new();
+    // Every n has a divisor sum of at least n, so no block starts past target
+    let primes = primes_up_to((target + BLOCK).sqrt());
     for block_start in (lower_bound(target)..).step_by(BLOCK) {
         sieve.fill(1);
-        small_sieve.extend_to((block_start + BLOCK).sqrt());
-        for &p in &small_sieve.primes {
+        let max_prime = (block_start + BLOCK).sqrt();
+        for &p in primes.iter().take_while(|&&p| p <= max_prime) {
             let first = block_start.next_multiple_of(p).max(p) - block_start;
             for i in (first..BLOCK).step_by(p) {
                 let mut p_pow = p * p;
@@ -98,36 +100,3 @@ fn robins_upper_bound(n: usize) -> f64 {
     let n = n as f64;
     E_TO_GAMMA * n * n.ln().ln()
 }
-
-struct SmallSieve {
-    primes: Vec<usize>,
-    sieve: Vec<bool>,
-}
-
-impl SmallSieve {
-    fn new() -> Self {
-        Self {
-            primes: Vec::new(),
-            sieve: vec![false; 2],
-        }
-    }
-
-    fn extend_to(&mut self, end: usize) {
-        let first_new = self.sieve.len();
-        self.sieve.resize(end, true);
-        for &p in &self.primes {
-            let first = first_new.div_ceil(p) * p;
-            for i in (first..end).step_by(p) {
-                self.sieve[i] = false;
-            }
-        }
-        for i in first_new..end {
-            if self.sieve[i] {
-                self.primes.push(i);
-                for j in (i * i..end).step_by(i) {
-                    self.sieve[j] = false;
-                }
-            }
-        }
-    }
-}
@@ -1,6 +1,12 @@
+use std::mem;
+
 use anyhow::Result;
+use itertools::Itertools;
 use register::register;
-use utils::input::Input;
+use utils::{
+    ilp::{LinExpr, Model},
+    input::Input,
+};
 
 const WEAPONS: &[(u32, u32)] = &[(8, 4), (10, 5), (25, 6), (40, 7), (74, 8)];
 const ARMORS: &[(u32, u32)] = &[(0, 0), (13, 1), (31, 2), (53, 3), (75, 4), (102, 5)];
@@ -27,16 +33,22 @@ const RING_COMBOS: &[(u32, u32, u32)] = &[
     (180, 3, 3),
 ];
 
+/// Individual rings as `(cost, damage, armor)`.
+const RINGS: &[(u32, u32, u32)] = &[
+    (25, 1, 0),
+    (50, 2, 0),
+    (100, 3, 0),
+    (20, 0, 1),
+    (40, 0, 2),
+    (80, 0, 3),
+];
+
 #[register]
 fn run(input: &str) -> Result<(u32, u32)> {
     let [boss_hp, boss_dmg, boss_ac] = input.unsigned_integers_n::<u32, 3>()?;
     let (part1, part2) = itertools::iproduct!(WEAPONS, ARMORS, RING_COMBOS)
         .map(|((c1, dmg1), (c2, ac1), (c3, dmg2, ac2))| {
-            let my_dmg = dmg1 + dmg2;
-            let my_ac = ac1 + ac2;
-            let my_dpr = my_dmg.saturating_sub(boss_ac).max(1);
-            let boss_dpr = boss_dmg.saturating_sub(my_ac).max(1);
-            let win = 100_u32.div_ceil(boss_dpr) >= boss_hp.div_ceil(my_dpr);
+            let win = wins([boss_hp, boss_dmg, boss_ac], dmg1 + dmg2, ac1 + ac2);
             (win, c1 + c2 + c3)
         })
         .fold((u32::MAX, 0), |(mut part1, mut part2), (win, cost)| {
@@ -49,3 +61,100 @@ fn run(input: &str) -> Result<(u32, u32)> {
         });
     Ok((part1, part2))
 }
+
+/// Chooses items with one integer program per part.
+///
+/// Whether we win depends non-linearly on our damage and armor, so both are
+/// encoded in unary. The number of turns we need to kill the boss and the
+/// number of turns we survive are then linear in that encoding.
+#[register]
+fn ilp(input: &str) -> Result<(u32, u32)> {
+    let [boss_hp, boss_dmg, boss_ac] = input.unsigned_integers_n::<u32, 3>()?;
+
+    let mut shop = Model::new();
+    let mut totals = Default::default();
+    let weapons = WEAPONS.iter().map(|&(cost, damage)| (cost, damage, 0));
+    let weapons = add_items(&mut shop, weapons, &mut totals);
+    let armors = ARMORS[1..].iter().map(|&(cost, armor)| (cost, 0, armor));
+    let armors = add_items(&mut shop, armors, &mut totals);
+    let rings = add_items(&mut shop, RINGS.iter().copied(), &mut totals);
+    shop.add_eq(weapons, 1);
+    shop.add_le(armors, 1);
+    shop.add_le(rings, 2);
+    let [cost, damage, armor] = totals;
+
+    // The two best rings add 5 damage or 5 armor
+    let max_damage = WEAPONS.iter().map(|w| w.1).max().unwrap_or(0) + 5;
+    let turns_to_win = unary(&mut shop, damage, max_damage, |my_dmg| {
+        boss_hp.div_ceil(my_dmg.saturating_sub(boss_ac).max(1))
+    });
+    let max_armor = ARMORS.iter().map(|a| a.1).max().unwrap_or(0) + 5;
+    let turns_to_lose = unary(&mut shop, armor, max_armor, |my_ac| {
+        100_u32.div_ceil(boss_dmg.saturating_sub(my_ac).max(1))
+    });
+
+    // Like the enumeration, report u32::MAX and 0 for impossible parts
+    let mut win = shop.clone();
+    win.add_le(turns_to_win.clone(), turns_to_lose.clone());
+    let part1 = win
+        .minimize(cost.clone())
+        .map_or(u32::MAX, |s| s.objective() as u32);
+    let mut lose = shop;
+    lose.add_ge(turns_to_win, turns_to_lose + 1);
+    let part2 = lose.maximize(cost).map_or(0, |s| s.objective() as u32);
+
+    Ok((part1, part2))
+}
+
+/// Encodes `expr` in unary as `z_1 >= z_2 >= ... >= z_max` and returns the
+/// linear expression for `f(expr)`, which only takes integer values in
+/// `0..=max`.
+///
+/// The relaxation of this encoding is tighter than that of one indicator per
+/// value, so branch and bound needs fewer nodes.
+fn unary(model: &mut Model, expr: LinExpr, max: u32, f: impl Fn(u32) -> u32) -> LinExpr {
+    let steps: Vec<_> = (1..=max).map(|_| model.bool_var()).collect();
+    for (&z1, &z2) in steps.iter().tuple_windows() {
+        model.add_ge(z1, z2);
+    }
+    model.add_eq(expr, steps.iter().copied().sum::<LinExpr>());
+    let f = |x| i64::from(f(x));
+    let deltas = (1..=max).zip(&steps).map(|(x, &z)| z * (f(x) - f(x - 1)));
+    deltas.sum::<LinExpr>() + f(0)
+}
+
+/// Adds a boolean variable per item, adding their `(cost, damage, armor)`
+/// to `totals` and returning the number of items bought.
+fn add_items(
+    model: &mut Model,
+    items: impl IntoIterator<Item = (u32, u32, u32)>,
+    totals: &mut [LinExpr; 3],
+) -> LinExpr {
+    let mut count = LinExpr::default();
+    for (cost, damage, armor) in items {
+        let var = model.bool_var();
+        count = count + var;
+        for (total, stat) in totals.iter_mut().zip([cost, damage, armor]) {
+            *total = mem::take(total) + var * i64::from(stat);
+        }
+    }
+    count
+}
+
+fn wins([boss_hp, boss_dmg, boss_ac]: [u32; 3], my_dmg: u32, my_ac: u32) -> bool {
+    let my_dpr = my_dmg.saturating_sub(boss_ac).max(1);
+    let boss_dpr = boss_dmg.saturating_sub(my_ac).max(1);
+    100_u32.div_ceil(boss_dpr) >= boss_hp.div_ceil(my_dpr)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ilp_matches_enumeration() {
+        for input in ["104 8 1", "100 8 2", "109 8 2", "103 9 2", "12 7 2"] {
+            assert_eq!(ilp(input).unwrap(), run(input).unwrap(), "boss {input}");
+        }
+    }
+}
@@ -1,6 +1,11 @@
 use std::iter;
 
+use anyhow::Result;
 use register::register;
+use utils::{
+    input::Parse,
+    vm::{self, Control, Io, Machine, NoIo, Register, Registers},
+};
 
 #[register]
 fn run(input: &str) -> (usize, usize) {
@@ -31,3 +36,111 @@ fn solve(n: u32) -> usize {
     .count()
         - 1
 }
+
+#[register]
+fn interpreted(input: &str) -> Result<(u64, u64)> {
+    let mut vm = Machine::<Instruction, 2>::parse(input)?;
+    vm.run(&mut NoIo)?;
+    let part1 = vm.registers()[Register::named(b'b')];
+
+    vm.reset();
+    vm.registers_mut()[Register::named(b'a')] = 1;
+    vm.run(&mut NoIo)?;
+    let part2 = vm.registers()[Register::named(b'b')];
+
+    Ok((part1, part2))
+}
+
+#[derive(Debug, Clone, Copy, Parse)]
+enum Instruction {
+    #[parse("hlf {0}")]
+    Half(Register),
+    #[parse("tpl {0}")]
+    Triple(Register),
+    #[parse("inc {0}")]
+    Increment(Register),
+    #[parse("jmp {0}")]
+    Jump(isize),
+    #[parse("jie {0}, {1}")]
+    JumpIfEven(Register, isize),
+    #[parse("jio {0}, {1}")]
+    JumpIfOne(Register, isize),
+}
+
+impl vm::Instruction for Instruction {
+    type Word = u64;
+
+    #[inline]
+    fn execute<const N: usize>(
+        self,
+        _pc: usize,
+        _program: &mut [Self],
+        registers: &mut Registers<u64, N>,
+        _io: &mut impl Io<u64>,
+    ) -> Result<Control> {
+        let jump = match self {
+            Self::Half(register) => {
+                registers[register] /= 2;
+                None
+            }
+            Self::Triple(register) => {
+                registers[register] *= 3;
+                None
+            }
+            Self::Increment(register) => {
+                registers[register] += 1;
+                None
+            }
+            Self::Jump(offset) => Some(offset),
+            Self::JumpIfEven(register, offset) => {
+                registers[register].is_multiple_of(2).then_some(offset)
+            }
+            Self::JumpIfOne(register, offset) => (registers[register] == 1).then_some(offset),
+        };
+        Ok(jump.map_or(Control::Next, Control::Jump))
+    }
+
+    fn registers(self) -> impl IntoIterator<Item = Register> {
+        match self {
+            Self::Half(register)
+            | Self::Triple(register)
+            | Self::Increment(register)
+            | Self::JumpIfEven(register, _)
+            | Self::JumpIfOne(register, _) => Some(register),
+            Self::Jump(_) => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "\
+jio a, +8
+inc a
+tpl a
+inc a
+tpl a
+tpl a
+inc a
+jmp +5
+tpl a
+inc a
+tpl a
+inc a
+jio a, +8
+inc b
+jie a, +4
+tpl a
+inc a
+jmp +2
+hlf a
+jmp -7";
+
+    #[test]
+    fn interpreted_matches() {
+        assert_eq!(run(INPUT), (21, 9));
+        assert_eq!(interpreted(INPUT).unwrap(), (21, 9));
+    }
+}
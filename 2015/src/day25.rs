@@ -1,6 +1,6 @@
 use anyhow::Result;
 use register::register;
-use utils::input::Input;
+use utils::{input::Input, num::ModInt};
 
 const MOD: u64 = 33_554_393;
 
@@ -13,19 +13,9 @@ fn run(input: &str) -> Result<(u64, u8)> {
 fn solve(row: u32, col: u32) -> u64 {
     let diag = row + col;
     let diag_start_index = diag * (diag + 1) / 2;
-    let mut index = diag_start_index + col;
+    let index = diag_start_index + col;
 
-    let mut res = 1;
-    let mut base = 252_533;
-    while index > 0 {
-        if index & 1 == 1 {
-            res = res * base % MOD;
-        }
-        base = base * base % MOD;
-        index >>= 1;
-    }
-
-    res * 20_151_125 % MOD
+    (ModInt::<MOD>::new(252_533).pow(u64::from(index)) * ModInt::new(20_151_125)).value()
 }
 
 #[cfg(test)]
@@ -1,149 +1,390 @@
-use std::str::FromStr;
+use std::{collections::HashSet, ops::ControlFlow};
 
-use anyhow::{Context, Error, Result, bail};
+use anyhow::{Result, bail};
+use arrayvec::ArrayVec;
+use utils::{
+    input::{Parse, parse_lines},
+    vm::{self, Control, Exit, Io, NoIo, Operand, Register, Registers},
+};
 
-pub(crate) type Integer = u32;
-pub(crate) type JumpOffset = i16;
+pub(crate) type Integer = i64;
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub(crate) struct Register(u8);
+pub(crate) type VirtualMachine = vm::Machine<Op, 4>;
 
-impl Register {
-    pub(crate) const A: Self = Self(0);
-    pub(crate) const C: Self = Self(2);
+pub(crate) const A: Register = Register::named(b'a');
+
+/// Steps after which a run is given up. With fused loops, the puzzle
+/// programs need far fewer.
+const STEP_LIMIT: u64 = 1 << 24;
+
+/// Parses a program and loads it with loops already fused into macro-ops
+/// and the step limit set.
+pub(crate) fn load(input: &str) -> Result<VirtualMachine> {
+    let instructions: Vec<Instruction> = parse_lines(input)?;
+    let mut program: Vec<_> = instructions.into_iter().map(Op::from).collect();
+    optimise(&mut program);
+    let mut vm = VirtualMachine::new(program)?;
+    vm.set_step_limit(STEP_LIMIT);
+    Ok(vm)
 }
 
-impl FromStr for Register {
-    type Err = Error;
+/// Runs the program until it halts.
+pub(crate) fn run(vm: &mut VirtualMachine) -> Result<()> {
+    match vm.run(&mut NoIo)? {
+        Exit::Halted => Ok(()),
+        exit => bail!("unexpected exit: {exit:?}"),
+    }
+}
 
-    fn from_str(s: &str) -> Result<Self> {
-        match s.as_bytes() {
-            &[c] if c.is_ascii_lowercase() => Ok(Self(c - b'a')),
-            _ => bail!("invalid register: {s:?}"),
+/// Checks whether the program outputs 0, 1, 0, 1, ... forever.
+///
+/// The signal repeats forever once the machine outputs the same bit twice in
+/// the same state. Fails if the step limit is reached before that.
+pub(crate) fn is_clock_signal(vm: &mut VirtualMachine) -> Result<bool> {
+    let mut clock = Clock::default();
+    let mut seen = HashSet::new();
+    loop {
+        match vm.run(&mut clock)? {
+            Exit::Stopped if clock.ok => {
+                if !seen.insert((vm.pc(), *vm.registers(), clock.next)) {
+                    return Ok(true);
+                }
+            }
+            Exit::Stopped | Exit::Halted => return Ok(false),
+            exit => bail!("unexpected exit: {exit:?}"),
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
-pub(crate) enum Source {
-    Immediate(Integer),
-    Register(Register),
+/// Stops the machine after every output, recording whether it continues the
+/// clock signal.
+#[derive(Default)]
+struct Clock {
+    next: Integer,
+    ok: bool,
 }
 
-impl FromStr for Source {
-    type Err = Error;
+impl Io<Integer> for Clock {
+    fn output(&mut self, value: Integer) -> ControlFlow<()> {
+        self.ok = value == self.next;
+        self.next = 1 - self.next;
+        ControlFlow::Break(())
+    }
 
-    fn from_str(s: &str) -> Result<Self> {
-        if let Ok(register) = s.parse() {
-            Ok(Self::Register(register))
-        } else {
-            Ok(Self::Immediate(s.parse()?))
-        }
+    fn input(&mut self, _current: Integer) -> ControlFlow<(), Option<Integer>> {
+        ControlFlow::Break(())
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Parse)]
 pub(crate) enum Instruction {
-    Copy(Source, Register),
-    Increment(Register),
-    Decrement(Register),
-    JumpNotZero(Register, JumpOffset),
-    Jump(JumpOffset),
-    Noop,
+    #[parse("cpy {0} {1}")]
+    Copy(Operand<Integer>, Operand<Integer>),
+    #[parse("inc {0}")]
+    Increment(Operand<Integer>),
+    #[parse("dec {0}")]
+    Decrement(Operand<Integer>),
+    #[parse("jnz {0} {1}")]
+    JumpNotZero(Operand<Integer>, Operand<Integer>),
+    #[parse("tgl {0}")]
+    Toggle(Operand<Integer>),
+    #[parse("out {0}")]
+    Out(Operand<Integer>),
+}
+
+impl Instruction {
+    fn toggled(self) -> Self {
+        match self {
+            Self::Increment(x) => Self::Decrement(x),
+            Self::Decrement(x) | Self::Toggle(x) | Self::Out(x) => Self::Increment(x),
+            Self::JumpNotZero(x, y) => Self::Copy(x, y),
+            Self::Copy(x, y) => Self::JumpNotZero(x, y),
+        }
+    }
+}
+
+/// An instruction, and the macro-op for the loop it starts, if any.
+///
+/// The other instructions of a fused loop stay in place, so that jumps into
+/// the middle of the loop and modifications of it still work.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct Op {
+    instruction: Instruction,
+    fused: Option<MacroOp>,
 }
 
-impl FromStr for Instruction {
-    type Err = Error;
-
-    fn from_str(s: &str) -> Result<Self> {
-        let instr = s
-            .as_bytes()
-            .first_chunk::<3>()
-            .context("instruction too short")?;
-        let args = &s[4..];
-        let res = match instr {
-            b"cpy" => {
-                let (a, b) = parse_two_args(args)?;
-                Self::Copy(a, b)
+impl From<Instruction> for Op {
+    fn from(instruction: Instruction) -> Self {
+        Self {
+            instruction,
+            fused: None,
+        }
+    }
+}
+
+impl vm::Instruction for Op {
+    type Word = Integer;
+
+    #[inline]
+    fn execute<const N: usize>(
+        self,
+        pc: usize,
+        program: &mut [Self],
+        registers: &mut Registers<Integer, N>,
+        io: &mut impl Io<Integer>,
+    ) -> Result<Control> {
+        if let Some(fused) = self.fused
+            && let Some(control) = fused.execute(registers)
+        {
+            return Ok(control);
+        }
+
+        // Instructions that became invalid through toggling are skipped.
+        match self.instruction {
+            Instruction::Copy(source, Operand::Register(to)) => {
+                registers[to] = source.eval(registers);
             }
-            b"inc" => Self::Increment(args.parse()?),
-            b"dec" => Self::Decrement(args.parse()?),
-            b"jnz" => {
-                let (pred, off) = parse_two_args(args)?;
-                match pred {
-                    Source::Immediate(0) => Self::Noop,
-                    Source::Immediate(_) => Self::Jump(off),
-                    Source::Register(reg) => Self::JumpNotZero(reg, off),
+            Instruction::Increment(Operand::Register(register)) => registers[register] += 1,
+            Instruction::Decrement(Operand::Register(register)) => registers[register] -= 1,
+            Instruction::JumpNotZero(value, offset) => {
+                if value.eval(registers) != 0 {
+                    return Ok(Control::Jump(offset.eval(registers) as isize));
                 }
             }
-            _ => bail!("invalid instruction: {instr:?}"),
-        };
-        Ok(res)
+            Instruction::Toggle(offset) => {
+                let target = pc.checked_add_signed(offset.eval(registers) as isize);
+                if let Some(op) = target.and_then(|target| program.get_mut(target)) {
+                    op.instruction = op.instruction.toggled();
+                    optimise(program);
+                }
+            }
+            Instruction::Out(value) => {
+                if io.output(value.eval(registers)).is_break() {
+                    return Ok(Control::Stop);
+                }
+            }
+            Instruction::Copy(..) | Instruction::Increment(_) | Instruction::Decrement(_) => {}
+        }
+        Ok(Control::Next)
     }
-}
 
-fn parse_two_args<S, T>(s: &str) -> Result<(S, T)>
-where
-    S: FromStr,
-    Error: From<<S as FromStr>::Err>,
-    T: FromStr,
-    Error: From<<T as FromStr>::Err>,
-{
-    let (a, b) = s.split_once(' ').context("expected two arguments")?;
-    Ok((a.parse()?, b.parse()?))
+    /// Fused loops only use registers of the instructions they replace.
+    fn registers(self) -> impl IntoIterator<Item = Register> {
+        match self.instruction {
+            Instruction::Copy(x, y) | Instruction::JumpNotZero(x, y) => [x, y],
+            Instruction::Increment(x)
+            | Instruction::Decrement(x)
+            | Instruction::Toggle(x)
+            | Instruction::Out(x) => [x, Operand::Immediate(0)],
+        }
+        .into_iter()
+        .filter_map(Operand::register)
+    }
 }
 
-#[derive(Debug, Clone)]
-pub(crate) struct VirtualMachine<const N: usize> {
-    registers: [Integer; N],
+/// A loop that is executed in one go.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum MacroOp {
+    /// `target += delta * counter`, from the three instructions
+    /// `inc target`, `dec counter`, `jnz counter -2` in either order of the
+    /// first two, where `dec target` gives a delta of -1.
+    Add {
+        target: Register,
+        counter: Register,
+        delta: Integer,
+    },
+    /// `target += delta * factor * counter`, from `cpy factor inner`, an
+    /// add loop over `inner`, and `dec counter`, `jnz counter -5`.
+    Mul {
+        target: Register,
+        factor: Operand<Integer>,
+        inner: Register,
+        counter: Register,
+        delta: Integer,
+    },
 }
 
-impl<const N: usize> VirtualMachine<N> {
-    pub(crate) fn new() -> Self {
-        Self { registers: [0; N] }
-    }
+impl MacroOp {
+    const ADD_LEN: usize = 3;
+    const MUL_LEN: usize = 6;
 
-    pub(crate) fn set(&mut self, register: Register, value: Integer) {
-        self.registers[usize::from(register.0)] = value;
+    fn find(window: &[Op]) -> Option<Self> {
+        let instructions: ArrayVec<_, { Self::MUL_LEN }> = window
+            .iter()
+            .take(Self::MUL_LEN)
+            .map(|op| op.instruction)
+            .collect();
+        Self::find_mul(&instructions).or_else(|| Self::find_add(&instructions))
     }
 
-    pub(crate) fn read(&self, register: Register) -> Integer {
-        self.registers[usize::from(register.0)]
+    fn find_add(instructions: &[Instruction]) -> Option<Self> {
+        let &[first, second, jump, ..] = instructions else {
+            return None;
+        };
+        let Instruction::JumpNotZero(Operand::Register(counter), Operand::Immediate(-2)) = jump
+        else {
+            return None;
+        };
+
+        let decrement = Instruction::Decrement(Operand::Register(counter));
+        let step = if first == decrement {
+            second
+        } else if second == decrement {
+            first
+        } else {
+            return None;
+        };
+        let (target, delta) = match step {
+            Instruction::Increment(Operand::Register(target)) => (target, 1),
+            Instruction::Decrement(Operand::Register(target)) => (target, -1),
+            _ => return None,
+        };
+
+        (target != counter).then_some(Self::Add {
+            target,
+            counter,
+            delta,
+        })
     }
 
-    pub(crate) fn reset(&mut self) {
-        self.registers = [0; N];
+    fn find_mul(instructions: &[Instruction]) -> Option<Self> {
+        let &[copy, _, _, _, decrement, jump] = instructions else {
+            return None;
+        };
+        let Instruction::Copy(factor, Operand::Register(inner)) = copy else {
+            return None;
+        };
+        let Some(Self::Add {
+            target,
+            counter: inner_counter,
+            delta,
+        }) = Self::find_add(&instructions[1..4])
+        else {
+            return None;
+        };
+        let Instruction::JumpNotZero(Operand::Register(counter), Operand::Immediate(-5)) = jump
+        else {
+            return None;
+        };
+
+        let distinct = inner_counter == inner
+            && decrement == Instruction::Decrement(Operand::Register(counter))
+            && counter != inner
+            && counter != target
+            && ![inner, target, counter]
+                .map(Operand::Register)
+                .contains(&factor);
+        distinct.then_some(Self::Mul {
+            target,
+            factor,
+            inner,
+            counter,
+            delta,
+        })
     }
 
-    pub(crate) fn execute(&mut self, program: &[Instruction]) -> Result<Integer> {
-        let mut pc = 0;
-        while let Some(&instr) = program.get(pc) {
-            let mut jump = None;
-            match instr {
-                Instruction::Copy(source, to) => {
-                    let val = match source {
-                        Source::Immediate(val) => val,
-                        Source::Register(from) => self.read(from),
-                    };
-                    self.set(to, val);
+    /// Executes the loop, unless a counter is not positive, in which case
+    /// the original instructions take (practically) forever anyway.
+    #[inline]
+    fn execute<const N: usize>(self, registers: &mut Registers<Integer, N>) -> Option<Control> {
+        match self {
+            Self::Add {
+                target,
+                counter,
+                delta,
+            } => {
+                let count = registers[counter];
+                if count <= 0 {
+                    return None;
                 }
-                Instruction::Increment(register) => self.set(register, self.read(register) + 1),
-                Instruction::Decrement(register) => self.set(register, self.read(register) - 1),
-                Instruction::JumpNotZero(register, offset) => {
-                    jump = (self.read(register) != 0).then_some(offset);
+                registers[target] += delta * count;
+                registers[counter] = 0;
+                Some(Control::Jump(Self::ADD_LEN as isize))
+            }
+            Self::Mul {
+                target,
+                factor,
+                inner,
+                counter,
+                delta,
+            } => {
+                let (factor, count) = (factor.eval(registers), registers[counter]);
+                if factor <= 0 || count <= 0 {
+                    return None;
                 }
-                Instruction::Jump(offset) => jump = Some(offset),
-                Instruction::Noop => {}
+                registers[target] += delta * factor * count;
+                registers[inner] = 0;
+                registers[counter] = 0;
+                Some(Control::Jump(Self::MUL_LEN as isize))
             }
-
-            pc = if let Some(offset) = jump {
-                pc.checked_add_signed(isize::from(offset))
-                    .context("program counter went below zero")?
-            } else {
-                pc + 1
-            };
         }
+    }
+}
+
+/// Fuses all loops in the program, discarding previously fused loops that
+/// were modified since.
+fn optimise(program: &mut [Op]) {
+    for i in 0..program.len() {
+        program[i].fused = MacroOp::find(&program[i..]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(program: &str, a: Integer) -> (Integer, u64) {
+        let mut vm = load(program).unwrap();
+        vm.registers_mut()[A] = a;
+        assert_eq!(vm.run(&mut NoIo).unwrap(), Exit::Halted);
+        (vm.registers()[A], vm.steps())
+    }
+
+    #[test]
+    fn examples() {
+        let program = "cpy 41 a\ninc a\ninc a\ndec a\njnz a 2\ndec a";
+        assert_eq!(run(program, 0).0, 42);
+
+        let program = "cpy 2 a\ntgl a\ntgl a\ntgl a\ncpy 1 a\ndec a\ndec a";
+        assert_eq!(run(program, 0).0, 3);
+    }
+
+    #[test]
+    fn fused_loops() {
+        // a = (a - 5) + 3 * 4
+        let program = "\
+cpy 3 b
+cpy 4 d
+cpy 5 c
+dec a
+dec c
+jnz c -2
+cpy b c
+inc a
+dec c
+jnz c -2
+dec d
+jnz d -5";
+        assert_eq!(run(program, 100), (107, 5));
+    }
+
+    #[test]
+    fn toggled_loops() {
+        let program = |target| format!("cpy {target} c\ntgl c\ncpy 3 b\ninc a\ndec b\njnz b -2");
+        // The loop is untouched, modified or broken up.
+        assert_eq!(run(&program(10), 0), (3, 4));
+        assert_eq!(run(&program(2), 0), (-3, 4));
+        assert_eq!(run(&program(4), 0).0, 1);
+    }
+
+    #[test]
+    fn clock_signal() {
+        let program = "cpy a b\nout b\ninc b\nout b\ndec b\njnz 1 -4";
+        let mut vm = load(program).unwrap();
+        assert!(is_clock_signal(&mut vm).unwrap());
 
-        Ok(self.read(Register::A))
+        vm.reset();
+        vm.registers_mut()[A] = 1;
+        assert!(!is_clock_signal(&mut vm).unwrap());
     }
 }
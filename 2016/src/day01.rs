@@ -1,38 +1,53 @@
 use anyhow::{Context, Result};
 use register::register;
-use utils::hash::{FastHashCollectionExt, FastHashSet};
+use utils::{
+    geom::{Direction, Point},
+    grid::InfiniteGrid,
+};
 
 #[register]
-fn run(input: &str) -> Result<(i16, i16)> {
-    let mut pos = (0_i16, 0_i16);
-    let mut dir = (0, 1);
-    let mut seen = FastHashSet::with_capacity(1024);
+fn run(input: &str) -> Result<(isize, isize)> {
+    let origin = Point::new(0, 0);
+    let mut pos = origin;
+    let mut dir = Direction::Up;
+    let mut seen = InfiniteGrid::new(false);
     let mut part2 = None;
-    seen.insert(pos);
+    seen[pos] = true;
     for instr in input.split(", ") {
         dir = if instr.starts_with('L') {
-            (-dir.1, dir.0)
+            dir.turn_left()
         } else {
-            (dir.1, -dir.0)
+            dir.turn_right()
         };
-        let dist: i16 = instr[1..].parse().context("invalid instruction")?;
+        let dist: isize = instr[1..].parse().context("invalid instruction")?;
 
         if part2.is_none() {
             let mut pos2 = pos;
             for _ in 0..dist {
-                pos2 = (pos2.0 + dir.0, pos2.1 + dir.1);
-                if !seen.insert(pos2) {
-                    part2 = Some(pos2.0.abs() + pos2.1.abs());
+                pos2 = dir.apply(pos2);
+                if seen[pos2] {
+                    part2 = Some(pos2.manhattan(origin));
                     break;
                 }
+                seen[pos2] = true;
             }
         }
 
-        pos = (pos.0 + dir.0 * dist, pos.1 + dir.1 * dist);
+        pos += dir.vector() * dist;
     }
 
     Ok((
-        pos.0.abs() + pos.1.abs(),
+        pos.manhattan(origin),
         part2.context("no square visited twice")?,
     ))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test() {
+        assert_eq!(run("R8, R4, R4, R8").unwrap(), (8, 4));
+    }
+}
@@ -1,5 +1,7 @@
 use std::{
+    array,
     collections::BinaryHeap,
+    ops::Range,
     sync::{
         Mutex,
         atomic::{AtomicBool, AtomicUsize, Ordering},
@@ -10,19 +12,23 @@ use std::{
 use anyhow::{Result, anyhow};
 use panic_message::panic_message;
 use register::register;
-use utils::md5::SingleBlock;
+use utils::{
+    md5::{self, Digest, SingleBlock},
+    threads,
+};
 
 const BLOCK_SIZE: usize = 1000;
+const LANES: usize = 8;
 
 #[register]
 fn run(input: &str) -> Result<(String, String)> {
     let state = State::new();
     for num in 1..BLOCK_SIZE {
-        state.consume(num, &prepare_block(input, num));
+        state.consume(num, prepare_block(input, num).digest());
     }
 
     thread::scope(|scope| {
-        let handles: Vec<_> = (0..num_cpus::get())
+        let handles: Vec<_> = (0..threads::count())
             .map(|_| scope.spawn(|| search_thread(input, &state)))
             .collect();
         handles.into_iter().try_for_each(|handle| {
@@ -33,15 +39,11 @@ fn run(input: &str) -> Result<(String, String)> {
     })?;
 
     let solution = state.solution.into_inner().unwrap();
-    let part1_digits = solution.part1.into_sorted_vec().into_iter().map(|(_, d)| d);
-    let part2_digits = solution.part2.map(|(_, d)| d);
-
-    Ok((to_hex_str(part1_digits), to_hex_str(part2_digits)))
-}
+    let hex_char = |(_, digit)| char::from(md5::hex_digit(digit));
+    let part1 = solution.part1.into_sorted_vec().into_iter().map(hex_char);
+    let part2 = solution.part2.into_iter().map(hex_char);
 
-fn to_hex_str(digits: impl IntoIterator<Item = u8>) -> String {
-    let num = digits.into_iter().fold(0_u32, |x, d| x << 4 | u32::from(d));
-    format!("{num:08x}")
+    Ok((part1.collect(), part2.collect()))
 }
 
 fn prepare_block(input: &str, mut num: usize) -> SingleBlock {
@@ -56,6 +58,18 @@ fn prepare_block(input: &str, mut num: usize) -> SingleBlock {
     block
 }
 
+/// Increments the decimal number stored in `block[digits]`.
+fn increment(block: &mut SingleBlock, digits: Range<usize>) {
+    for i in digits.rev() {
+        if block[i] == b'9' {
+            block[i] = b'0';
+        } else {
+            block[i] += 1;
+            break;
+        }
+    }
+}
+
 fn search_thread(input: &str, state: &State) {
     while !state.stop.load(Ordering::Relaxed) {
         let block_start = state.next_block.fetch_add(BLOCK_SIZE, Ordering::Relaxed);
@@ -64,17 +78,16 @@ fn search_thread(input: &str, state: &State) {
         debug_assert_eq!(num_digits, (block_end - 1).ilog10() as usize + 1);
 
         let mut block = prepare_block(input, block_start);
-        for num in block_start..block_end {
-            debug_assert_eq!(block, prepare_block(input, num));
-            state.consume(num, &block);
-
-            for i in (input.len()..input.len() + num_digits).rev() {
-                if block[i] == b'9' {
-                    block[i] = b'0';
-                } else {
-                    block[i] += 1;
-                    break;
-                }
+        for start in (block_start..block_end).step_by(LANES) {
+            let blocks: [_; LANES] = array::from_fn(|i| {
+                debug_assert_eq!(block, prepare_block(input, start + i));
+                let current = block;
+                increment(&mut block, input.len()..input.len() + num_digits);
+                current
+            });
+
+            for (num, digest) in (start..).zip(md5::digest_lanes(&blocks)) {
+                state.consume(num, digest);
             }
         }
     }
@@ -99,12 +112,11 @@ impl State {
         }
     }
 
-    fn consume(&self, num: usize, block: &SingleBlock) {
-        let digest = block.digest();
-        if digest[0] & 0xFF_FF_F0_00 == 0 {
+    fn consume(&self, num: usize, digest: Digest) {
+        if digest.words()[0] & 0xFF_FF_F0_00 == 0 {
             let mut solution = self.solution.lock().unwrap();
-            let digit6 = (digest[0] >> 8 & 0xF) as u8;
-            let digit7 = (digest[0] >> 4 & 0xF) as u8;
+            let digit6 = digest.nibble(5);
+            let digit7 = digest.nibble(6);
 
             solution.part1.push((num, digit6));
             if solution.part1.len() == 9 {
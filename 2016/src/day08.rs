@@ -1,10 +1,9 @@
 use anyhow::Result;
-use arrayvec::ArrayString;
 use register::register;
-use utils::{input::Input, ocr};
+use utils::{grid::Grid, input::Input, ocr};
 
 #[register]
-fn run(input: &str) -> Result<(usize, ArrayString<10>)> {
+fn run(input: &str) -> Result<(usize, String)> {
     let mut grid = [[false; 50]; 6];
     for line in input.lines() {
         let [a, b] = line.unsigned_integers_n()?;
@@ -27,13 +26,7 @@ fn run(input: &str) -> Result<(usize, ArrayString<10>)> {
     }
 
     let part1 = grid.iter().flatten().filter(|&&x| x).count();
-    let mut part2 = ArrayString::new();
-    for i in 0..10 {
-        let bits = (0..6)
-            .flat_map(|y| &grid[y][5 * i..5 * (i + 1)])
-            .fold(0, |bits, &x| bits << 1 | u32::from(x));
-        part2.push(char::from(ocr::character(bits)?));
-    }
+    let part2 = ocr::read_grid(&Grid::from_fn(6, 50, |(y, x)| grid[y][x]))?;
 
     Ok((part1, part2))
 }
@@ -1,7 +1,7 @@
 use std::fmt::{self, Display, Formatter};
 
 use register::register;
-use utils::hash::{FastHashCollectionExt, FastHashSet};
+use utils::search::Search;
 
 const NAMES: [(&str, &str); 5] = [
     ("promethium generator", "promethium-compatible microchip"),
@@ -45,26 +45,15 @@ fn find_shortest_solution<const N: usize, const M: usize>(initial_state: State)
     debug_assert_eq!(0, target_state.move_lower_bound());
     let possible_moves = State::possible_moves::<N, M>();
 
-    let mut seen = FastHashSet::with_capacity(1 << 14);
-    let mut cur = vec![initial_state];
-    let mut next = Vec::new();
-    seen.insert(initial_state.to_equivalence());
-
-    for dist in 1.. {
-        for state in cur.drain(..) {
-            for next_state in state.moves(possible_moves) {
-                if next_state == target_state {
-                    return dist;
-                } else if seen.insert(next_state.to_equivalence()) {
-                    next.push(next_state);
-                }
-            }
-        }
-
-        std::mem::swap(&mut cur, &mut next);
-    }
-
-    unreachable!("infinite loop terminated")
+    Search::new([initial_state])
+        .canonicalize(|state| state.to_equivalence())
+        .bfs(
+            |state| state.moves(possible_moves),
+            |&state| state == target_state,
+        )
+        .goal()
+        .expect("target state should be reachable")
+        .1
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -1,29 +1,19 @@
-use anyhow::{Context, Result};
-use itertools::Itertools;
+use anyhow::Result;
 use register::register;
-use utils::input::Input;
+use utils::{
+    input::{Input, LineReader},
+    vm::Register,
+};
 
-use crate::assembunny::{Register, VirtualMachine};
+use crate::assembunny::{self, A, Integer};
 
 #[register]
 fn run(input: &str) -> Result<(u32, u32)> {
-    let mut lines = input.lines();
-    let [index1] = lines
-        .nth(2)
-        .context("unexpected eof")?
-        .signed_integers_n()?;
-    let [off] = lines
-        .nth(2)
-        .context("unexpected eof")?
-        .signed_integers_n()?;
-    let [factor1]: [u32; 1] = lines
-        .nth(10)
-        .context("unexpected eof")?
-        .unsigned_integers_n()?;
-    let [factor2]: [u32; 1] = lines
-        .next()
-        .context("unexpected eof")?
-        .unsigned_integers_n()?;
+    let mut lines = LineReader::new(input);
+    let [index1] = lines.nth_line(2)?.parse_with(Input::signed_integers_n)?;
+    let [off] = lines.nth_line(2)?.parse_with(Input::signed_integers_n)?;
+    let [factor1]: [u32; 1] = lines.nth_line(10)?.parse_with(Input::unsigned_integers_n)?;
+    let [factor2]: [u32; 1] = lines.next_line()?.parse_with(Input::unsigned_integers_n)?;
 
     let mut a: u32 = 1;
     let mut b = 2;
@@ -40,15 +30,52 @@ fn run(input: &str) -> Result<(u32, u32)> {
 }
 
 #[register]
-fn interpreted(input: &str) -> Result<(u32, u32)> {
-    let program: Vec<_> = input.lines().map(str::parse).try_collect()?;
-
-    let mut vm = VirtualMachine::<4>::new();
-    let part1 = vm.execute(&program)?;
+fn interpreted(input: &str) -> Result<(Integer, Integer)> {
+    let mut vm = assembunny::load(input)?;
+    assembunny::run(&mut vm)?;
+    let part1 = vm.registers()[A];
 
     vm.reset();
-    vm.set(Register::C, 1);
-    let part2 = vm.execute(&program)?;
+    vm.registers_mut()[Register::named(b'c')] = 1;
+    assembunny::run(&mut vm)?;
+    let part2 = vm.registers()[A];
 
     Ok((part1, part2))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "\
+cpy 1 a
+cpy 1 b
+cpy 26 d
+jnz c 2
+jnz 1 5
+cpy 7 c
+inc d
+dec c
+jnz c -2
+cpy a c
+inc a
+dec b
+jnz b -2
+cpy c b
+dec d
+jnz d -6
+cpy 13 c
+cpy 14 d
+inc a
+dec d
+jnz d -2
+dec c
+jnz c -5";
+
+    #[test]
+    fn interpreted_matches() {
+        let (part1, part2) = run(INPUT).unwrap();
+        assert_eq!((part1, part2), (317_993, 9_227_647));
+        assert_eq!(interpreted(INPUT).unwrap(), (part1.into(), part2.into()));
+    }
+}
@@ -1,9 +1,14 @@
-use std::{collections::VecDeque, sync::mpsc, thread};
+use std::{array, collections::VecDeque, sync::mpsc, thread};
 
 use anyhow::{Result, anyhow};
 use panic_message::panic_message;
 use register::register;
-use utils::md5::{Digest, SingleBlock};
+use utils::{
+    md5::{self, Digest, SingleBlock},
+    threads,
+};
+
+const LANES: usize = 16;
 
 #[register]
 fn run(input: &str) -> Result<(usize, usize)> {
@@ -17,7 +22,7 @@ fn run(input: &str) -> Result<(usize, usize)> {
     }
 
     let mut searcher2 = Searcher::new();
-    let num_threads = num_cpus::get();
+    let num_threads = threads::count();
     thread::scope(|scope| {
         let (senders, receivers): (Vec<_>, Vec<_>) =
             (0..num_threads).map(|_| mpsc::channel()).unzip();
@@ -26,9 +31,9 @@ fn run(input: &str) -> Result<(usize, usize)> {
             .enumerate()
             .map(|(i, sender)| {
                 scope.spawn(move || {
-                    for n in (i..).step_by(num_threads) {
-                        let digest = calc_stretched_hash(input, n);
-                        if sender.send(digest).is_err() {
+                    for start in (i * LANES..).step_by(num_threads * LANES) {
+                        let digests = calc_stretched_hashes(input, start);
+                        if sender.send(digests).is_err() {
                             break;
                         }
                     }
@@ -36,13 +41,15 @@ fn run(input: &str) -> Result<(usize, usize)> {
             })
             .collect();
 
-        for n in 0.. {
-            if n >= searcher2.stop_bound {
-                break;
-            }
+        'outer: for start in (0..).step_by(LANES) {
+            let digests = receivers[start / LANES % num_threads].recv()?;
+            for (n, digest) in (start..).zip(digests) {
+                if n >= searcher2.stop_bound {
+                    break 'outer;
+                }
 
-            let digest = receivers[n % num_threads].recv()?;
-            searcher2.consume(n, digest);
+                searcher2.consume(n, digest);
+            }
         }
 
         drop(receivers);
@@ -73,30 +80,21 @@ fn block_for_num(input: &str, mut num: usize) -> SingleBlock {
     block
 }
 
-fn calc_stretched_hash(input: &str, n: usize) -> Digest {
-    let mut block = block_for_num(input, n);
-    let mut digest = block.digest();
+/// Calculates the stretched hashes for `start..start + LANES` at once.
+fn calc_stretched_hashes(input: &str, start: usize) -> [Digest; LANES] {
+    let mut blocks: [_; LANES] = array::from_fn(|i| block_for_num(input, start + i));
+    let mut digests = md5::digest_lanes(&blocks);
 
-    block = SingleBlock::new(32);
+    blocks = [SingleBlock::new(32); LANES];
     for _ in 0..2016 {
-        for (i, byte) in digest_digits(digest).enumerate() {
-            block[i] = if byte < 10 {
-                b'0' + byte
-            } else {
-                b'a' + byte - 10
-            };
+        for (block, digest) in blocks.iter_mut().zip(digests) {
+            digest.write_hex(block.first_chunk_mut().unwrap());
         }
 
-        digest = block.digest();
+        digests = md5::digest_lanes(&blocks);
     }
 
-    digest
-}
-
-fn digest_digits(digest: Digest) -> impl Iterator<Item = u8> {
-    digest
-        .into_iter()
-        .flat_map(|block| (0..8).rev().map(move |i| (block >> (i * 4) & 0xF) as u8))
+    digests
 }
 
 struct Searcher {
@@ -119,7 +117,7 @@ impl Searcher {
         let mut cnt = 0;
         let mut triple = None;
         let mut quintuples = 0;
-        for digit in digest_digits(digest) {
+        for digit in digest.nibbles() {
             if digit == prev {
                 cnt += 1;
                 if cnt == 3 {
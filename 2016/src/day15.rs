@@ -1,44 +1,21 @@
 use anyhow::{Context, Result};
-use num::{Integer, integer::ExtendedGcd};
 use register::register;
-use utils::input::Input;
+use utils::{input::Input, num::crt};
 
 #[register]
 fn run(input: &str) -> Result<(i64, i64)> {
-    let mut eq = (0, 1);
-    let mut idx = 0;
-    for line in input.lines() {
-        idx += 1;
+    // Disc `i` with `m` positions starting at `x0` is open if
+    // `t + i + x0 ≡ 0 (mod m)`
+    let mut discs = Vec::new();
+    for (i, line) in (1..).zip(input.lines()) {
         let [_, m, _, x0] = line.signed_integers_n()?;
-        eq = combine_equations(eq, to_eq(x0, m, idx)).context("unsolvable")?;
+        discs.push((-i - x0, m));
     }
-    let part1 = eq.0;
-    eq = combine_equations(eq, to_eq(0, 11, idx + 1)).context("unsolvable")?;
+    let part1 = crt(discs.iter().copied()).context("unsolvable")?.0;
+    discs.push((-(discs.len() as i64 + 1), 11));
+    let part2 = crt(discs).context("unsolvable")?.0;
 
-    Ok((part1, eq.0))
-}
-
-fn to_eq(x0: i64, m: i64, i: usize) -> (i64, i64) {
-    let x = (2 * m - x0 - i as i64 % m) % m;
-    (x, m)
-}
-
-#[allow(clippy::many_single_char_names)]
-fn combine_equations((mut a, m): (i64, i64), (mut b, n): (i64, i64)) -> Option<(i64, i64)> {
-    if n > m {
-        return combine_equations((b, n), (a, m));
-    }
-
-    let ExtendedGcd { gcd, x, .. } = m.extended_gcd(&n);
-    a %= m;
-    b %= n;
-
-    let l = m / gcd * n;
-    let z = (b - a) % n * x % n / gcd * m + a;
-    ((b - a) % gcd == 0).then(|| {
-        let z = if z < 0 { z + l } else { z };
-        (z, l)
-    })
+    Ok((part1, part2))
 }
 
 #[cfg(test)]
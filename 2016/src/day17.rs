@@ -1,3 +1,5 @@
+use std::array;
+
 use register::register;
 use utils::md5::Stack;
 
@@ -24,23 +26,24 @@ fn dfs(x: u8, y: u8, md5_stack: &mut Stack, callback: &mut impl FnMut(&Stack)) {
         return;
     }
 
-    let [up_down, left_right, ..] = md5_stack.digest()[0].to_be_bytes();
-    if y > 0 && up_down >> 4 > 0xA {
+    let digest = md5_stack.digest();
+    let [up, down, left, right] = array::from_fn(|i| digest.nibble(i) > 0xA);
+    if y > 0 && up {
         md5_stack.push(b'U');
         dfs(x, y - 1, md5_stack, callback);
         md5_stack.pop();
     }
-    if y < 3 && up_down & 0xF > 0xA {
+    if y < 3 && down {
         md5_stack.push(b'D');
         dfs(x, y + 1, md5_stack, callback);
         md5_stack.pop();
     }
-    if x > 0 && left_right >> 4 > 0xA {
+    if x > 0 && left {
         md5_stack.push(b'L');
         dfs(x - 1, y, md5_stack, callback);
         md5_stack.pop();
     }
-    if x < 3 && left_right & 0xF > 0xA {
+    if x < 3 && right {
         md5_stack.push(b'R');
         dfs(x + 1, y, md5_stack, callback);
         md5_stack.pop();
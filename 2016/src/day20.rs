@@ -1,4 +1,5 @@
 use register::register;
+use utils::input::Input;
 
 #[register]
 fn run(input: &str) -> (u64, u64) {
@@ -7,12 +8,12 @@ fn run(input: &str) -> (u64, u64) {
 
 fn run_testable(input: &str, max: u64) -> (u64, u64) {
     let mut events: Vec<_> = input
-        .lines()
-        .flat_map(|line| {
-            let (start, end) = line.split_once('-').expect("invalid input line");
-            let start: u64 = start.parse().expect("invalid input line");
-            let end: u64 = end.parse().expect("invalid input line");
-            [(start, EventType::Start), (end + 1, EventType::End)]
+        .ranges::<u64>()
+        .flat_map(|range| {
+            [
+                (*range.start(), EventType::Start),
+                (range.end() + 1, EventType::End),
+            ]
         })
         .collect();
     events.sort_unstable();
@@ -1,17 +1,68 @@
-use anyhow::{Context, Result};
+use anyhow::Result;
 use register::register;
-use utils::input::Input;
+use utils::input::{Input, LineReader};
+
+use crate::assembunny::{self, A, Integer};
 
 #[register]
 fn run(input: &str) -> Result<(u32, u32)> {
-    let mut lines = input.lines();
+    let mut lines = LineReader::new(input);
     let [a] = lines
-        .nth(19)
-        .context("unexpected EOF")?
-        .unsigned_integers_n::<u32, 1>()?;
+        .nth_line(19)?
+        .parse_with(Input::unsigned_integers_n::<u32, 1>)?;
     let [b] = lines
-        .next()
-        .context("unexpected EOF")?
-        .unsigned_integers_n::<u32, 1>()?;
+        .next_line()?
+        .parse_with(Input::unsigned_integers_n::<u32, 1>)?;
     Ok((5040 + a * b, 479_001_600 + a * b))
 }
+
+#[register]
+fn interpreted(input: &str) -> Result<(Integer, Integer)> {
+    let eggs = |count| {
+        let mut vm = assembunny::load(input)?;
+        vm.registers_mut()[A] = count;
+        assembunny::run(&mut vm)?;
+        anyhow::Ok(vm.registers()[A])
+    };
+    Ok((eggs(7)?, eggs(12)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "\
+cpy a b
+dec b
+cpy a d
+cpy 0 a
+cpy b c
+inc a
+dec c
+jnz c -2
+dec d
+jnz d -5
+dec b
+cpy b c
+cpy c d
+dec d
+inc c
+jnz d -2
+tgl c
+cpy -16 c
+jnz 1 c
+cpy 73 c
+jnz 79 d
+inc a
+inc d
+jnz d -2
+inc c
+jnz c -5";
+
+    #[test]
+    fn interpreted_matches() {
+        let (part1, part2) = run(INPUT).unwrap();
+        assert_eq!((part1, part2), (10_807, 479_007_367));
+        assert_eq!(interpreted(INPUT).unwrap(), (part1.into(), part2.into()));
+    }
+}
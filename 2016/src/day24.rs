@@ -1,7 +1,5 @@
-use std::collections::VecDeque;
-
 use register::register;
-use utils::num::bits;
+use utils::{graph::bitset, search::Search};
 
 #[register]
 fn run(input: &str) -> (usize, usize) {
@@ -18,53 +16,42 @@ fn run(input: &str) -> (usize, usize) {
         }
     }
 
-    let height = grid.len();
-    let width = grid[0].len();
-    let mut which_pos = vec![u8::MAX; height * width];
-    for (i, &(y, x)) in pos.iter().enumerate() {
-        which_pos[y * width + x] = i as u8;
-    }
-
-    let mut queue = VecDeque::with_capacity(height * width);
-    let mut seen = vec![u8::MAX; height * width];
     let mut dist = [[0; 10]; 10];
-    for v0 in 0..num_pos {
-        let (y0, x0) = pos[usize::from(v0)];
-        queue.push_back((y0, x0, 0));
-        seen[y0 * width + x0] = v0;
-        while let Some((y, x, d)) = queue.pop_front() {
-            if which_pos[y * width + x] != u8::MAX {
-                dist[usize::from(v0)][usize::from(which_pos[y * width + x])] = d;
-            }
-
-            for (y2, x2) in [(y - 1, x), (y + 1, x), (y, x - 1), (y, x + 1)] {
-                if seen[y2 * width + x2] != v0 && grid[y2][x2] != b'#' {
-                    seen[y2 * width + x2] = v0;
-                    queue.push_back((y2, x2, d + 1));
-                }
-            }
+    for (v0, &start) in pos[..usize::from(num_pos)].iter().enumerate() {
+        let reached = Search::new([start]).bfs(
+            |&(y, x)| {
+                [(y - 1, x), (y + 1, x), (y, x - 1), (y, x + 1)]
+                    .into_iter()
+                    .filter(|&(y2, x2)| grid[y2][x2] != b'#')
+            },
+            |_| false,
+        );
+        for (v1, end) in pos[..usize::from(num_pos)].iter().enumerate() {
+            dist[v0][v1] = reached
+                .cost(end)
+                .expect("all locations should be reachable");
         }
     }
 
-    let mut dp = vec![vec![usize::MAX / 2; 1 << (num_pos - 1)]; usize::from(num_pos) - 1];
-    for bs in 1_usize..1 << (num_pos - 1) {
-        if bs.is_power_of_two() {
-            dp[0][bs] = dist[0][bs.trailing_zeros() as usize + 1];
-            continue;
-        }
+    let n = usize::from(num_pos);
+    let weight = |v1: usize, v2: usize| dist[v1][v2];
+    let part1 = bitset::hamiltonian_path(n, Some(0), weight, usize::min).unwrap();
+    let part2 = bitset::hamiltonian_cycle(n, weight, usize::min).unwrap();
+    (part1, part2)
+}
 
-        for last in bits(bs) {
-            for prev in bits(bs & !(1 << last)) {
-                dp[last][bs] =
-                    dp[last][bs].min(dp[prev][bs & !(1 << last)] + dist[prev + 1][last + 1]);
-            }
-        }
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn example() {
+        let input = "\
+###########
+#0.1.....2#
+#.#######.#
+#4.......3#
+###########";
+        assert_eq!(run(input), (14, 20));
     }
-
-    let all = (1 << (num_pos - 1)) - 1;
-    (0..usize::from(num_pos) - 1)
-        .map(|i| (dp[i][all], dp[i][all] + dist[i + 1][0]))
-        .fold((usize::MAX, usize::MAX), |(a, b), (c, d)| {
-            (a.min(c), b.min(d))
-        })
 }
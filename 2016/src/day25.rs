@@ -1,20 +1,20 @@
 use std::iter;
 
-use anyhow::{Context, Result};
+use anyhow::{Result, bail};
 use register::register;
-use utils::input::Input;
+use utils::input::{Input, LineReader};
+
+use crate::assembunny::{self, A, Integer};
 
 #[register]
 fn run(input: &str) -> Result<(usize, u8)> {
-    let mut lines = input.lines();
+    let mut lines = LineReader::new(input);
     let [a] = lines
-        .nth(1)
-        .context("unexpected EOF")?
-        .unsigned_integers_n::<usize, 1>()?;
+        .nth_line(1)?
+        .parse_with(Input::unsigned_integers_n::<usize, 1>)?;
     let [b] = lines
-        .next()
-        .context("unexpected EOF")?
-        .unsigned_integers_n::<usize, 1>()?;
+        .next_line()?
+        .parse_with(Input::unsigned_integers_n::<usize, 1>)?;
 
     let part1 = iter::successors(Some(0b10), |x| Some((x << 2) | 0b10))
         .find_map(|x| (x >= a * b).then(|| x - a * b))
@@ -22,3 +22,69 @@ fn run(input: &str) -> Result<(usize, u8)> {
 
     Ok((part1, 0))
 }
+
+/// The largest initial value tried before giving up.
+const MAX_INITIAL_VALUE: Integer = 1 << 16;
+
+#[register]
+fn interpreted(input: &str) -> Result<(Integer, u8)> {
+    let mut vm = assembunny::load(input)?;
+    for a in 1..=MAX_INITIAL_VALUE {
+        vm.reset();
+        vm.registers_mut()[A] = a;
+        if assembunny::is_clock_signal(&mut vm)? {
+            return Ok((a, 0));
+        }
+    }
+    bail!("no initial value produces a clock signal")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "\
+cpy a d
+cpy 4 c
+cpy 643 b
+inc d
+dec b
+jnz b -2
+dec c
+jnz c -5
+cpy d a
+jnz 0 0
+cpy a b
+cpy 0 a
+cpy 2 c
+jnz b 2
+jnz 1 6
+dec b
+dec c
+jnz c -4
+inc a
+jnz 1 -7
+cpy 2 b
+jnz c 2
+jnz 1 4
+dec b
+dec c
+jnz 1 -4
+jnz 0 0
+out b
+jnz a -19
+jnz 1 -21";
+
+    #[test]
+    fn interpreted_matches() {
+        assert_eq!(run(INPUT).unwrap(), (158, 0));
+        assert_eq!(interpreted(INPUT).unwrap(), (158, 0));
+    }
+
+    #[test]
+    fn step_limit() {
+        // Loops forever without output.
+        let error = interpreted("jnz 1 0").unwrap_err();
+        assert!(error.to_string().contains("StepLimit"));
+    }
+}
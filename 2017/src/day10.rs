@@ -1,8 +1,7 @@
 use anyhow::Result;
 use itertools::Itertools;
 use register::register;
-
-use crate::knot_hash;
+use utils::crypto::knot_hash;
 
 #[register]
 fn run(input: &str) -> Result<(u16, String)> {
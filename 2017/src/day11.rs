@@ -1,21 +1,13 @@
-use anyhow::{Result, bail};
+use anyhow::Result;
 use register::register;
+use utils::hex::{self, FlatDirection, Hex};
 
 #[register]
 fn run(input: &str) -> Result<(u32, u32)> {
-    let mut coord = HexCoord(0, 0);
-    let mut part2 = coord.distance_to_origin();
-    for dir in input.split(',') {
-        let HexCoord(q, r) = coord;
-        coord = match dir {
-            "n" => HexCoord(q, r - 1),
-            "ne" => HexCoord(q + 1, r - 1),
-            "se" => HexCoord(q + 1, r),
-            "s" => HexCoord(q, r + 1),
-            "sw" => HexCoord(q - 1, r + 1),
-            "nw" => HexCoord(q - 1, r),
-            _ => bail!("Invalid direction: {dir:?}"),
-        };
+    let mut coord = Hex::ORIGIN;
+    let mut part2 = 0;
+    for dir in hex::steps::<FlatDirection>(input) {
+        coord = coord.neighbor(dir?);
         part2 = part2.max(coord.distance_to_origin());
     }
 
@@ -23,17 +15,6 @@ fn run(input: &str) -> Result<(u32, u32)> {
     Ok((part1, part2))
 }
 
-/// Based on: <https://www.redblobgames.com/grids/hexagons/>
-#[derive(Debug, Clone, Copy)]
-struct HexCoord(i32, i32);
-
-impl HexCoord {
-    fn distance_to_origin(self) -> u32 {
-        let HexCoord(q, r) = self;
-        (q.unsigned_abs() + r.unsigned_abs() + (q + r).unsigned_abs()) / 2
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,39 +1,25 @@
-use anyhow::Result;
+use anyhow::{Result, ensure};
 use register::register;
-use utils::input::Input;
+use utils::{graph, input::Input};
 
 #[register]
 fn run(input: &str) -> Result<(usize, usize)> {
-    let lines: Vec<_> = input.lines().collect();
-    let mut seen = vec![false; lines.len()];
+    let adj: Vec<Vec<usize>> = input
+        .lines()
+        .map(|line| line[6..].unsigned_integers().collect())
+        .collect();
+    ensure!(
+        adj.iter().flatten().all(|&v| v < adj.len()),
+        "neighbor index out of bounds"
+    );
 
-    let mut part1 = 0;
-    let mut part2 = 0;
-    for v0 in 0..lines.len() {
-        if !seen[v0] {
-            let size = dfs(v0, &mut seen, &lines)?;
-            if v0 == 0 {
-                part1 = size;
-            }
-            part2 += 1;
-        }
-    }
+    let components = graph::connected_components(&adj);
+    let part1 = components.first().map_or(0, Vec::len);
+    let part2 = components.len();
 
     Ok((part1, part2))
 }
 
-fn dfs(v: usize, seen: &mut [bool], lines: &[&str]) -> Result<usize> {
-    seen[v] = true;
-    lines[v][6..]
-        .unsigned_integers()
-        .try_fold(1, |mut cnt, neighbor: usize| {
-            if !seen[neighbor] {
-                cnt += dfs(neighbor, seen, lines)?;
-            }
-            Ok(cnt)
-        })
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
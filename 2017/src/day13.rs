@@ -1,9 +1,8 @@
-use std::{cmp::Reverse, collections::BinaryHeap, mem};
+use std::mem;
 
 use anyhow::Result;
-use itertools::Itertools;
 use register::register;
-use utils::input::Input;
+use utils::{input::Input, search::Search};
 
 #[register]
 fn run(input: &str) -> Result<(usize, usize)> {
@@ -21,10 +20,7 @@ fn brute_force(input: &str) -> Result<(usize, usize)> {
 }
 
 fn run_inner(input: &str, part2: fn(&[[usize; 2]]) -> usize) -> Result<(usize, usize)> {
-    let mut layers: Vec<_> = input
-        .lines()
-        .map(Input::unsigned_integers_n::<usize, 2>)
-        .try_collect()?;
+    let mut layers = input.lines_integers_n::<usize, 2>()?;
     layers.sort_unstable_by_key(|&[_, range]| range);
 
     let part1 = layers
@@ -76,22 +72,20 @@ fn part2_dijkstra(layers: &[[usize; 2]]) -> usize {
         })
         .collect();
 
-    let mut heap = BinaryHeap::from_iter([(Reverse(1), 0)]);
-    while let Some((Reverse(delay), index)) = heap.pop() {
-        if index == layers.len() {
-            return delay;
-        }
-
-        let (modulus, blocked, width, degree) = precalc[index];
-        for i in 0..degree {
-            let next_delay = delay + i * width;
-            if next_delay % modulus != blocked {
-                heap.push((Reverse(next_delay), index + 1));
-            }
-        }
-    }
-
-    unreachable!("no solution found")
+    // Each edge adds a multiple of the previous lcm to the delay, so the
+    // cheapest path to the last layer gives the smallest delay
+    let reached = Search::new([(0, 1)]).dijkstra(
+        |&(index, delay)| {
+            let (modulus, blocked, width, degree) = precalc[index];
+            (0..degree)
+                .map(move |i| i * width)
+                .filter(move |step| (delay + step) % modulus != blocked)
+                .map(move |step| ((index + 1, delay + step), step))
+        },
+        |&(index, _)| index == layers.len(),
+    );
+    let (&(_, delay), _) = reached.goal().expect("no solution found");
+    delay
 }
 
 fn part2_brute_force(layers: &[[usize; 2]]) -> usize {
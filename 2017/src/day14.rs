@@ -2,8 +2,7 @@ use std::{array, collections::VecDeque};
 
 use rayon::iter::{IndexedParallelIterator, IntoParallelRefMutIterator, ParallelIterator};
 use register::register;
-
-use crate::knot_hash;
+use utils::crypto::knot_hash;
 
 #[register]
 fn run(input: &str) -> (usize, usize) {
@@ -9,7 +9,7 @@ use std::{
 
 use anyhow::Result;
 use register::register;
-use utils::input::Input;
+use utils::{input::Input, threads};
 
 const MOD: u64 = 2_147_483_647;
 const F1: u64 = 16_807;
@@ -47,7 +47,8 @@ fn parallel(
     let next_chunk = AtomicUsize::new(0);
     let (tx, rx) = mpsc::channel();
     thread::scope(|scope| {
-        for _ in 0..num_cpus::get() - 1 {
+        // One thread is reserved for the consumer
+        for _ in 0..threads::count().max(2) - 1 {
             let tx = tx.clone();
             let next_chunk = &next_chunk;
             scope.spawn(move || {
@@ -1,26 +1,24 @@
-use std::{
-    collections::VecDeque,
-    mem,
-    ops::{ControlFlow, Index, IndexMut},
-    str::FromStr,
-};
+use std::{collections::VecDeque, mem, ops::ControlFlow};
 
-use anyhow::{Context, Error, Result, bail, ensure};
-use itertools::Itertools;
+use anyhow::{Context, Result};
 use register::register;
+use utils::{
+    input::Parse,
+    vm::{self, Control, Io, Machine, Operand, Register, Registers},
+};
 
 #[register]
 fn run(input: &str) -> Result<(Integer, usize)> {
-    let program: Vec<Operation> = input.lines().map(str::parse).try_collect()?;
-
-    let mut vm = VirtualMachine::new(&program);
+    let mut vm = VirtualMachine::parse(input)?;
     let mut ops = Part1Ops(0);
-    vm.run(&mut ops);
+    vm.run(&mut ops)?;
     let Part1Ops(part1) = ops;
 
-    let mut vms = [VirtualMachine::new(&program), VirtualMachine::new(&program)];
-    vms[0].registers[Register(b'p' - b'a')] = 0;
-    vms[1].registers[Register(b'p' - b'a')] = 1;
+    let mut vms = [vm.clone(), vm];
+    for (p, vm) in (0..).zip(&mut vms) {
+        vm.reset();
+        vm.registers_mut()[Register::named(b'p')] = p;
+    }
     let mut send_counts = [0, 0];
     let mut idx = 0;
     let mut ops = Part2Ops::default();
@@ -29,7 +27,7 @@ fn run(input: &str) -> Result<(Integer, usize)> {
             break;
         }
 
-        vms[idx].run(&mut ops);
+        vms[idx].run(&mut ops)?;
         send_counts[idx] += ops.send.len();
         mem::swap(&mut ops.send, &mut ops.recv);
         idx = 1 - idx;
@@ -40,12 +38,13 @@ fn run(input: &str) -> Result<(Integer, usize)> {
 
 struct Part1Ops(Integer);
 
-impl VmOps for Part1Ops {
-    fn snd(&mut self, value: Integer) {
+impl Io<Integer> for Part1Ops {
+    fn output(&mut self, value: Integer) -> ControlFlow<()> {
         self.0 = value;
+        ControlFlow::Continue(())
     }
 
-    fn rcv(&mut self, value: Integer) -> ControlFlow<(), Option<Integer>> {
+    fn input(&mut self, value: Integer) -> ControlFlow<(), Option<Integer>> {
         if value != 0 {
             ControlFlow::Break(())
         } else {
@@ -60,12 +59,13 @@ struct Part2Ops {
     recv: VecDeque<Integer>,
 }
 
-impl VmOps for Part2Ops {
-    fn snd(&mut self, value: Integer) {
+impl Io<Integer> for Part2Ops {
+    fn output(&mut self, value: Integer) -> ControlFlow<()> {
         self.send.push_back(value);
+        ControlFlow::Continue(())
     }
 
-    fn rcv(&mut self, _value: Integer) -> ControlFlow<(), Option<Integer>> {
+    fn input(&mut self, _value: Integer) -> ControlFlow<(), Option<Integer>> {
         match self.recv.pop_front() {
             Some(v) => ControlFlow::Continue(Some(v)),
             None => ControlFlow::Break(()),
@@ -73,152 +73,78 @@ impl VmOps for Part2Ops {
     }
 }
 
-struct VirtualMachine<'a> {
-    registers: Registers,
-    program: &'a [Operation],
-    pc: usize,
-}
-
-impl<'a> VirtualMachine<'a> {
-    fn new(program: &'a [Operation]) -> Self {
-        Self {
-            registers: Registers::default(),
-            program,
-            pc: 0,
-        }
-    }
-
-    fn run<V: VmOps>(&mut self, ops: &mut V) {
-        loop {
-            let mut new_pc = self.pc + 1;
-            match self.program[self.pc] {
-                Operation::Set(register, value) => {
-                    self.registers[register] = value.eval(&self.registers);
-                }
-                Operation::Add(register, value) => {
-                    self.registers[register] += value.eval(&self.registers);
-                }
-                Operation::Mul(register, value) => {
-                    self.registers[register] *= value.eval(&self.registers);
-                }
-                Operation::Mod(register, value) => {
-                    self.registers[register] %= value.eval(&self.registers);
-                }
-                Operation::Jgz(value1, value2) => {
-                    if value1.eval(&self.registers) > 0 {
-                        let offset = value2.eval(&self.registers);
-                        new_pc = self.pc.strict_add_signed(offset as isize);
-                    }
-                }
-                Operation::Snd(value) => ops.snd(value.eval(&self.registers)),
-                Operation::Rcv(register) => match ops.rcv(self.registers[register]) {
-                    ControlFlow::Continue(None) => {}
-                    ControlFlow::Continue(Some(value)) => self.registers[register] = value,
-                    ControlFlow::Break(()) => break,
-                },
-            }
-
-            self.pc = new_pc;
-        }
-    }
-}
-
-#[derive(Default)]
-struct Registers([Integer; 26]);
-
-impl Index<Register> for Registers {
-    type Output = Integer;
-
-    fn index(&self, register: Register) -> &Self::Output {
-        &self.0[usize::from(register.0)]
-    }
-}
-
-impl IndexMut<Register> for Registers {
-    fn index_mut(&mut self, register: Register) -> &mut Self::Output {
-        &mut self.0[usize::from(register.0)]
-    }
-}
-
-trait VmOps {
-    fn snd(&mut self, value: Integer);
-    fn rcv(&mut self, value: Integer) -> ControlFlow<(), Option<Integer>>;
-}
+type VirtualMachine = Machine<Operation, 26>;
 
 type Integer = i64;
 
-#[derive(Clone, Copy)]
-struct Register(u8);
-
-impl FromStr for Register {
-    type Err = Error;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let bytes = s.as_bytes();
-        ensure!(bytes.len() == 1);
-        ensure!(bytes[0].is_ascii_lowercase());
-        Ok(Self(bytes[0] - b'a'))
-    }
-}
-
-#[derive(Clone, Copy)]
-enum Value {
-    Immediate(Integer),
-    Register(Register),
+#[derive(Clone, Copy, Parse)]
+enum Operation {
+    #[parse("set {0} {1}")]
+    Set(Register, Operand<Integer>),
+    #[parse("add {0} {1}")]
+    Add(Register, Operand<Integer>),
+    #[parse("mul {0} {1}")]
+    Mul(Register, Operand<Integer>),
+    #[parse("mod {0} {1}")]
+    Mod(Register, Operand<Integer>),
+    #[parse("jgz {0} {1}")]
+    Jgz(Operand<Integer>, Operand<Integer>),
+    #[parse("snd {0}")]
+    Snd(Operand<Integer>),
+    #[parse("rcv {0}")]
+    Rcv(Register),
 }
 
-impl Value {
-    fn eval(self, registers: &Registers) -> Integer {
+impl vm::Instruction for Operation {
+    type Word = Integer;
+
+    #[inline]
+    fn execute<const N: usize>(
+        self,
+        _pc: usize,
+        _program: &mut [Self],
+        registers: &mut Registers<Integer, N>,
+        io: &mut impl Io<Integer>,
+    ) -> Result<Control> {
         match self {
-            Self::Immediate(v) => v,
-            Self::Register(r) => registers[r],
-        }
-    }
-}
-
-impl FromStr for Value {
-    type Err = Error;
-
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if s.len() == 1 && s.as_bytes()[0].is_ascii_lowercase() {
-            s.parse().map(Self::Register)
-        } else {
-            Ok(Self::Immediate(s.parse()?))
+            Self::Set(register, value) => registers[register] = value.eval(registers),
+            Self::Add(register, value) => registers[register] += value.eval(registers),
+            Self::Mul(register, value) => registers[register] *= value.eval(registers),
+            Self::Mod(register, value) => {
+                registers[register] = registers[register]
+                    .checked_rem(value.eval(registers))
+                    .context("modulo by zero")?;
+            }
+            Self::Jgz(value1, value2) => {
+                if value1.eval(registers) > 0 {
+                    return Ok(Control::Jump(value2.eval(registers) as isize));
+                }
+            }
+            Self::Snd(value) => {
+                if io.output(value.eval(registers)).is_break() {
+                    return Ok(Control::Stop);
+                }
+            }
+            Self::Rcv(register) => match io.input(registers[register]) {
+                ControlFlow::Continue(None) => {}
+                ControlFlow::Continue(Some(value)) => registers[register] = value,
+                ControlFlow::Break(()) => return Ok(Control::Wait),
+            },
         }
+        Ok(Control::Next)
     }
-}
-
-#[derive(Clone, Copy)]
-enum Operation {
-    Set(Register, Value),
-    Add(Register, Value),
-    Mul(Register, Value),
-    Mod(Register, Value),
-    Jgz(Value, Value),
-    Snd(Value),
-    Rcv(Register),
-}
-
-impl FromStr for Operation {
-    type Err = Error;
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let code = &s.as_bytes()[..3];
-        let tail = &s[4..];
-        if code == b"snd" {
-            Ok(Self::Snd(tail.parse()?))
-        } else if code == b"rcv" {
-            Ok(Self::Rcv(tail.parse()?))
-        } else {
-            let (op1, op2) = tail.split_once(' ').context("expected two operands")?;
-            match code {
-                b"set" => Ok(Self::Set(op1.parse()?, op2.parse()?)),
-                b"add" => Ok(Self::Add(op1.parse()?, op2.parse()?)),
-                b"mul" => Ok(Self::Mul(op1.parse()?, op2.parse()?)),
-                b"mod" => Ok(Self::Mod(op1.parse()?, op2.parse()?)),
-                b"jgz" => Ok(Self::Jgz(op1.parse()?, op2.parse()?)),
-                _ => bail!("unknown operation"),
-            }
+    fn registers(self) -> impl IntoIterator<Item = Register> {
+        match self {
+            Self::Set(register, value)
+            | Self::Add(register, value)
+            | Self::Mul(register, value)
+            | Self::Mod(register, value) => [Some(register), value.register()],
+            Self::Jgz(value1, value2) => [value1.register(), value2.register()],
+            Self::Snd(value) => [value.register(), None],
+            Self::Rcv(register) => [Some(register), None],
         }
+        .into_iter()
+        .flatten()
     }
 }
@@ -1,69 +1,54 @@
 use register::register;
+use utils::geom::{Direction, Point};
 
 #[register]
 fn run(input: &str) -> (String, usize) {
     let grid: Vec<_> = input.lines().map(str::as_bytes).collect();
-    let on_path = |x: usize, y: usize| {
-        grid.get(x)
-            .and_then(|row| row.get(y))
-            .is_some_and(|&c| c != b' ')
+    let cell = |pos: Point<usize>| {
+        grid.get(pos.y)
+            .and_then(|row| row.get(pos.x))
+            .copied()
+            .filter(|&c| c != b' ')
     };
 
-    let mut x = 0;
-    let mut y = grid[0].iter().position(|&c| c != b' ').unwrap();
+    let mut pos = Point::new(grid[0].iter().position(|&c| c != b' ').unwrap(), 0);
     let mut dir = Direction::Down;
     let mut part1 = String::new();
     let mut part2 = 0;
-    loop {
+    while let Some(c) = cell(pos) {
         part2 += 1;
-        if grid[x][y].is_ascii_uppercase() {
-            part1.push(char::from(grid[x][y]));
+        if c.is_ascii_uppercase() {
+            part1.push(char::from(c));
         }
 
-        let (nx, ny) = dir.next(x, y);
-        if on_path(nx, ny) {
-            (x, y) = (nx, ny);
-        } else {
-            let Some((nx, ny, ndir)) = dir
-                .turns(x, y)
-                .into_iter()
-                .find(|&(nx, ny, _)| on_path(nx, ny))
-            else {
-                break;
-            };
-            (x, y, dir) = (nx, ny, ndir);
-        }
+        let next = [dir, dir.turn_left(), dir.turn_right()]
+            .into_iter()
+            .filter_map(|dir2| Some((dir2.checked_apply(pos)?, dir2)))
+            .find(|&(pos2, _)| cell(pos2).is_some());
+        let Some((pos2, dir2)) = next else {
+            break;
+        };
+        (pos, dir) = (pos2, dir2);
     }
 
     (part1, part2)
 }
 
-#[derive(Clone, Copy)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-impl Direction {
-    fn next(self, x: usize, y: usize) -> (usize, usize) {
-        match self {
-            Self::Up => (x.wrapping_sub(1), y),
-            Self::Down => (x + 1, y),
-            Self::Left => (x, y.wrapping_sub(1)),
-            Self::Right => (x, y + 1),
-        }
-    }
+    const INPUT: &str = concat!(
+        "     |          \n",
+        "     |  +--+    \n",
+        "     A  |  C    \n",
+        " F---|----E|--+ \n",
+        "     |  |  |  D \n",
+        "     +B-+  +--+ \n",
+    );
 
-    fn turns(self, x: usize, y: usize) -> [(usize, usize, Self); 2] {
-        let dirs = match self {
-            Self::Up | Self::Down => [Self::Left, Self::Right],
-            Self::Left | Self::Right => [Self::Up, Self::Down],
-        };
-        dirs.map(|d| {
-            let (nx, ny) = d.next(x, y);
-            (nx, ny, d)
-        })
+    #[test]
+    fn test() {
+        assert_eq!(run(INPUT), ("ABCDEF".to_owned(), 38));
     }
 }
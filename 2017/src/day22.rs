@@ -1,37 +1,33 @@
-use ndarray::Array2;
+use anyhow::Result;
 use register::register;
 use utils::{
-    grid,
+    geom::{Direction, Point},
+    grid::{GridView, InfiniteGrid},
     hash::{FastHashCollectionExt, FastHashMap},
 };
 
 #[register]
-fn run(input: &str) -> (usize, usize) {
-    let buffer = 200;
-    let (height, width) = grid::from_lines(input).dim();
-    let cells1 = Array2::from_elem((height + 2 * buffer, width + 2 * buffer), State1::default());
-    let cells2 = Array2::from_elem((height + 2 * buffer, width + 2 * buffer), State2::default());
-    let part1 = simulate::<State1>(input, cells1, buffer, 10_000);
-    let part2 = simulate::<State2>(input, cells2, buffer, 10_000_000);
-    (part1, part2)
+fn run(input: &str) -> Result<(usize, usize)> {
+    let part1 = simulate(input, InfiniteGrid::new(State1::default()), 10_000)?;
+    let part2 = simulate(input, InfiniteGrid::new(State2::default()), 10_000_000)?;
+    Ok((part1, part2))
 }
 
 #[register]
-fn no_guessed_limits(input: &str) -> (usize, usize) {
-    let offset = usize::MAX / 2;
-    let part1 = simulate::<State1>(input, FastHashMap::new(), offset, 10_000);
-    let part2 = simulate::<State2>(input, FastHashMap::new(), offset, 10_000_000);
-    (part1, part2)
+fn hash_map(input: &str) -> Result<(usize, usize)> {
+    let part1 = simulate::<State1>(input, FastHashMap::new(), 10_000)?;
+    let part2 = simulate::<State2>(input, FastHashMap::new(), 10_000_000)?;
+    Ok((part1, part2))
 }
 
-fn simulate<S: State>(input: &str, mut cells: impl Grid<S>, offset: usize, bursts: usize) -> usize {
-    let grid = grid::from_lines(input);
+fn simulate<S: State>(input: &str, mut cells: impl Cells<S>, bursts: usize) -> Result<usize> {
+    let grid = GridView::from_lines(input)?;
     for ((y, x), &c) in grid.indexed_iter() {
-        let cell = cells.get_mut((y + offset, x + offset));
+        let cell = cells.get_mut(Point::new(x as isize, y as isize));
         *cell = if c == b'#' { S::INFECTED } else { S::default() };
     }
 
-    let mut pos = (grid.nrows() / 2 + offset, grid.ncols() / 2 + offset);
+    let mut pos = Point::new(grid.width() as isize / 2, grid.height() as isize / 2);
     let mut dir = Direction::Up;
     let mut infections = 0;
     for _ in 0..bursts {
@@ -42,21 +38,21 @@ fn simulate<S: State>(input: &str, mut cells: impl Grid<S>, offset: usize, burst
         pos = dir.apply(pos);
     }
 
-    infections
+    Ok(infections)
 }
 
-trait Grid<T> {
-    fn get_mut(&mut self, pos: (usize, usize)) -> &mut T;
+trait Cells<T> {
+    fn get_mut(&mut self, pos: Point<isize>) -> &mut T;
 }
 
-impl<T> Grid<T> for Array2<T> {
-    fn get_mut(&mut self, pos: (usize, usize)) -> &mut T {
-        &mut self[pos]
+impl<T: Clone> Cells<T> for InfiniteGrid<T> {
+    fn get_mut(&mut self, pos: Point<isize>) -> &mut T {
+        self.get_mut(pos)
     }
 }
 
-impl<T: Default> Grid<T> for FastHashMap<(usize, usize), T> {
-    fn get_mut(&mut self, pos: (usize, usize)) -> &mut T {
+impl<T: Default> Cells<T> for FastHashMap<Point<isize>, T> {
+    fn get_mut(&mut self, pos: Point<isize>) -> &mut T {
         self.entry(pos).or_default()
     }
 }
@@ -124,42 +120,6 @@ impl State for State2 {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
-enum Direction {
-    Up,
-    Right,
-    Down,
-    Left,
-}
-
-impl Direction {
-    fn turn_left(self) -> Self {
-        match self {
-            Self::Up => Self::Left,
-            Self::Right => Self::Up,
-            Self::Down => Self::Right,
-            Self::Left => Self::Down,
-        }
-    }
-
-    fn opposite(self) -> Self {
-        self.turn_left().turn_left()
-    }
-
-    fn turn_right(self) -> Self {
-        self.turn_left().turn_left().turn_left()
-    }
-
-    fn apply(self, (y, x): (usize, usize)) -> (usize, usize) {
-        match self {
-            Self::Up => (y - 1, x),
-            Self::Right => (y, x + 1),
-            Self::Down => (y + 1, x),
-            Self::Left => (y, x - 1),
-        }
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -171,6 +131,7 @@ mod tests {
 
     #[test]
     fn test() {
-        assert_eq!(run(INPUT), (5587, 2_511_944));
+        assert_eq!(run(INPUT).unwrap(), (5587, 2_511_944));
+        assert_eq!(hash_map(INPUT).unwrap(), (5587, 2_511_944));
     }
 }
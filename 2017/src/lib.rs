@@ -20,4 +20,3 @@ mod day19;
 mod day20;
 mod day21;
 mod day22;
-mod knot_hash;
@@ -1,15 +1,18 @@
 use anyhow::{Result, ensure};
 use register::register;
-use utils::input::Input;
+use utils::{
+    graph,
+    input::{Input, sections},
+};
 
 // Assume that node indices in the input are in the range [0, N).
 const N: usize = 100;
 
 #[register]
 fn run(input: &str) -> Result<(usize, usize)> {
-    let mut lines = input.lines();
+    let [rules, updates] = sections(input)?;
     let mut adj_rev = [0_u128; N];
-    for line in lines.by_ref().take_while(|line| !line.is_empty()) {
+    for line in rules.lines() {
         let [from, to] = line.unsigned_integers_n::<usize, 2>()?;
         ensure!(from < N && to < N, "node index out of bounds");
         adj_rev[to] |= 1 << from;
@@ -17,11 +20,9 @@ fn run(input: &str) -> Result<(usize, usize)> {
 
     let mut part1 = 0;
     let mut part2 = 0;
-    for line in lines {
-        let nodes: Vec<_> = line
-            .split(',')
-            .map(|s| s.parse::<usize>().unwrap())
-            .collect();
+    for line in updates.lines() {
+        let nodes: Vec<usize> = line.split(',').map(str::parse).collect::<Result<_, _>>()?;
+        ensure!(nodes.iter().all(|&v| v < N), "node index out of bounds");
 
         // The inputs seem to be designed s.t. the subgraph induced `nodes` is
         // a tournament graph, i.e., there is exactly one directed edge between
@@ -60,6 +61,56 @@ fn run(input: &str) -> Result<(usize, usize)> {
     Ok((part1, part2))
 }
 
+/// Sorts every update with a general topological sort of the induced
+/// subgraph, without relying on it being a tournament.
+#[register]
+fn topological_sort(input: &str) -> Result<(usize, usize)> {
+    let [rules, updates] = sections(input)?;
+    let mut adj = vec![Vec::new(); N];
+    for line in rules.lines() {
+        let [from, to] = line.unsigned_integers_n::<usize, 2>()?;
+        ensure!(from < N && to < N, "node index out of bounds");
+        adj[from].push(to);
+    }
+
+    let mut part1 = 0;
+    let mut part2 = 0;
+    let mut local_index = [usize::MAX; N];
+    for line in updates.lines() {
+        let nodes: Vec<usize> = line.split(',').map(str::parse).collect::<Result<_, _>>()?;
+        ensure!(nodes.iter().all(|&v| v < N), "node index out of bounds");
+        for (i, &v) in nodes.iter().enumerate() {
+            local_index[v] = i;
+        }
+        let sub_adj: Vec<Vec<usize>> = nodes
+            .iter()
+            .map(|&v| {
+                adj[v]
+                    .iter()
+                    .filter_map(|&v2| {
+                        Some(local_index[v2]).filter(|&i| i < nodes.len() && nodes[i] == v2)
+                    })
+                    .collect()
+            })
+            .collect();
+
+        // Topological orders are not unique, so decide correctness on the
+        // update itself and only use the order to find the sorted median.
+        let correct = sub_adj
+            .iter()
+            .enumerate()
+            .all(|(i, adj)| adj.iter().all(|&j| i < j));
+        if correct {
+            part1 += nodes[nodes.len() / 2];
+        } else {
+            let order = graph::topological_sort(&sub_adj)?;
+            part2 += nodes[order[nodes.len() / 2]];
+        }
+    }
+
+    Ok((part1, part2))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -97,5 +148,19 @@ mod tests {
     #[test]
     fn test() {
         assert_eq!(run(INPUT).unwrap(), (143, 123));
+        assert_eq!(topological_sort(INPUT).unwrap(), (143, 123));
+    }
+
+    #[test]
+    fn unrelated_pages() {
+        let input = "\
+1|9
+1|2
+2|3
+
+7,1,9
+9,7
+3,2,1";
+        assert_eq!(topological_sort(input).unwrap(), (8, 2));
     }
 }
@@ -1,36 +1,32 @@
 use anyhow::{Context, Result, ensure};
 use rayon::iter::{IntoParallelIterator, ParallelIterator};
 use register::register;
+use utils::{geom::Direction, grid::Grid};
 
 #[register]
 fn run(input: &str) -> Result<(usize, usize)> {
-    let grid: Vec<_> = input.lines().map(|l| l.as_bytes().to_vec()).collect();
-    let height = grid.len();
-    let width = grid[0].len();
-    let (y0, x0) = itertools::iproduct!(0..height, 0..width)
-        .find(|&(y, x)| grid[y][x] == b'^')
-        .context("no start")?;
-    let mut seen = vec![vec![0; width]; height];
-    let input_loops = simulate(&grid, y0, x0, &mut seen);
+    let grid = Grid::parse(input, |byte| byte)?;
+    let start = grid.find(&b'^').context("no start")?;
+    let mut seen = Grid::new(grid.height(), grid.width(), 0);
+    let input_loops = simulate(&grid, start, &mut seen);
     ensure!(!input_loops, "input loops already");
 
-    let candidates: Vec<_> = itertools::iproduct!(0..height, 0..width)
-        .filter(|&(y, x)| seen[y][x] != 0 && (y, x) != (y0, x0))
+    let candidates: Vec<_> = grid
+        .positions()
+        .filter(|&pos| seen[pos] != 0 && pos != start)
         .collect();
     let part1 = candidates.len() + 1;
 
     let part2 = candidates
         .into_par_iter()
         .map_init(
-            || (grid.clone(), vec![vec![0; width]; height]),
-            |(grid, seen), (y, x)| {
-                for row in &mut *seen {
-                    row.fill(0);
-                }
+            || (grid.clone(), seen.clone()),
+            |(grid, seen), pos| {
+                seen.fill(0);
 
-                grid[y][x] = b'#';
-                let loops = simulate(grid, y0, x0, seen);
-                grid[y][x] = b'.';
+                grid[pos] = b'#';
+                let loops = simulate(grid, start, seen);
+                grid[pos] = b'.';
 
                 loops
             },
@@ -41,58 +37,23 @@ fn run(input: &str) -> Result<(usize, usize)> {
     Ok((part1, part2))
 }
 
-fn simulate(grid: &[Vec<u8>], y0: usize, x0: usize, seen: &mut [Vec<u8>]) -> bool {
-    let height = grid.len();
-    let width = grid[0].len();
-    let mut y = y0;
-    let mut x = x0;
+fn simulate(grid: &Grid<u8>, start: (usize, usize), seen: &mut Grid<u8>) -> bool {
+    let mut pos = start;
     let mut direction = Direction::Up;
     loop {
-        if seen[y][x] & 1 << direction as u8 != 0 {
+        if seen[pos] & 1 << direction as u8 != 0 {
             break true;
         }
-        seen[y][x] |= 1 << direction as u8;
+        seen[pos] |= 1 << direction as u8;
 
-        let next = direction
-            .step(y, x)
-            .filter(|&(y2, x2)| y2 < height && x2 < width);
-        let Some((y2, x2)) = next else {
+        let Some(pos2) = grid.offset(pos, direction) else {
             break false;
         };
 
-        if grid[y2][x2] == b'#' {
-            direction = direction.rotate_right();
+        if grid[pos2] == b'#' {
+            direction = direction.turn_right();
         } else {
-            (y, x) = (y2, x2);
-        }
-    }
-}
-
-#[derive(Debug, Clone, Copy)]
-#[repr(u8)]
-enum Direction {
-    Up,
-    Right,
-    Down,
-    Left,
-}
-
-impl Direction {
-    fn step(self, y: usize, x: usize) -> Option<(usize, usize)> {
-        match self {
-            Self::Up => y.checked_sub(1).map(|yi| (yi, x)),
-            Self::Right => Some((y, x + 1)),
-            Self::Down => Some((y + 1, x)),
-            Self::Left => x.checked_sub(1).map(|xi| (y, xi)),
-        }
-    }
-
-    fn rotate_right(self) -> Self {
-        match self {
-            Self::Up => Self::Right,
-            Self::Right => Self::Down,
-            Self::Down => Self::Left,
-            Self::Left => Self::Up,
+            pos = pos2;
         }
     }
 }
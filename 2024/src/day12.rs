@@ -1,29 +1,31 @@
 use std::collections::VecDeque;
 
+use anyhow::Result;
 use register::register;
+use utils::grid::{Grid, GridView, OFFSETS8};
 
 #[register]
-fn run(input: &str) -> (usize, usize) {
-    let grid: Vec<_> = input.lines().map(str::as_bytes).collect();
-    let height = grid.len();
-    let width = grid[0].len();
-
-    let mut seen = vec![vec![false; width]; height];
+fn run(input: &str) -> Result<(usize, usize)> {
+    let grid = GridView::from_lines(input)?;
+    let mut seen = Grid::new(grid.height(), grid.width(), false);
     let mut queue = VecDeque::new();
     let mut part1 = 0;
     let mut part2 = 0;
-    for (y0, x0) in itertools::iproduct!(0..height, 0..width) {
-        if seen[y0][x0] {
+    for pos0 in grid.positions() {
+        if seen[pos0] {
             continue;
         }
 
-        seen[y0][x0] = true;
-        queue.push_back((y0, x0));
+        seen[pos0] = true;
+        queue.push_back(pos0);
         let mut area = 0;
         let mut corners = 0;
         let mut perimeter = 0;
-        while let Some((y, x)) = queue.pop_front() {
-            let neighbors = neighbors(y, x, &grid);
+        while let Some(pos) = queue.pop_front() {
+            let neighbors = OFFSETS8.map(|offset| {
+                grid.offset(pos, offset)
+                    .filter(|&pos2| grid[pos2] == grid[pos])
+            });
             let non_diagonal = [0, 2, 4, 6].map(|i| neighbors[i]);
 
             area += 1;
@@ -42,10 +44,10 @@ fn run(input: &str) -> (usize, usize) {
                 .count();
             corners += outside_corners + inside_corners;
 
-            for (y2, x2) in non_diagonal.into_iter().flatten() {
-                if !seen[y2][x2] && grid[y2][x2] == grid[y][x] {
-                    seen[y2][x2] = true;
-                    queue.push_back((y2, x2));
+            for pos2 in non_diagonal.into_iter().flatten() {
+                if !seen[pos2] {
+                    seen[pos2] = true;
+                    queue.push_back(pos2);
                 }
             }
         }
@@ -54,25 +56,7 @@ fn run(input: &str) -> (usize, usize) {
         part2 += area * corners;
     }
 
-    (part1, part2)
-}
-
-fn neighbors(y: usize, x: usize, grid: &[&[u8]]) -> [Option<(usize, usize)>; 8] {
-    let up = y > 0;
-    let right = x + 1 < grid[0].len();
-    let down = y + 1 < grid.len();
-    let left = x > 0;
-    [
-        up.then(|| (y - 1, x)),
-        (up && right).then(|| (y - 1, x + 1)),
-        right.then(|| (y, x + 1)),
-        (right && down).then(|| (y + 1, x + 1)),
-        down.then(|| (y + 1, x)),
-        (down && left).then(|| (y + 1, x - 1)),
-        left.then(|| (y, x - 1)),
-        (left && up).then(|| (y - 1, x - 1)),
-    ]
-    .map(|neigh| neigh.filter(|&(y2, x2)| grid[y2][x2] == grid[y][x]))
+    Ok((part1, part2))
 }
 
 #[cfg(test)]
@@ -121,17 +105,17 @@ AAAAAA";
 
     #[test]
     fn part1() {
-        assert_eq!(run(INPUT1).0, 140);
-        assert_eq!(run(INPUT2).0, 772);
-        assert_eq!(run(INPUT3).0, 1930);
+        assert_eq!(run(INPUT1).unwrap().0, 140);
+        assert_eq!(run(INPUT2).unwrap().0, 772);
+        assert_eq!(run(INPUT3).unwrap().0, 1930);
     }
 
     #[test]
     fn part2() {
-        assert_eq!(run(INPUT1).1, 80);
-        assert_eq!(run(INPUT2).1, 436);
-        assert_eq!(run(INPUT4).1, 236);
-        assert_eq!(run(INPUT5).1, 368);
-        assert_eq!(run(INPUT3).1, 1206);
+        assert_eq!(run(INPUT1).unwrap().1, 80);
+        assert_eq!(run(INPUT2).unwrap().1, 436);
+        assert_eq!(run(INPUT4).unwrap().1, 236);
+        assert_eq!(run(INPUT5).unwrap().1, 368);
+        assert_eq!(run(INPUT3).unwrap().1, 1206);
     }
 }
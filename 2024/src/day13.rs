@@ -1,27 +1,48 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use itertools::Itertools;
 use register::register;
-use utils::input::Input;
+use utils::{
+    input::{self, Input},
+    linalg::{self, LinearSystem},
+};
 
 const OFFSET: i64 = 10_000_000_000_000;
 
 #[register]
 fn run(input: &str) -> Result<(i64, i64)> {
-    input
-        .lines()
-        .filter(|line| !line.is_empty())
-        .tuples()
-        .try_fold((0, 0), |(part1, part2), (l1, l2, l3)| {
-            let [x1, x2] = l1.signed_integers_n()?;
-            let [y1, y2] = l2.signed_integers_n()?;
-            let [z1, z2] = l3.signed_integers_n()?;
-            let part1 = part1 + solve(z1, z2, x1, y1, x2, y2).unwrap_or(0);
-            let part2 = part2 + solve(z1 + OFFSET, z2 + OFFSET, x1, y1, x2, y2).unwrap_or(0);
-            Ok((part1, part2))
-        })
+    run_with(input, solve_exact)
 }
 
-fn solve(z1: i64, z2: i64, x1: i64, y1: i64, x2: i64, y2: i64) -> Option<i64> {
+#[register]
+fn cramer(input: &str) -> Result<(i64, i64)> {
+    run_with(input, solve_cramer)
+}
+
+fn run_with(
+    input: &str,
+    solve: fn([i64; 2], [i64; 2], [i64; 2]) -> Option<i64>,
+) -> Result<(i64, i64)> {
+    input::blocks(input).try_fold((0, 0), |(part1, part2), block| {
+        let (l1, l2, l3) = block
+            .lines()
+            .collect_tuple()
+            .context("expected a machine of three lines")?;
+        let a = l1.signed_integers_n()?;
+        let b = l2.signed_integers_n()?;
+        let [z1, z2] = l3.signed_integers_n()?;
+        let part1 = part1 + solve(a, b, [z1, z2]).unwrap_or(0);
+        let part2 = part2 + solve(a, b, [z1 + OFFSET, z2 + OFFSET]).unwrap_or(0);
+        Ok((part1, part2))
+    })
+}
+
+fn solve_exact([x1, x2]: [i64; 2], [y1, y2]: [i64; 2], prize: [i64; 2]) -> Option<i64> {
+    let system = LinearSystem::new(&[[x1, y1], [x2, y2]], &prize);
+    let [a, b] = linalg::to_integers(&system.solution()?)?.try_into().ok()?;
+    (a >= 0 && b >= 0).then_some(3 * a + b)
+}
+
+fn solve_cramer([x1, x2]: [i64; 2], [y1, y2]: [i64; 2], [z1, z2]: [i64; 2]) -> Option<i64> {
     let b = (z1 * x2 - z2 * x1) / (y1 * x2 - y2 * x1);
     let a = (z1 - b * y1) / x1;
     let valid = a * x1 + b * y1 == z1 && a * x2 + b * y2 == z2;
@@ -52,5 +73,6 @@ Prize: X=18641, Y=10279";
     #[test]
     fn part1() {
         assert_eq!(run(INPUT).unwrap().0, 480);
+        assert_eq!(cramer(INPUT).unwrap().0, 480);
     }
 }
@@ -1,8 +1,7 @@
-use anyhow::Result;
+use anyhow::{Context, Result};
 use itertools::Itertools;
-use num::Integer;
 use register::register;
-use utils::input::Input;
+use utils::{input::Input, num::crt};
 
 #[register]
 fn run(input: &str) -> Result<(usize, u16)> {
@@ -42,11 +41,10 @@ fn run_parameterized(input: &str, width: u16, height: u16) -> Result<(usize, u16
 
     let tree_x = find_min_variance(&robots, width, |robot, time| robot.x(time, width));
     let tree_y = find_min_variance(&robots, height, |robot, time| robot.y(time, height));
-    let [tree_x, tree_y, width, height] = [tree_x, tree_y, width, height].map(i32::from);
-    let gcd = width.extended_gcd(&height);
-    assert_eq!(gcd.gcd, 1);
-    let part2 = (tree_y - tree_x) % height * gcd.x % height * width + tree_x;
-    let part2 = part2.rem_euclid(width * height) as u16;
+    let [tree_x, tree_y, width, height] = [tree_x, tree_y, width, height].map(i64::from);
+    let (part2, _) =
+        crt([(tree_x, width), (tree_y, height)]).context("alignments never coincide")?;
+    let part2 = part2 as u16;
 
     Ok((part1, part2))
 }
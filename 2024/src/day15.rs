@@ -1,23 +1,31 @@
-use std::fmt::{self, Display, Formatter};
+use std::{
+    fmt::{self, Display, Formatter},
+    iter,
+};
 
+use anyhow::Result;
 use arrayvec::ArrayVec;
 use itertools::Itertools;
 use register::register;
 use tinybitset::TinyBitSet;
-use utils::hash::{FastHashCollectionExt, FastHashSet};
+use utils::{
+    geom::{Direction, Point},
+    hash::{FastHashCollectionExt, FastHashSet},
+    input::sections,
+};
 
 type RowBitSet1 = TinyBitSet<u64, 1>;
 type RowBitSet2 = TinyBitSet<u128, 1>;
 type RowArrayVec<T> = ArrayVec<T, 100>;
 
 #[register]
-fn run(input: &str) -> (usize, usize) {
-    let mut lines = input.lines().map(str::as_bytes);
-    let mut robot = (0, 0);
+fn run(input: &str) -> Result<(usize, usize)> {
+    let [map, moves] = sections(input)?;
+    let mut robot = Point::new(0, 0);
     let mut width = 0;
-    let (walls1, boxes1, grid2): (Vec<_>, Vec<_>, Vec<_>) = lines
-        .by_ref()
-        .take_while(|line| !line.is_empty())
+    let (walls1, boxes1, grid2): (Vec<_>, Vec<_>, Vec<_>) = map
+        .lines()
+        .map(str::as_bytes)
         .enumerate()
         .map(|(y, line)| {
             width = line.len();
@@ -31,7 +39,7 @@ fn run(input: &str) -> (usize, usize) {
                         [Field::Wall, Field::Wall]
                     }
                     b'@' => {
-                        robot = (x, y);
+                        robot = Point::new(x, y);
                         [Field::Empty, Field::Empty]
                     }
                     b'O' => {
@@ -54,27 +62,21 @@ fn run(input: &str) -> (usize, usize) {
         boxes: boxes1,
     };
     let mut state2 = State2 {
-        robot: (2 * robot.0, robot.1),
+        robot: Point::new(2 * robot.x, robot.y),
         grid: grid2,
     };
 
-    for direction in lines.flatten() {
-        let direction = match direction {
-            b'^' => Direction::Up,
-            b'v' => Direction::Down,
-            b'<' => Direction::Left,
-            _ => Direction::Right,
-        };
-
+    for &direction in moves.lines().flat_map(str::as_bytes) {
+        let direction = Direction::try_from(direction)?;
         state1.process_move(direction);
         state2.process_move(direction);
     }
 
-    (state1.coordinate_sum(), state2.coordinate_sum())
+    Ok((state1.coordinate_sum(), state2.coordinate_sum()))
 }
 
 struct State1 {
-    robot: (usize, usize),
+    robot: Point<usize>,
     width: usize,
     walls: Vec<RowBitSet1>,
     boxes: Vec<RowBitSet1>,
@@ -82,18 +84,16 @@ struct State1 {
 
 impl State1 {
     fn process_move(&mut self, direction: Direction) {
-        let robot_target = direction.offset(self.robot, 1);
+        let robot_target = direction.apply(self.robot);
 
-        #[allow(clippy::maybe_infinite_iter)]
-        let end = (1..)
-            .map(|i| direction.offset(self.robot, i))
-            .find(|&(x, y)| self.walls[y][x] || !self.boxes[y][x])
+        let end = ray(self.robot, direction)
+            .find(|&Point { x, y }| self.walls[y][x] || !self.boxes[y][x])
             .unwrap();
-        if !self.walls[end.1][end.0] {
+        if !self.walls[end.y][end.x] {
             self.robot = robot_target;
             if end != robot_target {
-                self.boxes[end.1].insert(end.0);
-                self.boxes[self.robot.1].remove(self.robot.0);
+                self.boxes[end.y].insert(end.x);
+                self.boxes[self.robot.y].remove(self.robot.x);
             }
         }
     }
@@ -111,7 +111,7 @@ impl Display for State1 {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         for (y, (walls, boxes)) in self.walls.iter().zip(&self.boxes).enumerate() {
             for x in 0..self.width {
-                let c = if (x, y) == self.robot {
+                let c = if Point::new(x, y) == self.robot {
                     '@'
                 } else if walls[x] {
                     '#'
@@ -130,34 +130,32 @@ impl Display for State1 {
 }
 
 struct State2 {
-    robot: (usize, usize),
+    robot: Point<usize>,
     grid: Vec<RowArrayVec<Field>>,
 }
 
 impl State2 {
     fn process_move(&mut self, direction: Direction) {
-        if matches!(direction, Direction::Left | Direction::Right) {
-            let robot_target = direction.offset(self.robot, 1);
+        if !direction.is_vertical() {
+            let robot_target = direction.apply(self.robot);
 
-            #[allow(clippy::maybe_infinite_iter)]
-            let end = (1..)
-                .map(|i| direction.offset(self.robot, i))
-                .find(|&(x, y)| matches!(self.grid[y][x], Field::Wall | Field::Empty))
+            let end = ray(self.robot, direction)
+                .find(|&Point { x, y }| matches!(self.grid[y][x], Field::Wall | Field::Empty))
                 .unwrap();
-            if self.grid[end.1][end.0] == Field::Empty {
+            if self.grid[end.y][end.x] == Field::Empty {
                 if direction == Direction::Left {
-                    self.grid[end.1][end.0..self.robot.0].rotate_left(1);
+                    self.grid[end.y][end.x..self.robot.x].rotate_left(1);
                 } else {
-                    self.grid[end.1][robot_target.0..=end.0].rotate_right(1);
+                    self.grid[end.y][robot_target.x..=end.x].rotate_right(1);
                 }
                 self.robot = robot_target;
             }
             return;
         }
 
-        let mut x_coords = RowBitSet2::from_iter([self.robot.0]);
+        let mut x_coords = RowBitSet2::from_iter([self.robot.x]);
         let mut boxes = FastHashSet::new();
-        let y_coords = (1..).map(|i| direction.offset(self.robot, i).1);
+        let y_coords = ray(self.robot, direction).map(|pos| pos.y);
         let mut blocked = false;
         'outer: for y in y_coords {
             let mut any_box = false;
@@ -190,11 +188,11 @@ impl State2 {
                 self.grid[y][x + 1] = Field::Empty;
             }
             for &(x, y) in &boxes {
-                let (x2, y2) = direction.offset((x, y), 1);
+                let Point { x: x2, y: y2 } = direction.apply(Point::new(x, y));
                 self.grid[y2][x2] = Field::BoxLeft;
                 self.grid[y2][x2 + 1] = Field::BoxRight;
             }
-            self.robot = direction.offset(self.robot, 1);
+            self.robot = direction.apply(self.robot);
         }
     }
 
@@ -217,7 +215,7 @@ impl Display for State2 {
         for (y, row) in self.grid.iter().enumerate() {
             for (x, &field) in row.iter().enumerate() {
                 let c = match field {
-                    _ if (x, y) == self.robot => '@',
+                    _ if Point::new(x, y) == self.robot => '@',
                     Field::Empty => '.',
                     Field::Wall => '#',
                     Field::BoxLeft => '[',
@@ -240,23 +238,12 @@ enum Field {
     BoxRight,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Direction {
-    Up,
-    Down,
-    Left,
-    Right,
-}
-
-impl Direction {
-    fn offset(self, (x, y): (usize, usize), steps: usize) -> (usize, usize) {
-        match self {
-            Self::Up => (x, y - steps),
-            Self::Down => (x, y + steps),
-            Self::Left => (x - steps, y),
-            Self::Right => (x + steps, y),
-        }
-    }
+/// Positions visited when walking from `start` in `direction`, excluding
+/// `start` itself.
+fn ray(start: Point<usize>, direction: Direction) -> impl Iterator<Item = Point<usize>> {
+    iter::successors(direction.checked_apply(start), move |&pos| {
+        direction.checked_apply(pos)
+    })
 }
 
 #[cfg(test)]
@@ -311,25 +298,25 @@ v^^>>><<^^<>>^v^<v^vv<>v^<<>^<^v^v><^<<<><<^<v><v<>vv>>v><v^<vv<>v^<<^";
 
     #[test]
     fn small_part1() {
-        let (part1, _) = run(SMALL_INPUT1);
+        let (part1, _) = run(SMALL_INPUT1).unwrap();
         assert_eq!(part1, 2028);
     }
 
     #[test]
     fn large_part1() {
-        let (part1, _) = run(LARGE_INPUT);
+        let (part1, _) = run(LARGE_INPUT).unwrap();
         assert_eq!(part1, 10092);
     }
 
     #[test]
     fn small_part2() {
-        let (_, part2) = run(SMALL_INPUT2);
+        let (_, part2) = run(SMALL_INPUT2).unwrap();
         assert_eq!(part2, 105 + 207 + 306);
     }
 
     #[test]
     fn large_part2() {
-        let (_, part2) = run(LARGE_INPUT);
+        let (_, part2) = run(LARGE_INPUT).unwrap();
         assert_eq!(part2, 9021);
     }
 }
@@ -1,76 +1,110 @@
 use std::{cmp::Reverse, collections::BinaryHeap, thread};
 
+use anyhow::{Context, Result};
+use itertools::Itertools;
 use register::register;
+use utils::{
+    geom::{Direction, Point},
+    grid::{Grid, GridView},
+    rng::Rng,
+    search::Search,
+};
 
 #[register]
-fn run(input: &str) -> (usize, usize) {
-    let grid: Vec<_> = input.lines().map(str::as_bytes).collect();
-    let height = grid.len();
-    let width = grid[0].len();
-
-    let mut from = (usize::MAX, usize::MAX);
-    let mut to = (usize::MAX, usize::MAX);
-    for (y, row) in grid.iter().enumerate() {
-        for (x, &cell) in row.iter().enumerate() {
-            match cell {
-                b'S' => from = (y, x),
-                b'E' => to = (y, x),
-                _ => {}
-            }
-        }
+fn run(input: &str) -> Result<(usize, usize)> {
+    let grid = GridView::from_lines(input)?;
+    let from: Point<usize> = grid.find(&b'S').context("no start")?.into();
+    let to: Point<usize> = grid.find(&b'E').context("no end")?.into();
+
+    let reached = Search::new([(from, Direction::Right)])
+        .all_predecessors()
+        .dijkstra(
+            |&(pos, dir)| {
+                let moves = [
+                    ((pos, dir.turn_left()), 1000),
+                    ((pos, dir.turn_right()), 1000),
+                    ((dir.apply(pos), dir), 1),
+                ];
+                moves
+                    .into_iter()
+                    .filter(|&((pos2, _), _)| grid[pos2] != b'#')
+            },
+            |&(pos, _)| pos == to,
+        );
+    let part1 = reached.goal().context("no path")?.1;
+
+    let mut on_path = Grid::new(grid.height(), grid.width(), false);
+    for &(pos, _) in reached.on_shortest_paths() {
+        on_path[pos] = true;
     }
+    let part2 = on_path.iter().filter(|&&cell| cell).count();
+
+    Ok((part1, part2))
+}
+
+/// Runs two independent Dijkstras on separate threads, one from the start
+/// and one backwards from the end. Tiles on a best path are those where the
+/// costs from the start and to the end add up to the optimum.
+#[register]
+fn bidirectional(input: &str) -> Result<(usize, usize)> {
+    let grid = GridView::from_lines(input)?;
+    let from = grid.find(&b'S').context("no start")?.into();
+    let to = grid.find(&b'E').context("no end")?.into();
 
     let (from_start, to_end) = thread::scope(|scope| {
-        let from_start = scope.spawn(|| run_dijkstra(&grid, from, [Direction::East], to));
-        let to_end = scope.spawn(|| run_dijkstra(&grid, to, Direction::ALL, from));
+        let from_start =
+            scope.spawn(|| run_dijkstra(grid, (from, &[Direction::Right]), (to, &Direction::ALL)));
+        // Walking backwards, we have to arrive at the start facing left
+        let to_end =
+            scope.spawn(|| run_dijkstra(grid, (to, &Direction::ALL), (from, &[Direction::Left])));
         (from_start.join().unwrap(), to_end.join().unwrap())
     });
-    let part1 = from_start[to.0][to.1].into_iter().min().unwrap();
-    let part2 = itertools::iproduct!(0..height, 0..width)
-        .filter(|&(y, x)| {
+    let part1 = from_start[to].into_iter().min().unwrap();
+    let part2 = grid
+        .positions()
+        .filter(|&pos| {
             Direction::ALL.into_iter().any(|dir| {
-                from_start[y][x][dir as usize] + to_end[y][x][dir.opposite() as usize] == part1
+                from_start[pos][dir as usize] + to_end[pos][dir.opposite() as usize] == part1
             })
         })
         .count();
 
-    (part1, part2)
+    Ok((part1, part2))
 }
 
 fn run_dijkstra(
-    grid: &[&[u8]],
-    from: (usize, usize),
-    from_dirs: impl IntoIterator<Item = Direction>,
-    to: (usize, usize),
-) -> Vec<Vec<[usize; 4]>> {
-    let height = grid.len();
-    let width = grid[0].len();
-
-    let mut cheapest = vec![vec![[usize::MAX / 2; 4]; width]; height];
+    grid: GridView<u8>,
+    (from, from_dirs): (Point<usize>, &[Direction]),
+    (to, to_dirs): (Point<usize>, &[Direction]),
+) -> Grid<[usize; 4]> {
+    let mut cheapest = Grid::new(grid.height(), grid.width(), [usize::MAX / 2; 4]);
     let mut queue = BinaryHeap::new();
-    for dir in from_dirs {
-        cheapest[from.0][from.1][dir as usize] = 0;
+    for &dir in from_dirs {
+        cheapest[from][dir as usize] = 0;
         queue.push((Reverse(0), from, dir));
     }
 
     let mut upper_bound = usize::MAX;
     while let Some((Reverse(cost), pos, dir)) = queue.pop() {
-        if pos == to {
-            upper_bound = cost;
+        if pos == to && to_dirs.contains(&dir) {
+            upper_bound = upper_bound.min(cost);
         }
         if cost > upper_bound {
             break;
         }
-        if cheapest[pos.0][pos.1][dir as usize] < cost {
+        if cheapest[pos][dir as usize] < cost {
             continue;
         }
 
-        let turns = dir.turn();
-        let moves = [(pos, turns[0]), (pos, turns[1]), (dir.offset(pos), dir)];
+        let moves = [
+            (pos, dir.turn_left()),
+            (pos, dir.turn_right()),
+            (dir.apply(pos), dir),
+        ];
         for (pos2, dir2) in moves {
             let cost2 = cost + if dir == dir2 { 1 } else { 1000 };
-            if grid[pos2.0][pos2.1] != b'#' && cost2 < cheapest[pos2.0][pos2.1][dir2 as usize] {
-                cheapest[pos2.0][pos2.1][dir2 as usize] = cost2;
+            if grid[pos2] != b'#' && cost2 < cheapest[pos2][dir2 as usize] {
+                cheapest[pos2][dir2 as usize] = cost2;
                 queue.push((Reverse(cost2), pos2, dir2));
             }
         }
@@ -79,41 +113,30 @@ fn run_dijkstra(
     cheapest
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
-enum Direction {
-    North,
-    East,
-    South,
-    West,
-}
-
-impl Direction {
-    const ALL: [Self; 4] = [Self::North, Self::East, Self::South, Self::West];
-
-    fn turn(self) -> [Self; 2] {
-        match self {
-            Self::North | Self::South => [Self::West, Self::East],
-            Self::East | Self::West => [Self::North, Self::South],
-        }
-    }
-
-    fn opposite(self) -> Self {
-        match self {
-            Self::North => Self::South,
-            Self::East => Self::West,
-            Self::South => Self::North,
-            Self::West => Self::East,
-        }
-    }
-
-    fn offset(self, (y, x): (usize, usize)) -> (usize, usize) {
-        match self {
-            Self::North => (y - 1, x),
-            Self::East => (y, x + 1),
-            Self::South => (y + 1, x),
-            Self::West => (y, x - 1),
-        }
-    }
+#[register(generator)]
+fn random_maze(seed: u64, size: usize) -> String {
+    let mut rng = Rng::new(seed);
+    let height = 4 + rng.index(size);
+    let width = 4 + rng.index(size);
+    (0..height)
+        .map(|y| {
+            (0..width)
+                .map(|x| {
+                    if y == 0 || x == 0 || y == height - 1 || x == width - 1 {
+                        '#'
+                    } else if (y, x) == (height - 2, 1) {
+                        'S'
+                    } else if (y, x) == (1, width - 2) {
+                        'E'
+                    } else if rng.chance(0.3) {
+                        '#'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect::<String>()
+        })
+        .join("\n")
 }
 
 #[cfg(test)]
@@ -158,21 +181,39 @@ mod tests {
 
     #[test]
     fn part1_maze1() {
-        assert_eq!(run(INPUT1).0, 7036);
+        assert_eq!(run(INPUT1).unwrap().0, 7036);
+        assert_eq!(bidirectional(INPUT1).unwrap().0, 7036);
     }
 
     #[test]
     fn part1_maze2() {
-        assert_eq!(run(INPUT2).0, 11048);
+        assert_eq!(run(INPUT2).unwrap().0, 11048);
+        assert_eq!(bidirectional(INPUT2).unwrap().0, 11048);
     }
 
     #[test]
     fn part2_maze1() {
-        assert_eq!(run(INPUT1).1, 45);
+        assert_eq!(run(INPUT1).unwrap().1, 45);
+        assert_eq!(bidirectional(INPUT1).unwrap().1, 45);
     }
 
     #[test]
     fn part2_maze2() {
-        assert_eq!(run(INPUT2).1, 64);
+        assert_eq!(run(INPUT2).unwrap().1, 64);
+        assert_eq!(bidirectional(INPUT2).unwrap().1, 64);
+    }
+
+    #[test]
+    fn fuzz_1792398786() {
+        const INPUT: &str = "\
+######
+####E#
+#....#
+#.#.##
+#S...#
+######";
+
+        let main = register::normalize(run(INPUT)).unwrap();
+        assert_eq!(register::normalize(bidirectional(INPUT)).unwrap(), main);
     }
 }
@@ -1,20 +1,28 @@
-use itertools::Itertools;
+use anyhow::{Context, Result, ensure};
 use joinery::Joinable;
 use register::register;
-use utils::input::Input;
+use utils::input::LineReader;
 
 #[register]
-fn run(input: &str) -> (String, u64) {
+fn run(input: &str) -> Result<(String, u64)> {
     run_testable(input, false)
 }
 
-fn run_testable(input: &str, skip_part2: bool) -> (String, u64) {
-    let (l1, l2, l3, _, l5) = input.lines().collect_tuple().unwrap();
-    let registers = [l1, l2, l3].map(|line| line.unsigned_integers_n::<u64, 1>().unwrap()[0]);
-    let program: Vec<_> = l5[9..]
-        .split(',')
-        .map(|s| s.parse::<u8>().unwrap())
-        .collect();
+fn run_testable(input: &str, skip_part2: bool) -> Result<(String, u64)> {
+    let mut lines = LineReader::new(input);
+    let mut registers = [0; 3];
+    for (name, register) in ["A", "B", "C"].into_iter().zip(&mut registers) {
+        let mut cursor = lines.next_line()?.cursor();
+        cursor.expect("Register ")?;
+        cursor.expect(name)?;
+        cursor.expect(":")?;
+        *register = cursor.parse()?;
+        cursor.finish()?;
+    }
+    lines.next_line()?;
+    let mut cursor = lines.next_line()?.cursor();
+    cursor.expect("Program: ")?;
+    let program: Vec<u8> = cursor.list("program", ",", None)?;
 
     let mut part1_output = Vec::new();
     simulate(&program, registers, |i| {
@@ -26,12 +34,14 @@ fn run_testable(input: &str, skip_part2: bool) -> (String, u64) {
     let part2 = if skip_part2 {
         0
     } else {
-        assert_eq!(registers[1], 0);
-        assert_eq!(registers[2], 0);
-        reconstruct_part2(&program, 0, 0).unwrap()
+        ensure!(
+            registers[1] == 0 && registers[2] == 0,
+            "registers B and C must start at zero"
+        );
+        reconstruct_part2(&program, 0, 0).context("no initial value outputs the program")?
     };
 
-    (part1, part2)
+    Ok((part1, part2))
 }
 
 fn reconstruct_part2(program: &[u8], length: usize, value: u64) -> Option<u64> {
@@ -56,10 +66,7 @@ fn reconstruct_part2(program: &[u8], length: usize, value: u64) -> Option<u64> {
 
 fn simulate(program: &[u8], mut registers: [u64; 3], mut out: impl FnMut(u8) -> bool) {
     let mut ip = 0;
-    loop {
-        let Some(&opcode) = program.get(ip) else {
-            break;
-        };
+    while let Some(&opcode) = program.get(ip) {
         let Some(&operand) = program.get(ip + 1) else {
             break;
         };
@@ -131,11 +138,11 @@ Program: 0,3,5,4,3,0";
 
     #[test]
     fn part1() {
-        assert_eq!(run_testable(INPUT1, true).0, "4,6,3,5,6,3,5,2,1,0");
+        assert_eq!(run_testable(INPUT1, true).unwrap().0, "4,6,3,5,6,3,5,2,1,0");
     }
 
     #[test]
     fn part2() {
-        assert_eq!(run(INPUT2).1, 117_440);
+        assert_eq!(run(INPUT2).unwrap().1, 117_440);
     }
 }
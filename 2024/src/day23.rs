@@ -2,77 +2,28 @@ use std::fmt::{self, Display, Formatter};
 
 use itertools::Itertools;
 use register::register;
-use tinybitset::TinyBitSet;
+use utils::graph::bitset::BitsetGraph;
 
 const N: usize = 26 * 26;
 
-type AdjBitset = TinyBitSet<u64, 11>;
-
-#[allow(clippy::similar_names)]
 #[register]
 fn run(input: &str) -> (usize, String) {
-    let mut adj = [AdjBitset::new(); N];
+    let mut graph = BitsetGraph::<{ N.div_ceil(64) }>::new(N);
     for line in input.lines() {
         let line = line.as_bytes();
-        let v1 = parse_node(line[0], line[1]);
-        let v2 = parse_node(line[3], line[4]);
-        adj[v1].insert(v2);
-        adj[v2].insert(v1);
+        graph.add_edge(parse_node(line[0], line[1]), parse_node(line[3], line[4]));
     }
 
-    let v_ta = parse_node(b't', b'a');
-    let v_ua = parse_node(b'u', b'a');
-    let part1 = (v_ta..v_ua)
-        .map(|v1| {
-            adj[v1]
-                .iter()
-                .filter(|v2| !(v1..v_ua).contains(v2))
-                .tuple_combinations()
-                .filter(|&(v2, v3)| adj[v2][v3])
-                .count()
-        })
-        .sum();
-
-    let mut state = State {
-        adj: &adj,
-        clique: Vec::new(),
-        largest: Vec::new(),
-    };
-    enumerate_cliques(&mut state, (0..N).collect());
-    let part2 = state.largest.into_iter().map(PrintNode).join(",");
+    let t_nodes = parse_node(b't', b'a')..parse_node(b'u', b'a');
+    let part1 = graph
+        .triangles()
+        .filter(|triangle| triangle.iter().any(|v| t_nodes.contains(v)))
+        .count();
+    let part2 = graph.max_clique().into_iter().map(PrintNode).join(",");
 
     (part1, part2)
 }
 
-struct State<'a> {
-    adj: &'a [AdjBitset; N],
-    clique: Vec<usize>,
-    largest: Vec<usize>,
-}
-
-fn enumerate_cliques(state: &mut State<'_>, mut ready: AdjBitset) {
-    if ready.is_empty() {
-        if state.clique.len() > state.largest.len() {
-            state.largest.clear();
-            state.largest.extend(state.clique.iter().copied());
-        }
-        return;
-    }
-
-    if state.clique.len() + ready.len() <= state.largest.len() {
-        return;
-    }
-
-    for next in ready {
-        ready.remove(next);
-
-        let next_ready = ready & state.adj[next];
-        state.clique.push(next);
-        enumerate_cliques(state, next_ready);
-        state.clique.pop();
-    }
-}
-
 fn parse_node(c1: u8, c2: u8) -> usize {
     usize::from(c1 - b'a') * 26 + usize::from(c2 - b'a')
 }
@@ -1,13 +1,17 @@
 use std::{collections::VecDeque, mem, str};
 
+use anyhow::{Context, Result, bail};
 use itertools::Itertools;
 use register::register;
-use utils::hash::{FastHashCollectionExt, FastHashMap};
+use utils::{
+    hash::{FastHashCollectionExt, FastHashMap},
+    input::sections,
+};
 
 #[register]
-fn run(input: &str) -> (u64, String) {
-    let (adj, wire_vals) = parse_graph(input);
-    (part1(&adj, wire_vals), part2(&adj))
+fn run(input: &str) -> Result<(u64, String)> {
+    let (adj, wire_vals) = parse_graph(input)?;
+    Ok((part1(&adj, wire_vals), part2(&adj)))
 }
 
 fn part1(adj: &FastHashMap<Wire, Vec<Gate>>, mut wire_vals: FastHashMap<Wire, bool>) -> u64 {
@@ -140,23 +144,26 @@ fn get_xor_and_gates(adj: &FastHashMap<Wire, Vec<Gate>>, wire: Wire) -> (Gate, G
     (gate1, gate2)
 }
 
-fn parse_graph(input: &str) -> (FastHashMap<Wire, Vec<Gate>>, FastHashMap<Wire, bool>) {
-    let mut lines = input.lines();
-    let input = lines
-        .by_ref()
-        .take_while(|line| !line.is_empty())
+type Graph = (FastHashMap<Wire, Vec<Gate>>, FastHashMap<Wire, bool>);
+
+fn parse_graph(input: &str) -> Result<Graph> {
+    let [wires, gates] = sections(input)?;
+    let input = wires
+        .lines()
         .map(|line| (Wire::from_str(&line[..3]), line.ends_with('1')))
         .collect();
 
     let mut adj = FastHashMap::<_, Vec<_>>::new();
-    for line in lines {
-        let (left, operation, right, _, output) =
-            line.split_ascii_whitespace().collect_tuple().unwrap();
+    for line in gates.lines() {
+        let (left, operation, right, _, output) = line
+            .split_ascii_whitespace()
+            .collect_tuple()
+            .with_context(|| format!("invalid gate: {line:?}"))?;
         let operation = match operation {
             "AND" => Operation::And,
             "OR" => Operation::Or,
             "XOR" => Operation::Xor,
-            _ => panic!("Unknown operation: {operation:?}"),
+            _ => bail!("Unknown operation: {operation:?}"),
         };
         let gate = Gate {
             operation,
@@ -169,7 +176,7 @@ fn parse_graph(input: &str) -> (FastHashMap<Wire, Vec<Gate>>, FastHashMap<Wire,
         adj.entry(gate.right).or_default().push(gate);
     }
 
-    (adj, input)
+    Ok((adj, input))
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -283,13 +290,13 @@ tnw OR pbm -> gnj";
 
     #[test]
     fn part1_small() {
-        let (adj, wire_vals) = parse_graph(SMALL_INPUT);
+        let (adj, wire_vals) = parse_graph(SMALL_INPUT).unwrap();
         assert_eq!(part1(&adj, wire_vals), 4);
     }
 
     #[test]
     fn part1_large() {
-        let (adj, wire_vals) = parse_graph(LARGE_INPUT);
+        let (adj, wire_vals) = parse_graph(LARGE_INPUT).unwrap();
         assert_eq!(part1(&adj, wire_vals), 2024);
     }
 }
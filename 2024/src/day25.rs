@@ -1,26 +1,23 @@
 use std::array;
 
-use itertools::Itertools;
+use anyhow::{Result, ensure};
 use register::register;
+use utils::input;
 
 #[register]
-fn run(input: &str) -> (usize, u8) {
+fn run(input: &str) -> Result<(usize, u8)> {
     let mut locks = [[[[[0; 6]; 6]; 6]; 6]; 6];
     let mut keys = Vec::new();
-    let line_groups = input
-        .lines()
-        .map(str::as_bytes)
-        .filter(|line| !line.is_empty())
-        .tuples();
-    for (l1, l2, l3, l4, l5, l6, _) in line_groups {
-        let [h1, h2, h3, h4, h5] = array::from_fn(|i| {
-            [l2, l3, l4, l5, l6]
-                .iter()
-                .filter(|line| line[i] == b'#')
-                .count()
-        });
+    for grid in input::grids(input, |c| c == b'#')? {
+        ensure!(
+            grid.width() == 5 && grid.height() == 7,
+            "expected 5x7 locks and keys"
+        );
+        // The full top or bottom row is not part of the height.
+        let [h1, h2, h3, h4, h5] =
+            array::from_fn(|x| grid.column(x).filter(|&&filled| filled).count() - 1);
 
-        if l1 == b"#####" {
+        if grid.row(0).iter().all(|&filled| filled) {
             locks[h1][h2][h3][h4][h5] += 1;
         } else {
             keys.push([h1, h2, h3, h4, h5]);
@@ -48,7 +45,7 @@ fn run(input: &str) -> (usize, u8) {
         .map(|&[h1, h2, h3, h4, h5]| locks[5 - h1][5 - h2][5 - h3][5 - h4][5 - h5])
         .sum();
 
-    (part1, 0)
+    Ok((part1, 0))
 }
 
 #[cfg(test)]
@@ -98,6 +95,6 @@ mod tests {
 
     #[test]
     fn part1() {
-        assert_eq!(run(INPUT).0, 3);
+        assert_eq!(run(INPUT).unwrap().0, 3);
     }
 }
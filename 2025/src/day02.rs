@@ -1,5 +1,7 @@
 use const_array_init::const_arr;
+use itertools::Itertools;
 use register::register;
+use utils::rng::Rng;
 
 const TEN_POW: [u64; 20] = const_arr!([u64; 20], |i| 10_u64.pow(i as u32));
 const MOEBIUS: [i64; 20] = [
@@ -123,6 +125,20 @@ fn is_silly2_string(x: u64) -> bool {
         })
 }
 
+#[register(generator)]
+fn random_ranges(seed: u64, size: usize) -> String {
+    let mut rng = Rng::new(seed);
+    let num_ranges = 1 + rng.index(size);
+    (0..num_ranges)
+        .map(|_| {
+            let digits = 1 + rng.index(size.min(12));
+            let low = rng.range(TEN_POW[digits - 1]..TEN_POW[digits]);
+            let high = low + rng.range(0..10 * size as u64);
+            format!("{low}-{high}")
+        })
+        .join(",")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1,17 +1,22 @@
 use std::collections::VecDeque;
 
-use itertools::iproduct;
+use anyhow::Result;
+use itertools::{Itertools, iproduct};
 use ndarray::{Array2, s};
 use register::register;
-use utils::grid;
+use utils::{
+    grid::{Grid, GridView},
+    rng::Rng,
+};
 
 #[register]
-fn run(input: &str) -> (usize, usize) {
-    let grid = grid::from_lines(input);
-    let mut num_neighbors = Array2::from_elem(grid.dim(), 255_u8);
+fn run(input: &str) -> Result<(usize, usize)> {
+    let grid = GridView::from_lines(input)?;
+    let mut num_neighbors = Grid::new(grid.height(), grid.width(), 255_u8);
     for (pos, &cell) in grid.indexed_iter() {
         if cell == b'@' {
-            num_neighbors[pos] = neighbors(pos, grid.dim())
+            num_neighbors[pos] = grid
+                .neighbors8(pos)
                 .filter(|&pos2| grid[pos2] == b'@')
                 .count() as u8;
         }
@@ -27,7 +32,7 @@ fn run(input: &str) -> (usize, usize) {
     let mut part2 = 0;
     while let Some(pos) = ready.pop_front() {
         part2 += 1;
-        for pos2 in neighbors(pos, grid.dim()) {
+        for pos2 in grid.neighbors8(pos) {
             num_neighbors[pos2] -= 1;
             if num_neighbors[pos2] == 3 {
                 ready.push_back(pos2);
@@ -35,7 +40,7 @@ fn run(input: &str) -> (usize, usize) {
         }
     }
 
-    (part1, part2)
+    Ok((part1, part2))
 }
 
 #[register]
@@ -91,15 +96,19 @@ fn slow(input: &str) -> (usize, usize) {
     (part1, part2)
 }
 
-fn neighbors(
-    (y, x): (usize, usize),
-    (height, width): (usize, usize),
-) -> impl Iterator<Item = (usize, usize)> {
-    let min_y = y.saturating_sub(1);
-    let max_y = (y + 2).min(height);
-    let min_x = x.saturating_sub(1);
-    let max_x = (x + 2).min(width);
-    iproduct!(min_y..max_y, min_x..max_x).filter(move |&pos| pos != (y, x))
+#[register(generator)]
+fn random_grid(seed: u64, size: usize) -> String {
+    let mut rng = Rng::new(seed);
+    let height = 1 + rng.index(size);
+    let width = 1 + rng.index(size);
+    let density = if rng.chance(0.5) { 0.7 } else { 0.4 };
+    (0..height)
+        .map(|_| {
+            (0..width)
+                .map(|_| if rng.chance(density) { '@' } else { '.' })
+                .collect::<String>()
+        })
+        .join("\n")
 }
 
 #[cfg(test)]
@@ -120,6 +129,6 @@ mod tests {
 
     #[test]
     fn test() {
-        assert_eq!(run(INPUT), (13, 43));
+        assert_eq!(run(INPUT).unwrap(), (13, 43));
     }
 }
@@ -1,28 +1,26 @@
+use std::ops::RangeInclusive;
+
 use anyhow::Result;
 use itertools::Itertools;
 use register::register;
+use utils::{
+    input::{Input, sections},
+    rng::Rng,
+};
 
 #[register]
 fn run(input: &str) -> Result<(usize, u64)> {
-    let mut events: Vec<_> = input
-        .lines()
-        .filter(|l| !l.is_empty())
-        .map(|l| -> Result<_> {
-            if let Some((start, end)) = l.split_once('-') {
-                let start: u64 = start.parse()?;
-                let end: u64 = end.parse()?;
-                Ok([
-                    Some((start, EventType::StartRange)),
-                    Some((end + 1, EventType::EndRange)),
-                ])
-            } else {
-                let num: u64 = l.parse()?;
-                Ok([Some((num, EventType::Query)), None])
-            }
+    let [ranges, ids] = sections(input)?;
+    let mut events: Vec<_> = ranges
+        .ranges::<u64>()
+        .flat_map(|range| {
+            [
+                (*range.start(), EventType::StartRange),
+                (range.end() + 1, EventType::EndRange),
+            ]
         })
-        .flatten_ok()
-        .flatten_ok()
-        .try_collect()?;
+        .chain(ids.unsigned_integers().map(|id| (id, EventType::Query)))
+        .collect();
     events.sort_unstable();
 
     let mut part1 = 0;
@@ -60,6 +58,54 @@ enum EventType {
     Query,
 }
 
+/// Merges overlapping ranges first and then looks up every id with a
+/// binary search.
+#[register]
+fn merge_ranges(input: &str) -> Result<(usize, u64)> {
+    let [ranges, ids] = sections(input)?;
+    let mut ranges: Vec<_> = ranges
+        .ranges::<u64>()
+        .map(RangeInclusive::into_inner)
+        .collect();
+    ranges.sort_unstable();
+
+    let mut merged: Vec<(u64, u64)> = Vec::new();
+    for (start, end) in ranges {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 + 1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+
+    let part1 = ids
+        .unsigned_integers::<u64>()
+        .filter(|&id| {
+            let i = merged.partition_point(|&(start, _)| start <= id);
+            i > 0 && id <= merged[i - 1].1
+        })
+        .count();
+    let part2 = merged.iter().map(|&(start, end)| end - start + 1).sum();
+
+    Ok((part1, part2))
+}
+
+#[register(generator)]
+fn random_ranges(seed: u64, size: usize) -> String {
+    let mut rng = Rng::new(seed);
+    let max = 10 * size as u64;
+    let ranges = (0..=rng.index(size))
+        .map(|_| {
+            let start = rng.range(0..max);
+            let end = start + rng.range(0..max / 2);
+            format!("{start}-{end}")
+        })
+        .join("\n");
+    let ids = (0..=rng.index(size))
+        .map(|_| rng.range(0..2 * max).to_string())
+        .join("\n");
+    format!("{ranges}\n\n{ids}")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -80,5 +126,6 @@ mod tests {
     #[test]
     fn test() {
         assert_eq!(run(INPUT).unwrap(), (3, 14));
+        assert_eq!(merge_ranges(INPUT).unwrap(), (3, 14));
     }
 }
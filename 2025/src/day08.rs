@@ -4,7 +4,7 @@ use anyhow::Result;
 use itertools::Itertools;
 use rayon::slice::ParallelSliceMut;
 use register::register;
-use utils::input::Input;
+use utils::{graph::DisjointSetUnion, input::Input, threads};
 
 #[register]
 fn run(input: &str) -> Result<(usize, u64)> {
@@ -43,8 +43,8 @@ fn jarnik_prim_multi_threaded(input: &str) -> Result<(usize, u64)> {
     let (points, part1_edge_count) = parse(input)?;
     let n = points.len();
 
-    let num_cores = num_cpus::get();
-    assert!(num_cores >= 2);
+    // One thread is reserved for part 2
+    let num_cores = threads::count().max(2);
 
     thread::scope(|s| {
         let part2_handle = s.spawn(|| jarnik_prim_impl(&points));
@@ -155,44 +155,6 @@ fn parse(input: &str) -> Result<(Vec<[u32; 3]>, usize)> {
     Ok((points, part1_edge_count))
 }
 
-struct DisjointSetUnion(Vec<isize>);
-
-impl DisjointSetUnion {
-    fn new(size: usize) -> Self {
-        DisjointSetUnion(vec![-1; size])
-    }
-
-    fn find(&mut self, i: usize) -> usize {
-        if let Ok(parent) = usize::try_from(self.0[i]) {
-            let root = self.find(parent);
-            self.0[i] = root as isize;
-            root
-        } else {
-            i
-        }
-    }
-
-    fn root_sizes(&self) -> impl Iterator<Item = usize> {
-        self.0.iter().filter(|&&x| x < 0).map(|&x| (-x) as usize)
-    }
-
-    fn join(&mut self, i: usize, j: usize) -> bool {
-        let i = self.find(i);
-        let j = self.find(j);
-        if i == j {
-            return false;
-        }
-        let (large, small) = if self.0[i] < self.0[j] {
-            (i, j)
-        } else {
-            (j, i)
-        };
-        self.0[large] += self.0[small];
-        self.0[small] = large as isize;
-        true
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -234,7 +196,7 @@ mod tests {
 
         for (name, solve) in impls {
             let result = solve(INPUT).unwrap();
-            assert_eq!(result, (40, 25_272), "Failed for {name}",);
+            assert_eq!(result, (40, 25_272), "Failed for {name}");
         }
     }
 }
@@ -1,23 +1,19 @@
 mod bench;
+mod fuzz;
 mod run;
 mod setup;
 
-use std::{fs, str::FromStr, time::SystemTime};
+use std::{str::FromStr, time::Duration};
 
-use anyhow::{Context, Error, Result, bail};
+use anyhow::{Context, Error, Result, bail, ensure};
 use clap::Parser;
 use indicatif::ProgressBar;
-use jiff::{
-    Zoned,
-    tz::{Offset, TimeZone},
-};
+use jiff::SignedDuration;
 use mitsein::{iter1::IteratorExt, vec1::Vec1};
+use once_cell::sync::OnceCell;
+use runner::{Day, InputSource, PuzzleId, PuzzleSolutions, Solutions, Year, inputs::AocInputs};
 
-use crate::{
-    Day, PuzzleId, Year,
-    solutions::{PuzzleSolutions, Solutions},
-    style::progress_style,
-};
+use crate::style::progress_style;
 
 #[derive(Parser)]
 enum Args {
@@ -27,6 +23,9 @@ enum Args {
     /// Benchmark one or multiple puzzle solutions.
     Bench(bench::Args),
 
+    /// Cross-check alternative solutions on randomly generated inputs.
+    Fuzz(fuzz::Args),
+
     /// Setup a new, empty puzzle solution.
     Setup(setup::Args),
 }
@@ -36,6 +35,7 @@ pub(crate) fn run() -> Result<()> {
     match opts {
         Args::Run(args) => run::run(&args),
         Args::Bench(args) => bench::run(&args),
+        Args::Fuzz(args) => fuzz::run(&args),
         Args::Setup(args) => setup::run(&args),
     }
 }
@@ -68,7 +68,7 @@ impl MultiPuzzleArgs {
         let (maybe_year, maybe_day) = if let Some(selector) = self.puzzles {
             match selector {
                 MultiPuzzleSelector::DayInMostRecentYear(day) => {
-                    let year = most_recent_puzzle()?.year;
+                    let year = PuzzleId::most_recent()?.year;
                     (Some(year), Some(day))
                 }
                 MultiPuzzleSelector::Year(year) => (Some(year), None),
@@ -77,25 +77,22 @@ impl MultiPuzzleArgs {
         } else if self.all {
             (None, None)
         } else if self.most_recent {
-            let id = most_recent_puzzle()?;
+            let id = PuzzleId::most_recent()?;
             (Some(id.year), Some(id.day))
         } else {
-            return most_recently_edited_puzzle().map(Vec1::from_one);
+            return Solutions::get()?.most_recently_edited().map(Vec1::from_one);
         };
 
-        let mut selected: Vec1<_> = Solutions::get()?
-            .by_id
-            .iter()
-            .map(|(id, solutions)| (*id, solutions))
+        Solutions::get()?
+            .sorted()
+            .into_iter()
             .filter(|(id, _)| {
                 maybe_year.is_none_or(|year| id.year == year)
                     && maybe_day.is_none_or(|day| id.day == day)
             })
             .try_collect1()
             .ok()
-            .context("no puzzles found for selection")?;
-        selected.sort_unstable_by_key(|(id, _)| *id);
-        Ok(selected)
+            .context("no puzzles found for selection")
     }
 }
 
@@ -126,51 +123,15 @@ impl FromStr for MultiPuzzleSelector {
     }
 }
 
-fn most_recent_puzzle() -> Result<PuzzleId> {
-    let est = TimeZone::fixed(Offset::constant(-5));
-    let date_est = Zoned::now().with_time_zone(est).date();
-    let year = u16::try_from(date_est.year()).context("current year is negative!?")?;
-    let (year, day) = if date_est.month() == 12 {
-        #[expect(
-            clippy::cast_sign_loss,
-            reason = "day() is guaranteed to be in [1, 31]"
-        )]
-        let day = date_est.day() as u8;
-        let day = Day::try_new(day.min(25)).expect("check should ensure validity");
-        (year, day)
-    } else {
-        (year - 1, Day::TWENTY_FIVE)
-    };
-
-    let year = Year::try_new(year).context("before the start of AoC")?;
-    Ok(PuzzleId { year, day })
-}
-
-fn most_recently_edited_puzzle() -> Result<(PuzzleId, &'static PuzzleSolutions)> {
-    let solutions = Solutions::get()?;
-    let (_, id) = solutions
-        .by_file
-        .iter()
-        .map(|(&file, &id)| {
-            let modified_time = file_modified_time(file)
-                .with_context(|| format!("failed to determine modification time of {file:?}"))?;
-            anyhow::Ok((modified_time, id))
-        })
-        .reduce(|sol1, sol2| {
-            let (t1, id1) = sol1?;
-            let (t2, id2) = sol2?;
-            if t1 > t2 {
-                Ok((t1, id1))
-            } else {
-                Ok((t2, id2))
-            }
-        })
-        .context("no puzzles found")??;
-    Ok((id, &solutions.by_id[&id]))
+fn get_input(puzzle_id: PuzzleId) -> Result<String> {
+    static INPUTS: OnceCell<AocInputs> = OnceCell::new();
+    INPUTS.get_or_try_init(AocInputs::new)?.get(puzzle_id)
 }
 
-fn file_modified_time(path: &str) -> Result<SystemTime> {
-    Ok(fs::metadata(path)?.modified()?)
+fn parse_duration(s: &str) -> Result<Duration> {
+    let duration: SignedDuration = s.parse()?;
+    ensure!(duration.is_positive(), "must be positive");
+    Ok(duration.unsigned_abs())
 }
 
 fn init_progress_bar(puzzles: &[(PuzzleId, &PuzzleSolutions)], alts: bool) -> ProgressBar {
@@ -1,30 +1,38 @@
 use std::{
     cmp::Reverse,
+    fmt::Write as _,
+    iter,
+    num::NonZeroUsize,
     time::{Duration, Instant},
 };
 
 use anyhow::{Result, ensure};
 use indicatif::ProgressIterator;
 use itertools::Itertools;
-use jiff::SignedDuration;
-use mitsein::vec1::Vec1;
-use register::SolutionFunction;
+use runner::{
+    PuzzleId, PuzzleSolutions, SolutionFunction, bench,
+    counters::{self, CounterValues, Counters},
+    memory::{self, MemoryUsage},
+};
+use serde_json::json;
+use utils::threads;
 
 use crate::{
-    PuzzleId,
-    commands::{MultiPuzzleArgs, init_progress_bar},
-    inputs,
-    solutions::PuzzleSolutions,
-    style::{AOC_STAR, DIM, HIGHLIGHT, print_runtime_bar},
+    commands::{MultiPuzzleArgs, get_input, init_progress_bar, parse_duration},
+    style::{AOC_STAR, DIM, HIGHLIGHT, INCORRECT, print_runtime_bar},
 };
 
 #[derive(clap::Args)]
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "independent command line flags"
+)]
 pub(crate) struct Args {
     #[clap(flatten)]
     puzzles: MultiPuzzleArgs,
 
     /// Minimum time to run each benchmark for.
-    #[clap(short, long, default_value = "1s", value_parser = parse_bench_time)]
+    #[clap(short, long, default_value = "1s", value_parser = parse_duration)]
     time: Duration,
 
     /// Render bars to show the relative runtimes of each solution.
@@ -34,32 +42,101 @@ pub(crate) struct Args {
     /// Benchmark alternative solutions
     #[clap(long, conflicts_with = "bar")]
     alts: bool,
+
+    /// Also report peak heap usage, total allocated bytes and allocation
+    /// count.
+    #[clap(long, conflicts_with = "bar")]
+    memory: bool,
+
+    /// Also report hardware performance counters (Linux only).
+    #[clap(long, conflicts_with = "bar")]
+    counters: bool,
+
+    /// Number of threads for multithreaded solutions (all logical CPUs by
+    /// default).
+    ///
+    /// Multiple comma-separated counts run a scaling sweep reporting the
+    /// speedup relative to the first count.
+    #[clap(long, value_delimiter = ',')]
+    threads: Vec<NonZeroUsize>,
+
+    /// Output format of the results.
+    #[clap(long, value_enum, default_value_t = Format::Text, conflicts_with = "bar")]
+    format: Format,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum Format {
+    /// Human-readable text.
+    Text,
+    /// One JSON object per line and solution.
+    Json,
 }
 
 pub(crate) fn run(args: &Args) -> Result<()> {
     let puzzles = args.puzzles.evaluate()?;
+    match *args.threads.as_slice() {
+        [] => run_single(&puzzles, args),
+        [count] => threads::with_count(count.get(), || run_single(&puzzles, args)),
+        ref counts => {
+            ensure!(!args.bar, "--bar does not support multiple thread counts");
+            run_sweep(&puzzles, counts, args)
+        }
+    }
+}
+
+fn run_single(puzzles: &[(PuzzleId, &PuzzleSolutions)], args: &Args) -> Result<()> {
     if args.bar {
-        run_bars(&puzzles, args.time)
+        run_bars(puzzles, args.time)
     } else {
-        run_normal(&puzzles, args)
+        run_normal(puzzles, args)
     }
 }
 
+fn open_counters(args: &Args) -> Option<Counters> {
+    if !args.counters {
+        return None;
+    }
+
+    Counters::open()
+        .inspect_err(|err| {
+            let msg = format!("Hardware counters unavailable ({err:#}), reporting wall time only");
+            eprintln!("{}", INCORRECT.apply_to(msg));
+        })
+        .ok()
+}
+
 fn run_normal(puzzles: &[(PuzzleId, &PuzzleSolutions)], args: &Args) -> Result<()> {
+    // Opened before any solution runs so that all threads inherit them
+    let counters = open_counters(args);
     let progress_bar = init_progress_bar(puzzles, args.alts);
     for &(puzzle_id, solutions) in puzzles {
-        let main_time = benchmark(puzzle_id, solutions.main, args.time)?;
-        let message = format!(
-            "{AOC_STAR} {} {main_time:>8.2?}",
-            HIGHLIGHT.apply_to(puzzle_id),
-        );
+        let main = measure(puzzle_id, solutions.main, args, counters.as_ref())?;
         progress_bar.inc(1);
-        progress_bar.println(message);
+        if args.format == Format::Json {
+            progress_bar.suspend(|| println!("{}", main.to_json(puzzle_id, "main")));
+        } else {
+            let message = format!(
+                "{AOC_STAR} {} {:>8.2?}{}",
+                HIGHLIGHT.apply_to(puzzle_id),
+                main.time,
+                main.details(),
+            );
+            progress_bar.println(message);
+        }
 
         if args.alts {
-            for (alt_name, alt_solution) in &solutions.alts {
-                let alt_time = benchmark(puzzle_id, *alt_solution, args.time)?;
-                let factor = alt_time.as_secs_f64() / main_time.as_secs_f64();
+            for alt in &solutions.alts {
+                let alt_measurement = measure(puzzle_id, alt.func, args, counters.as_ref())?;
+                progress_bar.inc(1);
+                if args.format == Format::Json {
+                    let line = alt_measurement.to_json(puzzle_id, &alt.name);
+                    progress_bar.suspend(|| println!("{line}"));
+                    continue;
+                }
+
+                let alt_time = alt_measurement.time;
+                let factor = alt_time.as_secs_f64() / main.time.as_secs_f64();
                 let digits = if factor >= 100.0 {
                     0
                 } else if factor >= 10.0 {
@@ -67,9 +144,70 @@ fn run_normal(puzzles: &[(PuzzleId, &PuzzleSolutions)], args: &Args) -> Result<(
                 } else {
                     2
                 };
-                let paren_info = format!("({alt_name}, {factor:.digits$}x slower)");
-                let message = format!("{alt_time:>18.2?} {}", DIM.apply_to(paren_info));
+                let paren_info = format!("({}, {factor:.digits$}x slower)", alt.name);
+                let message = format!(
+                    "{alt_time:>18.2?} {}{}",
+                    DIM.apply_to(paren_info),
+                    alt_measurement.details(),
+                );
+                progress_bar.println(message);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn run_sweep(
+    puzzles: &[(PuzzleId, &PuzzleSolutions)],
+    thread_counts: &[NonZeroUsize],
+    args: &Args,
+) -> Result<()> {
+    let counters = open_counters(args);
+    let progress_bar = init_progress_bar(puzzles, args.alts);
+    progress_bar.set_length(progress_bar.length().unwrap_or(0) * thread_counts.len() as u64);
+    for &(puzzle_id, solutions) in puzzles {
+        let main = ("main", solutions.main);
+        let alts = solutions
+            .alts
+            .iter()
+            .map(|alt| (alt.name.as_str(), alt.func));
+        let alts = alts.take(if args.alts { usize::MAX } else { 0 });
+        for (index, (name, solution)) in iter::once(main).chain(alts).enumerate() {
+            if args.format == Format::Text {
+                let header = if index == 0 {
+                    format!("{AOC_STAR} {}", HIGHLIGHT.apply_to(puzzle_id))
+                } else {
+                    format!("    {}", DIM.apply_to(name))
+                };
+                progress_bar.println(header);
+            }
+
+            let mut baseline = None;
+            for &count in thread_counts {
+                let measurement = threads::with_count(count.get(), || {
+                    measure(puzzle_id, solution, args, counters.as_ref())
+                })?;
                 progress_bar.inc(1);
+                if args.format == Format::Json {
+                    let line = measurement.to_json(puzzle_id, name);
+                    progress_bar.suspend(|| println!("{line}"));
+                    continue;
+                }
+
+                let baseline = *baseline.get_or_insert(measurement.time);
+                let speedup = baseline.as_secs_f64() / measurement.time.as_secs_f64();
+                let unit = if count.get() == 1 {
+                    "thread"
+                } else {
+                    "threads"
+                };
+                let message = format!(
+                    "{count:>8} {unit:<7} {:>8.2?} {}{}",
+                    measurement.time,
+                    DIM.apply_to(format!("({speedup:.2}x speedup)")),
+                    measurement.details(),
+                );
                 progress_bar.println(message);
             }
         }
@@ -78,6 +216,84 @@ fn run_normal(puzzles: &[(PuzzleId, &PuzzleSolutions)], args: &Args) -> Result<(
     Ok(())
 }
 
+struct Measurement {
+    threads: usize,
+    time: Duration,
+    memory: Option<MemoryUsage>,
+    counters: Option<CounterValues>,
+}
+
+impl Measurement {
+    fn details(&self) -> String {
+        let memory = self.memory.map(|memory| memory.to_string());
+        let counters = self.counters.map(|counters| counters.to_string());
+        let mut details = String::new();
+        for part in memory.into_iter().chain(counters) {
+            let _ = write!(details, " {}", DIM.apply_to(format!("[{part}]")));
+        }
+        details
+    }
+
+    fn to_json(&self, puzzle_id: PuzzleId, solution: &str) -> String {
+        let mut value = json!({
+            "puzzle": puzzle_id.to_string(),
+            "solution": solution,
+            "threads": self.threads,
+            "time_ns": self.time.as_nanos(),
+        });
+        if let Some(memory) = self.memory {
+            value["memory"] = json!({
+                "peak_bytes": memory.peak,
+                "total_bytes": memory.total,
+                "allocations": memory.count,
+            });
+        }
+        if let Some(counters) = self.counters {
+            value["counters"] = json!({
+                "cycles": counters.cycles,
+                "instructions": counters.instructions,
+                "branch_misses": counters.branch_misses,
+                "cache_misses": counters.cache_misses,
+            });
+        }
+        value.to_string()
+    }
+}
+
+fn measure(
+    puzzle_id: PuzzleId,
+    solution: SolutionFunction,
+    args: &Args,
+    counters: Option<&Counters>,
+) -> Result<Measurement> {
+    let input = get_input(puzzle_id)?;
+    let time = bench::benchmark(solution, &input, args.time)?.median();
+    let memory = if args.memory {
+        // Measured in a separate run to keep the counting out of the timings
+        let (result, usage) = memory::measure(|| solution(&input));
+        result?;
+        Some(usage)
+    } else {
+        None
+    };
+    let counters = counters
+        .map(|counters| {
+            let start = Instant::now();
+            counters::measure_per_run(counters, || {
+                solution(&input)?;
+                Ok(start.elapsed() < args.time)
+            })
+        })
+        .transpose()?;
+
+    Ok(Measurement {
+        threads: threads::count(),
+        time,
+        memory,
+        counters,
+    })
+}
+
 fn run_bars(puzzles: &[(PuzzleId, &PuzzleSolutions)], min_time: Duration) -> Result<()> {
     let progress_bar = init_progress_bar(puzzles, false);
     let mut benchmarked: Vec<_> = puzzles
@@ -107,36 +323,6 @@ fn benchmark(
     solution: SolutionFunction,
     min_time: Duration,
 ) -> Result<Duration> {
-    let input = inputs::get(puzzle_id)?;
-    let run = || {
-        let start_run = Instant::now();
-        solution(&input)?;
-        anyhow::Ok(start_run.elapsed())
-    };
-
-    let start = Instant::now();
-    let mut runs = Vec1::from_one(run()?);
-    while start.elapsed() < min_time {
-        runs.push(run()?);
-    }
-
-    let count = runs.len().get();
-    let (small, &mut upper_median, _) = runs.select_nth_unstable(count / 2);
-    let median = if count.is_multiple_of(2) {
-        let lower_median = *small
-            .iter()
-            .min()
-            .expect("we are guaranteed to have at least two elements");
-        (lower_median + upper_median) / 2
-    } else {
-        upper_median
-    };
-
-    Ok(median)
-}
-
-fn parse_bench_time(s: &str) -> Result<Duration> {
-    let duration: SignedDuration = s.parse()?;
-    ensure!(duration.is_positive(), "must be positive");
-    Ok(duration.unsigned_abs())
+    let input = get_input(puzzle_id)?;
+    Ok(bench::benchmark(solution, &input, min_time)?.median())
 }
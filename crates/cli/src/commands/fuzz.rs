@@ -0,0 +1,220 @@
+use std::{
+    env,
+    fmt::Write as _,
+    panic,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Result, ensure};
+use indicatif::ProgressBar;
+use runner::{
+    PuzzleId, PuzzleSolutions,
+    fuzz::{self, Check, Disagreement},
+};
+
+use crate::{
+    commands::{MultiPuzzleArgs, parse_duration},
+    style::{AOC_STAR, CHECKMARK, CORRECT, CROSSMARK, DIM, HIGHLIGHT, INCORRECT, progress_style},
+};
+
+#[derive(clap::Args)]
+pub(crate) struct Args {
+    #[clap(flatten)]
+    puzzles: MultiPuzzleArgs,
+
+    /// Number of inputs to generate per puzzle and generator.
+    #[clap(short = 'n', long, default_value_t = 1000)]
+    iterations: u64,
+
+    /// Seed of the first generated input (random by default).
+    #[clap(long)]
+    seed: Option<u64>,
+
+    /// Size hint passed to generators for the last iteration.
+    #[clap(long, default_value_t = 20)]
+    max_size: usize,
+
+    /// Give up on solutions running longer than this, counting them as
+    /// failed.
+    #[clap(long, default_value = "10s", value_parser = parse_duration)]
+    timeout: Duration,
+
+    /// Only report counterexamples instead of adding them as tests.
+    #[clap(long)]
+    no_save: bool,
+
+    /// Workspace root to add tests to (by default the current directory or
+    /// the closest ancestor containing the solution file).
+    #[clap(long)]
+    workspace: Option<PathBuf>,
+}
+
+pub(crate) fn run(args: &Args) -> Result<()> {
+    let puzzles: Vec<_> = args
+        .puzzles
+        .evaluate()?
+        .into_iter()
+        .filter(|(_, solutions)| !solutions.generators.is_empty())
+        .collect();
+    ensure!(
+        !puzzles.is_empty(),
+        "no input generators for selected puzzles"
+    );
+
+    let seed = args.seed.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_secs())
+    });
+    println!("{} {seed}", HIGHLIGHT.apply_to("Seed:"));
+
+    let total = puzzles
+        .iter()
+        .map(|(_, solutions)| solutions.generators.len() as u64 * args.iterations)
+        .sum();
+    let progress_bar = ProgressBar::new(total).with_style(progress_style());
+    progress_bar.tick();
+
+    // Solutions are expected to panic on some of the inputs while shrinking,
+    // so we silence the default hook that would print each of them.
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(|_| {}));
+    let result = puzzles.iter().try_for_each(|&(puzzle_id, solutions)| {
+        fuzz_puzzle(puzzle_id, solutions, seed, args, &progress_bar)
+    });
+    panic::set_hook(default_hook);
+    result
+}
+
+fn fuzz_puzzle(
+    puzzle_id: PuzzleId,
+    solutions: &PuzzleSolutions,
+    seed: u64,
+    args: &Args,
+    progress_bar: &ProgressBar,
+) -> Result<()> {
+    let header = HIGHLIGHT.apply_to(format!("{puzzle_id}:"));
+    if solutions.alts.is_empty() {
+        progress_bar.inc(solutions.generators.len() as u64 * args.iterations);
+        let msg = DIM.apply_to("no alternative solutions to compare");
+        progress_bar.println(format!("{AOC_STAR} {header} {msg}"));
+        return Ok(());
+    }
+
+    let mut invalid = 0;
+    let mut generator_panics = 0;
+    for (gen_name, generator) in &solutions.generators {
+        for i in 0..args.iterations {
+            let input_seed = seed.wrapping_add(i);
+            let size = 1 + (i as usize * args.max_size / args.iterations as usize);
+            progress_bar.inc(1);
+            let Ok(input) = panic::catch_unwind(|| generator(input_seed, size)) else {
+                generator_panics += 1;
+                continue;
+            };
+
+            match fuzz::check(solutions, &input, args.timeout) {
+                Check::Agree => {}
+                Check::Invalid => invalid += 1,
+                Check::Disagree(disagreement) => {
+                    let (input, disagreement) = fuzz::shrink(
+                        solutions,
+                        *generator,
+                        (input_seed, size),
+                        input,
+                        disagreement,
+                        args.timeout,
+                    );
+                    let msg = format!(
+                        "{CROSSMARK} {}",
+                        DIM.apply_to(format!("generator {gen_name:?}, seed {input_seed}"))
+                    );
+                    progress_bar.println(format!("{AOC_STAR} {header} {msg}"));
+                    report(
+                        solutions,
+                        disagreement,
+                        input_seed,
+                        &input,
+                        args,
+                        progress_bar,
+                    )?;
+                    return Ok(());
+                }
+            }
+        }
+    }
+
+    let checked = solutions.generators.len() as u64 * args.iterations - invalid - generator_panics;
+    let mut msg = format!("{CHECKMARK} {checked} inputs");
+    let mut skipped = Vec::new();
+    if invalid > 0 {
+        skipped.push(format!("{invalid} rejected by main"));
+    }
+    if generator_panics > 0 {
+        skipped.push(format!("{generator_panics} generator panics"));
+    }
+    if !skipped.is_empty() {
+        let _ = write!(
+            msg,
+            " {}",
+            DIM.apply_to(format!("({})", skipped.join(", ")))
+        );
+    }
+    progress_bar.println(format!("{AOC_STAR} {header} {msg}"));
+    Ok(())
+}
+
+fn report(
+    solutions: &PuzzleSolutions,
+    Disagreement { main, alts }: Disagreement<'_>,
+    seed: u64,
+    input: &str,
+    args: &Args,
+    progress_bar: &ProgressBar,
+) -> Result<()> {
+    progress_bar.println(format!("    {}", HIGHLIGHT.apply_to("Input:")));
+    for line in input.lines() {
+        progress_bar.println(format!("      {}", DIM.apply_to(line)));
+    }
+
+    let main = CORRECT.apply_to(format!("{} {}", main.0, main.1));
+    for (alt, outcome) in &alts {
+        let alt_output = match outcome {
+            Ok((part1, part2)) => format!("{part1} {part2}"),
+            Err(err) => err.clone(),
+        };
+        let alt_output = INCORRECT.apply_to(alt_output);
+        progress_bar.println(format!("    {CROSSMARK} {}", DIM.apply_to(&alt.name)));
+        progress_bar.println(format!("        {main} vs. {alt_output}"));
+    }
+
+    if !args.no_save {
+        let alts: Vec<_> = alts.into_iter().map(|(alt, _)| alt).collect();
+        let path = source_path(solutions.file, args.workspace.as_deref())?;
+        let name = fuzz::add_regression_test(&path, seed, input, &alts)?;
+        let msg = format!("Added test `{name}` to {}", solutions.file);
+        progress_bar.println(format!("    {}", HIGHLIGHT.apply_to(msg)));
+    }
+
+    Ok(())
+}
+
+/// Resolves a path from `file!()`, which is relative to the workspace root.
+fn source_path(file: &str, workspace: Option<&Path>) -> Result<PathBuf> {
+    if let Some(workspace) = workspace {
+        return Ok(workspace.join(file));
+    }
+
+    let current_dir = env::current_dir()?;
+    current_dir
+        .ancestors()
+        .map(|dir| dir.join(file))
+        .find(|path| path.is_file())
+        .with_context(|| {
+            format!(
+                "{file} not found in {} or its ancestors, use --workspace",
+                current_dir.display()
+            )
+        })
+}
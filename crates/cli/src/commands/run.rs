@@ -1,9 +1,11 @@
-use anyhow::{Context, Result};
+use std::{num::NonZeroUsize, time::Duration};
+
+use anyhow::{Context, Error, Result, anyhow};
+use runner::{Day, SolutionFunction};
+use utils::{input::ParseError, threads};
 
 use crate::{
-    Day,
-    commands::{MultiPuzzleArgs, init_progress_bar},
-    inputs,
+    commands::{MultiPuzzleArgs, get_input, init_progress_bar, parse_duration},
     style::{AOC_STAR, CHECKMARK, CORRECT, CROSSMARK, DIM, HIGHLIGHT, INCORRECT, StyledStaticStr},
 };
 
@@ -15,15 +17,31 @@ pub(crate) struct Args {
     /// Run alternative solutions
     #[clap(long)]
     alts: bool,
+
+    /// Number of threads for multithreaded solutions (all logical CPUs by
+    /// default).
+    #[clap(long)]
+    threads: Option<NonZeroUsize>,
+
+    /// Give up on solutions running longer than this.
+    #[clap(long, value_parser = parse_duration)]
+    timeout: Option<Duration>,
 }
 
 pub(crate) fn run(args: &Args) -> Result<()> {
+    match args.threads {
+        Some(count) => threads::with_count(count.get(), || run_puzzles(args)),
+        None => run_puzzles(args),
+    }
+}
+
+fn run_puzzles(args: &Args) -> Result<()> {
     let puzzles = args.puzzles.evaluate()?;
     let progress_bar = init_progress_bar(&puzzles, args.alts);
     for (puzzle_id, solution) in puzzles {
-        let input = inputs::get(puzzle_id)?;
-        let (part1, part2) =
-            (solution.main)(&input).with_context(|| format!("solution for {puzzle_id} failed"))?;
+        let input = get_input(puzzle_id)?;
+        let (part1, part2) = run_solution(solution.main, &input, args)
+            .with_context(|| format!("solution for {puzzle_id} failed"))?;
 
         let results = if puzzle_id.day == Day::TWENTY_FIVE {
             part1.clone()
@@ -36,14 +54,15 @@ pub(crate) fn run(args: &Args) -> Result<()> {
         progress_bar.println(msg);
 
         if args.alts {
-            for (alt_name, alt_solution) in &solution.alts {
-                let (alt_part1, alt_part2) = (*alt_solution)(&input).with_context(|| {
-                    format!("alternative solution {alt_name:?} for {puzzle_id} failed")
-                })?;
+            for alt in &solution.alts {
+                let (alt_part1, alt_part2) =
+                    run_solution(alt.func, &input, args).with_context(|| {
+                        format!("alternative solution {:?} for {puzzle_id} failed", alt.name)
+                    })?;
 
                 let mark1 = check_or_cross(alt_part1 == part1);
                 let mark2 = check_or_cross(alt_part2 == part2);
-                let msg = format!("    {mark1} {mark2} {}", DIM.apply_to(alt_name));
+                let msg = format!("    {mark1} {mark2} {}", DIM.apply_to(&alt.name));
                 progress_bar.inc(1);
                 progress_bar.println(msg);
 
@@ -51,7 +70,7 @@ pub(crate) fn run(args: &Args) -> Result<()> {
                     if main != alt {
                         let main = CORRECT.apply_to(main);
                         let alt = INCORRECT.apply_to(alt);
-                        progress_bar.println(format!("        {main} vs. {alt}",));
+                        progress_bar.println(format!("        {main} vs. {alt}"));
                     }
                 }
             }
@@ -61,6 +80,22 @@ pub(crate) fn run(args: &Args) -> Result<()> {
     Ok(())
 }
 
+fn run_solution(solution: SolutionFunction, input: &str, args: &Args) -> Result<(String, String)> {
+    let result = match args.timeout {
+        Some(timeout) => runner::run_with_timeout(solution, input, timeout),
+        None => solution(input),
+    };
+    result.map_err(|err| with_input_excerpt(err, input))
+}
+
+/// Shows the offending line of the input below parse errors.
+fn with_input_excerpt(err: Error, input: &str) -> Error {
+    match err.downcast_ref::<ParseError>() {
+        Some(parse_error) => anyhow!("{err:#}\n{}", parse_error.excerpt(input)),
+        None => err,
+    }
+}
+
 fn check_or_cross(correct: bool) -> &'static StyledStaticStr {
     if correct { &CHECKMARK } else { &CROSSMARK }
 }
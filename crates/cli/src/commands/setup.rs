@@ -2,10 +2,7 @@ use std::{fs, path::PathBuf};
 
 use anyhow::{Context, Result, ensure};
 
-use crate::{
-    Day, PuzzleId,
-    commands::{most_recent_puzzle, most_recently_edited_puzzle},
-};
+use runner::{Day, PuzzleId, Solutions};
 
 const DEFAULT_DAY_RS_CONTENT: &str = "\
 use anyhow::Result;
@@ -35,7 +32,7 @@ pub(crate) struct Args {
 impl Args {
     fn puzzle_id(&self) -> Result<PuzzleId> {
         if self.next {
-            let (PuzzleId { year, day }, _) = most_recently_edited_puzzle()?;
+            let (PuzzleId { year, day }, _) = Solutions::get()?.most_recently_edited()?;
             let next_day = Day::try_new(day.into_inner() + 1)
                 .with_context(|| format!("no more days in {year}"))?;
             Ok(PuzzleId {
@@ -43,7 +40,7 @@ impl Args {
                 day: next_day,
             })
         } else if self.most_recent {
-            most_recent_puzzle()
+            PuzzleId::most_recent()
         } else {
             Ok(self
                 .puzzle
@@ -6,7 +6,7 @@ use std::{
 use console::{Color, Style};
 use indicatif::{ProgressBar, ProgressStyle};
 
-use crate::PuzzleId;
+use runner::PuzzleId;
 
 pub(crate) const HIGHLIGHT: Style = Style::new().blue().bold();
 pub(crate) const DIM: Style = Style::new().dim();
@@ -0,0 +1,208 @@
+use std::mem;
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{Attribute, Data, DeriveInput, Fields, LitStr, Member, parse_macro_input};
+
+enum Segment {
+    Literal(String),
+    Field {
+        name: String,
+        separator: Option<String>,
+    },
+}
+
+/// Splits a format string like `"{a} to {b} = {dist}"` into literal text
+/// and placeholders, where `{{` and `}}` escape braces and `{name:sep}`
+/// parses a list separated by `sep`.
+fn parse_format(format: &LitStr) -> syn::Result<Vec<Segment>> {
+    let error = |message: &str| syn::Error::new(format.span(), message);
+    let value = format.value();
+    let mut segments = Vec::new();
+    let mut literal = String::new();
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '{' if chars.next_if_eq(&'{').is_some() => literal.push('{'),
+            '}' if chars.next_if_eq(&'}').is_some() => literal.push('}'),
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(c) => placeholder.push(c),
+                        None => return Err(error("unclosed `{` in format string")),
+                    }
+                }
+
+                if !literal.is_empty() {
+                    segments.push(Segment::Literal(mem::take(&mut literal)));
+                } else if matches!(segments.last(), Some(Segment::Field { .. })) {
+                    return Err(error("placeholders must be separated by literal text"));
+                }
+                let (name, separator) = match placeholder.split_once(':') {
+                    Some((_, "")) => return Err(error("list separator must not be empty")),
+                    Some((name, separator)) => (name, Some(separator.to_string())),
+                    None => (placeholder.as_str(), None),
+                };
+                segments.push(Segment::Field {
+                    name: name.trim().to_string(),
+                    separator,
+                });
+            }
+            '}' => return Err(error("unmatched `}` in format string")),
+            c => literal.push(c),
+        }
+    }
+    if !literal.is_empty() {
+        segments.push(Segment::Literal(literal));
+    }
+
+    Ok(segments)
+}
+
+fn format_attr(attrs: &[Attribute], span: &impl quote::ToTokens) -> syn::Result<LitStr> {
+    attrs
+        .iter()
+        .find(|attr| attr.path().is_ident("parse"))
+        .ok_or_else(|| {
+            syn::Error::new_spanned(span, "missing `#[parse(\"...\")]` format attribute")
+        })?
+        .parse_args()
+}
+
+fn member_name(member: &Member) -> String {
+    match member {
+        Member::Named(ident) => ident.to_string(),
+        Member::Unnamed(index) => index.index.to_string(),
+    }
+}
+
+/// Generates code that parses `s` according to `format` and evaluates to a
+/// `Result` holding the value constructed by `path`.
+fn parse_fields(
+    path: &TokenStream2,
+    fields: &Fields,
+    format: &LitStr,
+    support: &TokenStream2,
+) -> syn::Result<TokenStream2> {
+    let error = |message: String| syn::Error::new(format.span(), message);
+    let segments = parse_format(format)?;
+    let members: Vec<Member> = fields.members().collect();
+    let mut vars = vec![None; members.len()];
+
+    let mut steps = Vec::new();
+    for (i, segment) in segments.iter().enumerate() {
+        match segment {
+            Segment::Literal(text) => steps.push(quote! { cursor.expect(#text)?; }),
+            Segment::Field { name, separator } => {
+                let index = members
+                    .iter()
+                    .position(|member| member_name(member) == *name)
+                    .ok_or_else(|| error(format!("unknown field `{name}`")))?;
+                if vars[index].is_some() {
+                    return Err(error(format!("field `{name}` appears more than once")));
+                }
+
+                let var = format_ident!("field_{index}");
+                let until = if let Some(Segment::Literal(text)) = segments.get(i + 1) {
+                    quote! { ::core::option::Option::Some(#text) }
+                } else {
+                    quote! { ::core::option::Option::None }
+                };
+                steps.push(if let Some(separator) = separator {
+                    quote! { let #var = cursor.list(#name, #separator, #until)?; }
+                } else {
+                    quote! { let #var = cursor.field(#name, #until)?; }
+                });
+                vars[index] = Some(var);
+            }
+        }
+    }
+
+    if let Some(index) = vars.iter().position(Option::is_none) {
+        let name = member_name(&members[index]);
+        return Err(error(format!("field `{name}` is missing from the format")));
+    }
+    let construct = match fields {
+        Fields::Named(_) => quote! { #path { #(#members: #vars),* } },
+        Fields::Unnamed(_) => quote! { #path(#(#vars),*) },
+        Fields::Unit => quote! { #path },
+    };
+
+    Ok(quote! {
+        let mut cursor = #support::Cursor::new(s);
+        #(#steps)*
+        cursor.finish()?;
+        ::core::result::Result::Ok(#construct)
+    })
+}
+
+fn derive(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let support = quote! { ::utils::input::__macro_support };
+    let body = match &input.data {
+        Data::Struct(data) => {
+            let format = format_attr(&input.attrs, &input.ident)?;
+            parse_fields(&quote! { Self }, &data.fields, &format, &support)?
+        }
+        Data::Enum(data) => {
+            if data.variants.is_empty() {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "cannot derive `Parse` for an enum without variants",
+                ));
+            }
+            let alternatives = data
+                .variants
+                .iter()
+                .map(|variant| {
+                    let format = format_attr(&variant.attrs, variant)?;
+                    let ident = &variant.ident;
+                    let body =
+                        parse_fields(&quote! { Self::#ident }, &variant.fields, &format, &support)?;
+                    Ok(quote! {
+                        &|s: &str| -> ::core::result::Result<Self, #support::ParseError> { #body }
+                    })
+                })
+                .collect::<syn::Result<Vec<_>>>()?;
+            quote! { #support::first_match(s, &[#(#alternatives),*]) }
+        }
+        Data::Union(_) => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "cannot derive `Parse` for a union",
+            ));
+        }
+    };
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::core::str::FromStr for #ident #ty_generics #where_clause {
+            type Err = #support::ParseError;
+
+            fn from_str(s: &str) -> ::core::result::Result<Self, Self::Err> {
+                #body
+            }
+        }
+    })
+}
+
+/// Derives `FromStr` from a format string given as `#[parse("...")]`.
+///
+/// Placeholders name the fields of a struct, or their indices for tuple
+/// structs, and are parsed with their own `FromStr` impl. Each placeholder
+/// extends up to the next occurrence of the following literal text, or to
+/// the end of the input. `{field:sep}` parses a `Vec` field separated by
+/// `sep`. Each enum variant has its own format, and the first one that
+/// matches is used.
+///
+/// Errors are `utils::input::ParseError`s pointing at the offending column.
+#[proc_macro_derive(Parse, attributes(parse))]
+pub fn derive_parse(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as DeriveInput);
+    derive(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
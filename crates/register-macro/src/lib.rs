@@ -1,9 +1,34 @@
 use proc_macro::TokenStream;
 use quote::{format_ident, quote};
-use syn::{ItemFn, parse_macro_input};
+use syn::{
+    Ident, ItemFn,
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+};
+
+enum Kind {
+    Solution,
+    Generator,
+}
+
+impl Parse for Kind {
+    fn parse(input: ParseStream<'_>) -> syn::Result<Self> {
+        if input.is_empty() {
+            return Ok(Self::Solution);
+        }
+
+        let ident: Ident = input.parse()?;
+        if ident == "generator" {
+            Ok(Self::Generator)
+        } else {
+            Err(syn::Error::new(ident.span(), "expected `generator`"))
+        }
+    }
+}
 
 #[proc_macro_attribute]
-pub fn register(_attr: TokenStream, item: TokenStream) -> TokenStream {
+pub fn register(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let kind = parse_macro_input!(attr as Kind);
     let input_fn = parse_macro_input!(item as ItemFn);
     let fn_name = &input_fn.sig.ident;
     let fn_name_str = fn_name.to_string();
@@ -11,16 +36,29 @@ pub fn register(_attr: TokenStream, item: TokenStream) -> TokenStream {
     let krate = quote! { ::register };
     let macro_supp = quote! { #krate::__macro_support };
 
+    let (slice, ty, func) = match kind {
+        Kind::Solution => (
+            quote! { REGISTERED_FUNCTIONS },
+            quote! { RegisteredFunction },
+            quote! { |input: &str| #macro_supp::NormalizeOutput::normalize(#fn_name(input)) },
+        ),
+        Kind::Generator => (
+            quote! { REGISTERED_GENERATORS },
+            quote! { RegisteredGenerator },
+            quote! { #fn_name },
+        ),
+    };
+
     quote! {
         #input_fn
 
-        #[#macro_supp::distributed_slice(#macro_supp::REGISTERED_FUNCTIONS)]
+        #[#macro_supp::distributed_slice(#macro_supp::#slice)]
         #[linkme(crate=#macro_supp::linkme)]
-        static #static_name: #krate::RegisteredFunction = #krate::RegisteredFunction {
+        static #static_name: #krate::#ty = #krate::#ty {
             module_path: module_path!(),
             name: #fn_name_str,
             file: file!(),
-            func: |input: &str| #macro_supp::NormalizeOutput::normalize(#fn_name(input)),
+            func: #func,
         };
     }
     .into()
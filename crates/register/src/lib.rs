@@ -3,6 +3,12 @@ pub use register_macro::register;
 
 pub type SolutionFunction = fn(&str) -> Result<(String, String)>;
 
+/// Generates a random puzzle input from a seed and a size hint.
+///
+/// Smaller sizes should produce smaller inputs, which is used to shrink
+/// counterexamples found while fuzzing.
+pub type GeneratorFunction = fn(u64, usize) -> String;
+
 #[derive(Debug, Clone, Copy)]
 pub struct RegisteredFunction {
     pub module_path: &'static str,
@@ -18,6 +24,28 @@ impl RegisteredFunction {
     }
 }
 
+#[derive(Debug, Clone, Copy)]
+pub struct RegisteredGenerator {
+    pub module_path: &'static str,
+    pub name: &'static str,
+    pub file: &'static str,
+    pub func: GeneratorFunction,
+}
+
+impl RegisteredGenerator {
+    #[must_use]
+    pub fn all() -> &'static [Self] {
+        &__macro_support::REGISTERED_GENERATORS
+    }
+}
+
+/// Converts the return value of a solution into the form produced by
+/// [`SolutionFunction`]s, e.g. to compare solutions with different return
+/// types in tests.
+pub fn normalize(output: impl __macro_support::NormalizeOutput) -> Result<(String, String)> {
+    output.normalize()
+}
+
 #[doc(hidden)]
 pub mod __macro_support {
     pub use linkme;
@@ -26,6 +54,9 @@ pub mod __macro_support {
     #[distributed_slice]
     pub static REGISTERED_FUNCTIONS: [super::RegisteredFunction];
 
+    #[distributed_slice]
+    pub static REGISTERED_GENERATORS: [super::RegisteredGenerator];
+
     pub trait NormalizeOutput {
         fn normalize(self) -> anyhow::Result<(String, String)>;
     }
@@ -0,0 +1,75 @@
+use std::time::{Duration, Instant};
+
+use anyhow::Result;
+use register::SolutionFunction;
+
+/// Wall times of repeated runs of a solution.
+#[derive(Debug, Clone)]
+pub struct Samples(Vec<Duration>);
+
+impl Samples {
+    /// Individual run times in the order they were measured (never empty).
+    #[must_use]
+    pub fn runs(&self) -> &[Duration] {
+        &self.0
+    }
+
+    /// Middle run time, or the mean of the two middle ones for an even
+    /// number of runs.
+    #[must_use]
+    pub fn median(&self) -> Duration {
+        let mut runs = self.0.clone();
+        let count = runs.len();
+        let (small, &mut upper_median, _) = runs.select_nth_unstable(count / 2);
+        if count.is_multiple_of(2) {
+            let lower_median = *small
+                .iter()
+                .max()
+                .expect("we are guaranteed to have at least two elements");
+            (lower_median + upper_median) / 2
+        } else {
+            upper_median
+        }
+    }
+
+    #[must_use]
+    pub fn min(&self) -> Duration {
+        *self.0.iter().min().expect("samples are never empty")
+    }
+
+    #[must_use]
+    pub fn mean(&self) -> Duration {
+        self.0.iter().sum::<Duration>() / self.0.len() as u32
+    }
+}
+
+/// Runs a solution repeatedly for at least `min_time`, and at least once.
+pub fn benchmark(solution: SolutionFunction, input: &str, min_time: Duration) -> Result<Samples> {
+    let run = || {
+        let start_run = Instant::now();
+        solution(input)?;
+        anyhow::Ok(start_run.elapsed())
+    };
+
+    let start = Instant::now();
+    let mut runs = vec![run()?];
+    while start.elapsed() < min_time {
+        runs.push(run()?);
+    }
+
+    Ok(Samples(runs))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn median() {
+        let samples =
+            |millis: &[u64]| Samples(millis.iter().copied().map(Duration::from_millis).collect());
+        assert_eq!(samples(&[5]).median(), Duration::from_millis(5));
+        assert_eq!(samples(&[9, 1, 5]).median(), Duration::from_millis(5));
+        assert_eq!(samples(&[1, 8, 2, 4]).median(), Duration::from_millis(3));
+    }
+}
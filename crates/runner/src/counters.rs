@@ -0,0 +1,284 @@
+use std::fmt::{self, Display, Formatter};
+
+use anyhow::Result;
+
+/// Hardware performance counter values, averaged over a number of runs.
+///
+/// Individual counters may be missing if the CPU or hypervisor doesn't
+/// support them.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct CounterValues {
+    pub cycles: Option<f64>,
+    pub instructions: Option<f64>,
+    pub branch_misses: Option<f64>,
+    pub cache_misses: Option<f64>,
+}
+
+impl CounterValues {
+    fn per_run(self, runs: usize) -> Self {
+        let div = |value: Option<f64>| value.map(|value| value / runs as f64);
+        Self {
+            cycles: div(self.cycles),
+            instructions: div(self.instructions),
+            branch_misses: div(self.branch_misses),
+            cache_misses: div(self.cache_misses),
+        }
+    }
+}
+
+impl Display for CounterValues {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut parts = Vec::new();
+        if let Some(cycles) = self.cycles {
+            parts.push(format!("{} cycles", Count(cycles)));
+        }
+        if let Some(instructions) = self.instructions {
+            let ipc = self
+                .cycles
+                .filter(|&cycles| cycles > 0.0)
+                .map(|cycles| format!(" ({:.2} IPC)", instructions / cycles))
+                .unwrap_or_default();
+            parts.push(format!("{} instrs{ipc}", Count(instructions)));
+        }
+        if let Some(branch_misses) = self.branch_misses {
+            parts.push(format!("{} branch misses", Count(branch_misses)));
+        }
+        if let Some(cache_misses) = self.cache_misses {
+            parts.push(format!("{} cache misses", Count(cache_misses)));
+        }
+        write!(f, "{}", parts.join(", "))
+    }
+}
+
+/// Runs `f` repeatedly until it returns `false` and reports the mean counter
+/// values per call.
+pub fn measure_per_run(
+    counters: &Counters,
+    mut f: impl FnMut() -> Result<bool>,
+) -> Result<CounterValues> {
+    let mut runs = 0;
+    let (result, values) = counters.measure(|| {
+        loop {
+            runs += 1;
+            if !f()? {
+                break anyhow::Ok(());
+            }
+        }
+    });
+    result?;
+    Ok(values.per_run(runs))
+}
+
+struct Count(f64);
+
+impl Display for Count {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        const UNITS: [(f64, &str); 3] = [(1e9, "G"), (1e6, "M"), (1e3, "K")];
+        match UNITS.into_iter().find(|&(scale, _)| self.0 >= scale) {
+            Some((scale, unit)) => write!(f, "{:.2}{unit}", self.0 / scale),
+            None => write!(f, "{:.0}", self.0),
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+pub use linux::Counters;
+
+#[cfg(not(target_os = "linux"))]
+pub use unsupported::Counters;
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use std::{
+        fs::File,
+        io::{self, Read},
+        os::fd::{AsRawFd, FromRawFd},
+    };
+
+    use anyhow::{Result, bail};
+
+    use super::CounterValues;
+
+    const PERF_TYPE_HARDWARE: u32 = 0;
+    const PERF_COUNT_HW_CPU_CYCLES: u64 = 0;
+    const PERF_COUNT_HW_INSTRUCTIONS: u64 = 1;
+    const PERF_COUNT_HW_CACHE_MISSES: u64 = 3;
+    const PERF_COUNT_HW_BRANCH_MISSES: u64 = 5;
+
+    const PERF_FORMAT_TOTAL_TIME_ENABLED: u64 = 1 << 0;
+    const PERF_FORMAT_TOTAL_TIME_RUNNING: u64 = 1 << 1;
+
+    const FLAG_DISABLED: u64 = 1 << 0;
+    const FLAG_INHERIT: u64 = 1 << 1;
+    const FLAG_EXCLUDE_KERNEL: u64 = 1 << 5;
+    const FLAG_EXCLUDE_HV: u64 = 1 << 6;
+
+    const PERF_EVENT_IOC_ENABLE: libc::c_ulong = 0x2400;
+    const PERF_EVENT_IOC_DISABLE: libc::c_ulong = 0x2401;
+    const PERF_EVENT_IOC_RESET: libc::c_ulong = 0x2403;
+    const PERF_FLAG_FD_CLOEXEC: libc::c_ulong = 1 << 3;
+
+    /// `struct perf_event_attr` from `linux/perf_event.h` (version 5 layout).
+    #[repr(C)]
+    #[derive(Default)]
+    struct PerfEventAttr {
+        type_: u32,
+        size: u32,
+        config: u64,
+        sample_period: u64,
+        sample_type: u64,
+        read_format: u64,
+        flags: u64,
+        wakeup_events: u32,
+        bp_type: u32,
+        config1: u64,
+        config2: u64,
+        branch_sample_type: u64,
+        sample_regs_user: u64,
+        sample_stack_user: u32,
+        clockid: i32,
+        sample_regs_intr: u64,
+        aux_watermark: u32,
+        sample_max_stack: u16,
+        reserved: u16,
+    }
+
+    /// Set of hardware counters for the calling thread, opened via
+    /// `perf_event_open`.
+    ///
+    /// Counters are inherited by threads the calling thread (or one of its
+    /// descendants) spawns after opening them. Reads include the counts of
+    /// all inherited threads, whether they are still running or already
+    /// exited, so pools like rayon's global pool are measured as long as
+    /// they are started after opening. Threads that already existed before
+    /// are not counted.
+    #[derive(Debug)]
+    pub struct Counters {
+        cycles: Option<File>,
+        instructions: Option<File>,
+        branch_misses: Option<File>,
+        cache_misses: Option<File>,
+    }
+
+    impl Counters {
+        /// Opens all counters supported by the machine, failing only if none
+        /// are available (e.g. in containers or with a restrictive
+        /// `perf_event_paranoid` setting).
+        pub fn open() -> Result<Self> {
+            let cycles = open_counter(PERF_COUNT_HW_CPU_CYCLES);
+            let instructions = open_counter(PERF_COUNT_HW_INSTRUCTIONS);
+            let branch_misses = open_counter(PERF_COUNT_HW_BRANCH_MISSES);
+            let cache_misses = open_counter(PERF_COUNT_HW_CACHE_MISSES);
+            if let (Err(err), Err(_), Err(_), Err(_)) =
+                (&cycles, &instructions, &branch_misses, &cache_misses)
+            {
+                bail!("perf_event_open failed: {err}");
+            }
+
+            Ok(Self {
+                cycles: cycles.ok(),
+                instructions: instructions.ok(),
+                branch_misses: branch_misses.ok(),
+                cache_misses: cache_misses.ok(),
+            })
+        }
+
+        /// Runs `f` and returns the counter values accumulated during it.
+        pub fn measure<T>(&self, f: impl FnOnce() -> T) -> (T, CounterValues) {
+            let files = self.files();
+            for file in files.iter().flatten() {
+                ioctl(file, PERF_EVENT_IOC_RESET);
+                ioctl(file, PERF_EVENT_IOC_ENABLE);
+            }
+            let result = f();
+            for file in files.iter().flatten() {
+                ioctl(file, PERF_EVENT_IOC_DISABLE);
+            }
+
+            let [cycles, instructions, branch_misses, cache_misses] =
+                files.map(|file| file.and_then(read_counter));
+            let values = CounterValues {
+                cycles,
+                instructions,
+                branch_misses,
+                cache_misses,
+            };
+            (result, values)
+        }
+
+        fn files(&self) -> [Option<&File>; 4] {
+            [
+                self.cycles.as_ref(),
+                self.instructions.as_ref(),
+                self.branch_misses.as_ref(),
+                self.cache_misses.as_ref(),
+            ]
+        }
+    }
+
+    fn open_counter(config: u64) -> io::Result<File> {
+        let attr = PerfEventAttr {
+            type_: PERF_TYPE_HARDWARE,
+            size: size_of::<PerfEventAttr>() as u32,
+            config,
+            read_format: PERF_FORMAT_TOTAL_TIME_ENABLED | PERF_FORMAT_TOTAL_TIME_RUNNING,
+            flags: FLAG_DISABLED | FLAG_INHERIT | FLAG_EXCLUDE_KERNEL | FLAG_EXCLUDE_HV,
+            ..PerfEventAttr::default()
+        };
+
+        // SAFETY: `attr` is a valid `perf_event_attr` that outlives the call
+        let fd = unsafe {
+            libc::syscall(
+                libc::SYS_perf_event_open,
+                &raw const attr,
+                0,
+                -1,
+                -1,
+                PERF_FLAG_FD_CLOEXEC,
+            )
+        };
+        if fd < 0 {
+            return Err(io::Error::last_os_error());
+        }
+
+        // SAFETY: the kernel just handed us ownership of this fd
+        Ok(unsafe { File::from_raw_fd(fd as i32) })
+    }
+
+    fn ioctl(file: &File, request: libc::c_ulong) {
+        // SAFETY: all requests we use take no argument
+        unsafe { libc::ioctl(file.as_raw_fd(), request, 0) };
+    }
+
+    /// Reads a counter, scaling it up if the kernel had to multiplex it.
+    fn read_counter(mut file: &File) -> Option<f64> {
+        let mut buffer = [0; 24];
+        file.read_exact(&mut buffer).ok()?;
+        let [value, enabled, running] = [0, 1, 2].map(|i| {
+            let bytes = buffer[8 * i..8 * (i + 1)].try_into().expect("8 byte slice");
+            u64::from_ne_bytes(bytes) as f64
+        });
+        (running > 0.0).then(|| value * enabled / running)
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod unsupported {
+    use anyhow::{Result, bail};
+
+    use super::CounterValues;
+
+    /// Hardware counters, which are not supported on this platform.
+    #[derive(Debug)]
+    pub struct Counters;
+
+    impl Counters {
+        pub fn open() -> Result<Self> {
+            bail!("hardware counters are only supported on Linux")
+        }
+
+        pub fn measure<T>(&self, f: impl FnOnce() -> T) -> (T, CounterValues) {
+            (f(), CounterValues::default())
+        }
+    }
+}
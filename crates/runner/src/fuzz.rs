@@ -0,0 +1,264 @@
+use std::{cell::RefCell, fmt::Write as _, fs, panic, path::Path, time::Duration};
+
+use anyhow::{Context, Result, ensure};
+use register::{GeneratorFunction, SolutionFunction};
+
+use crate::{Alternative, PuzzleSolutions, run_with_timeout};
+
+/// Result of running a solution, with errors and panics rendered as text.
+pub type Outcome = Result<(String, String), String>;
+
+/// Result of comparing the alternative solutions of a puzzle against the main
+/// one.
+#[derive(Debug)]
+pub enum Check<'a> {
+    Agree,
+    /// The main solution failed, so the input is probably not valid.
+    Invalid,
+    /// At least one alternative solution failed or returned a different
+    /// result than the main solution.
+    Disagree(Disagreement<'a>),
+}
+
+/// Alternative solutions that disagree with the main solution on an input.
+#[derive(Debug)]
+pub struct Disagreement<'a> {
+    pub main: (String, String),
+    /// Only the alternatives that disagree.
+    pub alts: Vec<(&'a Alternative, Outcome)>,
+}
+
+/// Runs all solutions of a puzzle on `input` and compares their results.
+///
+/// Solutions running longer than `timeout` count as failed. As with
+/// [`run_with_timeout`], they keep running in the background.
+#[must_use]
+pub fn check<'a>(solutions: &'a PuzzleSolutions, input: &str, timeout: Duration) -> Check<'a> {
+    let Ok(main) = evaluate(solutions.main, input, timeout) else {
+        return Check::Invalid;
+    };
+
+    let alts: Vec<_> = solutions
+        .alts
+        .iter()
+        .map(|alt| (alt, evaluate(alt.func, input, timeout)))
+        .filter(|(_, outcome)| outcome.as_ref() != Ok(&main))
+        .collect();
+    if alts.is_empty() {
+        Check::Agree
+    } else {
+        Check::Disagree(Disagreement { main, alts })
+    }
+}
+
+fn evaluate(func: SolutionFunction, input: &str, timeout: Duration) -> Outcome {
+    run_with_timeout(func, input, timeout).map_err(|err| format!("{err:#}"))
+}
+
+/// Reduces a counterexample to a (locally) minimal one.
+///
+/// We first try to regenerate the input from the same seed with smaller
+/// sizes, then remove lines and comma-separated items for as long as the
+/// solutions still disagree.
+///
+/// Returns the shrunk input together with the disagreement observed on it,
+/// so that callers don't have to rerun solutions that may be
+/// nondeterministic. `disagreement` is the one observed on `input`.
+///
+/// Solutions will likely panic on some of the candidates, so callers may
+/// want to silence the panic hook while shrinking.
+pub fn shrink<'a>(
+    solutions: &'a PuzzleSolutions,
+    generator: GeneratorFunction,
+    (seed, size): (u64, usize),
+    input: String,
+    disagreement: Disagreement<'a>,
+    timeout: Duration,
+) -> (String, Disagreement<'a>) {
+    // Every candidate that is a counterexample gets accepted, so the last
+    // disagreement seen is the one for the final input.
+    let last = RefCell::new(disagreement);
+    let is_counterexample = |input: &str| match check(solutions, input, timeout) {
+        Check::Disagree(disagreement) => {
+            *last.borrow_mut() = disagreement;
+            true
+        }
+        Check::Agree | Check::Invalid => false,
+    };
+    let input = (0..size)
+        .filter_map(|size| panic::catch_unwind(|| generator(seed, size)).ok())
+        .find(|input| is_counterexample(input))
+        .unwrap_or(input);
+
+    let lines = input.lines().map(str::to_owned).collect();
+    let mut lines = remove_chunks(lines, "\n", is_counterexample);
+    for i in 0..lines.len() {
+        let items = lines[i].split(',').map(str::to_owned).collect();
+        let is_counterexample = |line: &str| {
+            let mut lines = lines.clone();
+            line.clone_into(&mut lines[i]);
+            is_counterexample(&lines.join("\n"))
+        };
+        lines[i] = remove_chunks(items, ",", is_counterexample).join(",");
+    }
+
+    (lines.join("\n"), last.into_inner())
+}
+
+fn remove_chunks(
+    mut parts: Vec<String>,
+    separator: &str,
+    is_counterexample: impl Fn(&str) -> bool,
+) -> Vec<String> {
+    let mut chunk_size = parts.len() / 2;
+    while chunk_size > 0 {
+        let mut start = 0;
+        while start < parts.len() {
+            let end = (start + chunk_size).min(parts.len());
+            let candidate = [&parts[..start], &parts[end..]].concat();
+            if !candidate.is_empty() && is_counterexample(&candidate.join(separator)) {
+                parts = candidate;
+            } else {
+                start = end;
+            }
+        }
+
+        chunk_size /= 2;
+    }
+
+    parts
+}
+
+/// Adds a test to the test module of the solution file at `path` asserting
+/// that the given alternatives agree with the main solution on `input`.
+///
+/// Returns the name of the new test, which is derived from `seed`.
+pub fn add_regression_test(
+    path: &Path,
+    seed: u64,
+    input: &str,
+    alts: &[&Alternative],
+) -> Result<String> {
+    let display = path.display();
+    let source = fs::read_to_string(path).with_context(|| format!("failed to read {display}"))?;
+    let name = format!("fuzz_{seed}");
+    ensure!(
+        !source.contains(&format!("fn {name}()")),
+        "{display} already contains a test named `{name}`"
+    );
+
+    // Inputs with leading whitespace can't use string continuation escapes.
+    let input_literal = if input
+        .lines()
+        .any(|line| line.starts_with(char::is_whitespace))
+    {
+        format!("{input:?}")
+    } else {
+        let escaped = input.replace('\\', "\\\\").replace('"', "\\\"");
+        format!("\"\\\n{escaped}\"")
+    };
+
+    let mut test = format!(
+        "
+    #[test]
+    fn {name}() {{
+        const INPUT: &str = {input_literal};
+
+        let main = register::normalize(run(INPUT)).unwrap();
+"
+    );
+    for alt in alts {
+        let path = &alt.path;
+        let _ = writeln!(
+            test,
+            "        assert_eq!(register::normalize({path}(INPUT)).unwrap(), main);"
+        );
+    }
+    test.push_str("    }\n");
+
+    let file = syn::parse_file(&source).with_context(|| format!("failed to parse {display}"))?;
+    let tests = file.items.iter().find_map(|item| match item {
+        syn::Item::Mod(module) if module.ident == "tests" => Some(module),
+        _ => None,
+    });
+    let source = match tests {
+        Some(syn::ItemMod {
+            content: Some((brace, _)),
+            ..
+        }) => {
+            let end = byte_offset(&source, brace.span.close().start());
+            format!("{}{test}{}", &source[..end], &source[end..])
+        }
+        tests => {
+            // Don't clash with a test module in a separate file
+            let module = if tests.is_some() {
+                "fuzz_regressions"
+            } else {
+                "tests"
+            };
+            let source = source.trim_end();
+            format!("{source}\n\n#[cfg(test)]\nmod {module} {{\n    use super::*;\n{test}}}\n")
+        }
+    };
+    fs::write(path, source).with_context(|| format!("failed to write {display}"))?;
+
+    Ok(name)
+}
+
+/// Converts a line and character column from `syn` to a byte offset.
+fn byte_offset(source: &str, location: proc_macro2::LineColumn) -> usize {
+    let line_start: usize = source
+        .split_inclusive('\n')
+        .take(location.line - 1)
+        .map(str::len)
+        .sum();
+    source[line_start..]
+        .char_indices()
+        .nth(location.column)
+        .map_or(source.len(), |(offset, _)| line_start + offset)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn regression_test_in_test_module() {
+        let source = "\
+fn run(input: &str) -> usize {
+    input.len()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn braces() {
+        assert_eq!(run(\"}\"), 1);
+    }
+}
+
+struct Trailing {
+    é: u8,
+}
+";
+        let path = std::env::temp_dir().join(format!("fuzz_splice_{}.rs", std::process::id()));
+        fs::write(&path, source).unwrap();
+        let alt = Alternative {
+            name: "alt".into(),
+            path: "alt".into(),
+            func: |_| Ok((String::new(), String::new())),
+        };
+        let name = add_regression_test(&path, 7, "a{b", &[&alt]).unwrap();
+        let result = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(name, "fuzz_7");
+        let test_end = result.find("fn fuzz_7").unwrap();
+        let trailing = result.find("struct Trailing").unwrap();
+        assert!(result.find("mod tests").unwrap() < test_end && test_end < trailing);
+        assert!(result.ends_with("struct Trailing {\n    é: u8,\n}\n"));
+        syn::parse_file(&result).unwrap();
+    }
+}
@@ -0,0 +1,85 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+use anyhow::{Context, Result};
+use jiff::{
+    Zoned,
+    tz::{Offset, TimeZone},
+};
+use nutype::nutype;
+
+/// An Advent of Code event year (2015 or later).
+#[nutype(
+    validate(greater_or_equal = 2015),
+    derive(
+        Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Display, FromStr
+    )
+)]
+pub struct Year(u16);
+
+/// A puzzle day within an event (1 to 25).
+#[nutype(
+    validate(greater_or_equal = 1, less_or_equal = 25),
+    const_fn,
+    derive(
+        Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Display, FromStr
+    )
+)]
+pub struct Day(u8);
+
+impl Day {
+    /// The last day of an event, which only has a single part.
+    pub const TWENTY_FIVE: Self = match Self::try_new(25) {
+        Ok(day) => day,
+        Err(_) => panic!("invalid hardcoded value"),
+    };
+}
+
+/// Identifies a single puzzle, displayed and parsed as `<year>-<day>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PuzzleId {
+    pub year: Year,
+    pub day: Day,
+}
+
+impl PuzzleId {
+    /// Returns the most recently released puzzle.
+    pub fn most_recent() -> Result<Self> {
+        let est = TimeZone::fixed(Offset::constant(-5));
+        let date_est = Zoned::now().with_time_zone(est).date();
+        let year = u16::try_from(date_est.year()).context("current year is negative!?")?;
+        let (year, day) = if date_est.month() == 12 {
+            #[expect(
+                clippy::cast_sign_loss,
+                reason = "day() is guaranteed to be in [1, 31]"
+            )]
+            let day = date_est.day() as u8;
+            let day = Day::try_new(day.min(25)).expect("check should ensure validity");
+            (year, day)
+        } else {
+            (year - 1, Day::TWENTY_FIVE)
+        };
+
+        let year = Year::try_new(year).context("before the start of AoC")?;
+        Ok(Self { year, day })
+    }
+}
+
+impl Display for PuzzleId {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}", self.year, self.day)
+    }
+}
+
+impl FromStr for PuzzleId {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (year_str, day_str) = s.split_once('-').context("expected <year>-<day>")?;
+        let year = year_str.parse().context("invalid year")?;
+        let day = day_str.parse().context("invalid day")?;
+        Ok(Self { year, day })
+    }
+}
@@ -1,12 +1,13 @@
 use std::{
+    collections::HashMap,
     env, fs,
+    hash::BuildHasher,
     io::ErrorKind,
     path::{Path, PathBuf},
 };
 
 use anyhow::{Context, Result};
 use directories::ProjectDirs;
-use once_cell::sync::OnceCell;
 use ureq::Agent;
 
 use crate::PuzzleId;
@@ -16,36 +17,78 @@ const USER_AGENT: &str =
 const ORGANIZATION: &str = "felerius";
 const APPLICATION: &str = "advent-of-code";
 
-struct Downloader {
-    cache_dir: PathBuf,
+/// Provides puzzle inputs.
+pub trait InputSource {
+    fn get(&self, puzzle_id: PuzzleId) -> Result<String>;
+}
+
+impl<F: Fn(PuzzleId) -> Result<String>> InputSource for F {
+    fn get(&self, puzzle_id: PuzzleId) -> Result<String> {
+        self(puzzle_id)
+    }
+}
+
+impl<S: BuildHasher> InputSource for HashMap<PuzzleId, String, S> {
+    fn get(&self, puzzle_id: PuzzleId) -> Result<String> {
+        self.get(&puzzle_id)
+            .cloned()
+            .with_context(|| format!("no input for {puzzle_id}"))
+    }
+}
+
+/// Reads inputs from `<dir>/<year>-<day>.txt` files.
+#[derive(Debug, Clone)]
+pub struct DirectoryInputs {
+    dir: PathBuf,
+}
+
+impl DirectoryInputs {
+    pub fn new(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    fn path(&self, puzzle_id: PuzzleId) -> PathBuf {
+        self.dir.join(format!("{puzzle_id}.txt"))
+    }
+}
+
+impl InputSource for DirectoryInputs {
+    fn get(&self, puzzle_id: PuzzleId) -> Result<String> {
+        let path = self.path(puzzle_id);
+        fs::read_to_string(&path)
+            .with_context(|| format!("failed to read input from {}", path.display()))
+    }
+}
+
+/// Downloads inputs from adventofcode.com and caches them on disk.
+///
+/// Downloading requires the session cookie to be set in the `AOC_SESSION`
+/// environment variable.
+#[derive(Debug)]
+pub struct AocInputs {
+    cache: DirectoryInputs,
     http_agent: Agent,
 }
 
-impl Downloader {
-    fn new() -> Result<Self> {
+impl AocInputs {
+    /// Uses the platform's cache directory for downloaded inputs.
+    pub fn new() -> Result<Self> {
         let cache_dir = ProjectDirs::from("", ORGANIZATION, APPLICATION)
             .context("could not determine home directory")?
             .cache_dir()
             .to_path_buf();
+        Ok(Self::with_cache_dir(cache_dir))
+    }
+
+    pub fn with_cache_dir(cache_dir: impl Into<PathBuf>) -> Self {
         let http_agent = Agent::config_builder()
             .user_agent(USER_AGENT)
             .build()
             .new_agent();
-        Ok(Self {
-            cache_dir,
+        Self {
+            cache: DirectoryInputs::new(cache_dir),
             http_agent,
-        })
-    }
-
-    fn get(&self, puzzle_id: PuzzleId) -> Result<String> {
-        let cache_file = self.cache_dir.join(format!("{puzzle_id}.txt"));
-        fs::read_to_string(&cache_file).or_else(|err| {
-            if err.kind() == ErrorKind::NotFound {
-                self.download(puzzle_id, &cache_file)
-            } else {
-                Err(err).context("failed to read input data from cache file")
-            }
-        })
+        }
     }
 
     fn download(&self, puzzle_id: PuzzleId, cache_file: &Path) -> Result<String> {
@@ -66,14 +109,22 @@ impl Downloader {
             .context("failed to decode input data from adventofcode.com")?;
         input.truncate(input.trim_end().len());
 
-        fs::create_dir_all(&self.cache_dir).context("failed to create cache directory")?;
+        fs::create_dir_all(&self.cache.dir).context("failed to create cache directory")?;
         fs::write(cache_file, &input)
             .context("failed to write downloaded input data to cache file")?;
         Ok(input)
     }
 }
 
-pub(crate) fn get(puzzle_id: PuzzleId) -> Result<String> {
-    static DOWNLOADER: OnceCell<Downloader> = OnceCell::new();
-    DOWNLOADER.get_or_try_init(Downloader::new)?.get(puzzle_id)
+impl InputSource for AocInputs {
+    fn get(&self, puzzle_id: PuzzleId) -> Result<String> {
+        let cache_file = self.cache.path(puzzle_id);
+        fs::read_to_string(&cache_file).or_else(|err| {
+            if err.kind() == ErrorKind::NotFound {
+                self.download(puzzle_id, &cache_file)
+            } else {
+                Err(err).context("failed to read input data from cache file")
+            }
+        })
+    }
 }
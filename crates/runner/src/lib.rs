@@ -0,0 +1,30 @@
+//! Enumerating, running and benchmarking the solutions registered with
+//! [`register::register`].
+//!
+//! The command line interface in the `cli` crate is a thin front end for this
+//! crate, which can also be used to drive solutions from other tools or
+//! tests:
+//!
+//! ```ignore
+//! let solutions = runner::Solutions::get()?;
+//! let inputs = runner::inputs::AocInputs::new()?;
+//! for (id, puzzle) in solutions.sorted() {
+//!     let input = inputs.get(id)?;
+//!     let (part1, part2) = runner::run_with_timeout(puzzle.main, &input, timeout)?;
+//!     let samples = runner::bench::benchmark(puzzle.main, &input, min_time)?;
+//! }
+//! ```
+pub mod bench;
+pub mod counters;
+pub mod fuzz;
+mod id;
+pub mod inputs;
+pub mod memory;
+mod run;
+mod solutions;
+
+pub use id::{Day, PuzzleId, Year};
+pub use inputs::InputSource;
+pub use register::{GeneratorFunction, SolutionFunction};
+pub use run::{run, run_with_timeout};
+pub use solutions::{Alternative, PuzzleSolutions, Solutions};
@@ -0,0 +1,140 @@
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    fmt::{self, Display, Formatter},
+    sync::atomic::{AtomicBool, AtomicIsize, AtomicUsize, Ordering},
+};
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+static CURRENT: AtomicIsize = AtomicIsize::new(0);
+static PEAK: AtomicIsize = AtomicIsize::new(0);
+static TOTAL: AtomicUsize = AtomicUsize::new(0);
+static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Heap usage of a single solution run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MemoryUsage {
+    /// Maximum number of bytes allocated at once during the run.
+    pub peak: usize,
+    /// Sum of the sizes of all allocations.
+    pub total: usize,
+    /// Number of allocations, including reallocations.
+    pub count: usize,
+}
+
+impl Display for MemoryUsage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} peak, {} total, {} allocs",
+            Bytes(self.peak),
+            Bytes(self.total),
+            self.count
+        )
+    }
+}
+
+/// Runs `f` and records its heap usage.
+///
+/// Allocations from all threads are counted, so this must not be called
+/// concurrently. Only allocations going through [`CountingAllocator`] are
+/// seen, so the binary must install it as its global allocator.
+///
+/// The peak is relative to the heap usage when `f` starts, so freeing memory
+/// allocated before doesn't count against it.
+pub fn measure<T>(f: impl FnOnce() -> T) -> (T, MemoryUsage) {
+    let start = CURRENT.load(Ordering::SeqCst);
+    PEAK.store(start, Ordering::SeqCst);
+    TOTAL.store(0, Ordering::SeqCst);
+    COUNT.store(0, Ordering::SeqCst);
+    ENABLED.store(true, Ordering::SeqCst);
+    let result = f();
+    ENABLED.store(false, Ordering::SeqCst);
+
+    let usage = MemoryUsage {
+        peak: (PEAK.load(Ordering::SeqCst) - start).max(0).unsigned_abs(),
+        total: TOTAL.load(Ordering::SeqCst),
+        count: COUNT.load(Ordering::SeqCst),
+    };
+    (result, usage)
+}
+
+fn record(allocated: usize, freed: usize) {
+    // The current usage is always tracked, so that it is accurate once a
+    // measurement starts.
+    let delta = allocated as isize - freed as isize;
+    let current = CURRENT.fetch_add(delta, Ordering::Relaxed) + delta;
+    if !ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+
+    if allocated > 0 {
+        TOTAL.fetch_add(allocated, Ordering::Relaxed);
+        COUNT.fetch_add(1, Ordering::Relaxed);
+    }
+    PEAK.fetch_max(current, Ordering::Relaxed);
+}
+
+/// Global allocator forwarding to [`System`] which keeps track of the
+/// statistics reported by [`measure`].
+///
+/// ```ignore
+/// #[global_allocator]
+/// static ALLOCATOR: CountingAllocator = CountingAllocator;
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CountingAllocator;
+
+// SAFETY: all calls are forwarded to the system allocator unchanged
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // SAFETY: forwarded from our caller
+        let ptr = unsafe { System.alloc(layout) };
+        if !ptr.is_null() {
+            record(layout.size(), 0);
+        }
+        ptr
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        // SAFETY: forwarded from our caller
+        let ptr = unsafe { System.alloc_zeroed(layout) };
+        if !ptr.is_null() {
+            record(layout.size(), 0);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        // SAFETY: forwarded from our caller
+        unsafe { System.dealloc(ptr, layout) };
+        record(0, layout.size());
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        // SAFETY: forwarded from our caller
+        let new_ptr = unsafe { System.realloc(ptr, layout, new_size) };
+        if !new_ptr.is_null() {
+            record(new_size, layout.size());
+        }
+        new_ptr
+    }
+}
+
+struct Bytes(usize);
+
+impl Display for Bytes {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+        if self.0 < 1024 {
+            return write!(f, "{}B", self.0);
+        }
+
+        let mut value = self.0 as f64 / 1024.0;
+        let mut unit = 0;
+        while value >= 1024.0 && unit + 1 < UNITS.len() {
+            value /= 1024.0;
+            unit += 1;
+        }
+        write!(f, "{value:.1}{}", UNITS[unit])
+    }
+}
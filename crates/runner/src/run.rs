@@ -0,0 +1,51 @@
+use std::{
+    panic,
+    sync::{Arc, mpsc},
+    thread,
+    time::Duration,
+};
+
+use anyhow::{Result, anyhow, bail};
+use register::SolutionFunction;
+
+/// Stack size for solutions run on separate threads, matching the usual main
+/// thread stack size on Linux (some solutions recurse deeply).
+const STACK_SIZE: usize = 8 << 20;
+
+/// Runs a solution, turning panics into errors.
+pub fn run(solution: SolutionFunction, input: &str) -> Result<(String, String)> {
+    panic::catch_unwind(|| solution(input)).map_err(|payload| {
+        anyhow!(
+            "solution panicked: {}",
+            panic_message::panic_message(&payload)
+        )
+    })?
+}
+
+/// Runs a solution on a separate thread and gives up after `timeout`.
+///
+/// Threads can't be cancelled, so a timed out solution keeps running in the
+/// background until it finishes or the process exits.
+pub fn run_with_timeout(
+    solution: SolutionFunction,
+    input: &str,
+    timeout: Duration,
+) -> Result<(String, String)> {
+    let input: Arc<str> = input.into();
+    let (sender, receiver) = mpsc::channel();
+    thread::Builder::new()
+        .name("solution".into())
+        .stack_size(STACK_SIZE)
+        .spawn(move || {
+            // The receiver is gone if we timed out, which is fine
+            let _ = sender.send(run(solution, &input));
+        })?;
+
+    match receiver.recv_timeout(timeout) {
+        Ok(result) => result,
+        Err(mpsc::RecvTimeoutError::Timeout) => {
+            bail!("solution did not finish within {timeout:?}")
+        }
+        Err(mpsc::RecvTimeoutError::Disconnected) => bail!("solution thread exited unexpectedly"),
+    }
+}
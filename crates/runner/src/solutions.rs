@@ -0,0 +1,177 @@
+use std::{collections::HashMap, fs, time::SystemTime};
+
+use anyhow::{Context, Result, bail};
+use itertools::Itertools;
+use once_cell::sync::OnceCell;
+use register::{GeneratorFunction, RegisteredFunction, RegisteredGenerator, SolutionFunction};
+
+use crate::PuzzleId;
+
+/// All solutions registered with [`register::register`] that are linked into
+/// the current binary.
+///
+/// Solution crates are only linked if they are referenced, so binaries
+/// embedding the runner should use `extern crate` for each of them.
+#[derive(Debug, Clone)]
+pub struct Solutions {
+    pub by_id: HashMap<PuzzleId, PuzzleSolutions>,
+    /// Maps source files (relative to the workspace root) to their puzzle.
+    pub by_file: HashMap<&'static str, PuzzleId>,
+}
+
+impl Solutions {
+    /// Returns the registered solutions, collecting them on first use.
+    pub fn get() -> Result<&'static Self> {
+        static INSTANCE: OnceCell<Solutions> = OnceCell::new();
+        INSTANCE.get_or_try_init(Self::collect)
+    }
+
+    /// Returns all puzzles sorted by id.
+    #[must_use]
+    pub fn sorted(&self) -> Vec<(PuzzleId, &PuzzleSolutions)> {
+        let mut puzzles: Vec<_> = self.by_id.iter().map(|(id, sol)| (*id, sol)).collect();
+        puzzles.sort_unstable_by_key(|(id, _)| *id);
+        puzzles
+    }
+
+    /// Returns the puzzle whose solution file was modified last.
+    ///
+    /// File paths are resolved relative to the current directory, which
+    /// should therefore be the workspace root.
+    pub fn most_recently_edited(&self) -> Result<(PuzzleId, &PuzzleSolutions)> {
+        let (_, id) = self
+            .by_file
+            .iter()
+            .map(|(&file, &id)| {
+                let modified_time = file_modified_time(file).with_context(|| {
+                    format!("failed to determine modification time of {file:?}")
+                })?;
+                anyhow::Ok((modified_time, id))
+            })
+            .reduce(|sol1, sol2| {
+                let (t1, id1) = sol1?;
+                let (t2, id2) = sol2?;
+                if t1 > t2 {
+                    Ok((t1, id1))
+                } else {
+                    Ok((t2, id2))
+                }
+            })
+            .context("no puzzles found")??;
+        Ok((id, &self.by_id[&id]))
+    }
+
+    fn collect() -> Result<Self> {
+        let mut by_id: Vec<_> = RegisteredFunction::all()
+            .iter()
+            .map(|&reg_fn| {
+                let (id, tail_modules) = parse_module_path(reg_fn.module_path)
+                    .context("expected function to be in a module like `aoc<year>::day<day>`")?;
+                anyhow::Ok((id, tail_modules, reg_fn))
+            })
+            .try_collect()?;
+        by_id.sort_unstable_by_key(|(id, _, _)| *id);
+
+        let mut by_file = HashMap::new();
+        let mut by_id: HashMap<_, _> = by_id
+            .chunk_by(|(id1, _, _), (id2, _, _)| id1 == id2)
+            .map(|solutions| PuzzleSolutions::collect(solutions, &mut by_file))
+            .try_collect()?;
+
+        for reg_gen in RegisteredGenerator::all() {
+            let (id, _) = parse_module_path(reg_gen.module_path)
+                .context("expected generator to be in a module like `aoc<year>::day<day>`")?;
+            let solutions = by_id
+                .get_mut(&id)
+                .with_context(|| format!("found generator for {id}, but no solutions"))?;
+            let name = reg_gen.name.replace('_', " ");
+            solutions.generators.push((name, reg_gen.func));
+        }
+
+        Ok(Self { by_id, by_file })
+    }
+}
+
+/// The solutions and input generators registered for a single puzzle.
+#[derive(Debug, Clone)]
+pub struct PuzzleSolutions {
+    /// The function named `run` in the puzzle's module.
+    pub main: SolutionFunction,
+    pub alts: Vec<Alternative>,
+    /// Input generators with human-readable names.
+    pub generators: Vec<(String, GeneratorFunction)>,
+    /// Source file of the main solution.
+    pub file: &'static str,
+}
+
+/// An alternative solution, which should produce the same results as the
+/// main one.
+#[derive(Debug, Clone)]
+pub struct Alternative {
+    /// Human-readable name, derived from the function and module names.
+    pub name: String,
+    /// Path of the function relative to the puzzle's module.
+    pub path: String,
+    pub func: SolutionFunction,
+}
+
+impl PuzzleSolutions {
+    fn collect(
+        solutions: &[(PuzzleId, &'static str, RegisteredFunction)],
+        by_file: &mut HashMap<&'static str, PuzzleId>,
+    ) -> Result<(PuzzleId, Self)> {
+        let (id, _, _) = solutions[0];
+        let files = solutions.iter().map(|(_, _, reg_fn)| reg_fn.file);
+        for file in files {
+            if let Some(other_id) = by_file.insert(file, id).filter(|&i| i != id) {
+                bail!("found solutions for both {id} and {other_id} in the same file {file}");
+            }
+        }
+
+        let mut main = None;
+        let alts = solutions
+            .iter()
+            .filter_map(|&(_, tail_modules, reg_fn)| {
+                if tail_modules.is_empty() && reg_fn.name == "run" {
+                    main = Some(reg_fn);
+                    None
+                } else {
+                    let alt_name = reg_fn.name.replace('_', " ");
+                    let (name, path) = if tail_modules.is_empty() {
+                        (alt_name, reg_fn.name.to_string())
+                    } else {
+                        let module = tail_modules.replace("::", "/").replace('_', " ");
+                        let name = format!("{module} {alt_name}");
+                        (name, format!("{tail_modules}::{}", reg_fn.name))
+                    };
+                    Some(Alternative {
+                        name,
+                        path,
+                        func: reg_fn.func,
+                    })
+                }
+            })
+            .collect();
+
+        let main = main.with_context(|| format!("no main solution found for {id}"))?;
+        let solutions = Self {
+            main: main.func,
+            alts,
+            generators: Vec::new(),
+            file: main.file,
+        };
+        Ok((id, solutions))
+    }
+}
+
+fn parse_module_path(module_path: &str) -> Option<(PuzzleId, &str)> {
+    let (krate, module) = module_path.split_once("::")?;
+    let (top_level_module, tail_modules) = module.split_once("::").unwrap_or((module, ""));
+    let year = krate.strip_prefix("aoc")?.parse().ok()?;
+    let day = top_level_module.strip_prefix("day")?.parse().ok()?;
+    Some((PuzzleId { year, day }, tail_modules))
+}
+
+fn file_modified_time(path: &str) -> Result<SystemTime> {
+    Ok(fs::metadata(path)?.modified()?)
+}
@@ -0,0 +1,86 @@
+//! Compares the unchecked integer extraction hot loops against the checked
+//! and sign-aware variants on a puzzle-like input.
+
+use std::{fmt::Write, hint::black_box, sync::LazyLock};
+
+use divan::Bencher;
+use utils::input::{Input, Sign};
+
+fn main() {
+    divan::main();
+}
+
+/// Lines in the style of 2024 day14, `p=x,y v=dx,dy`.
+static INPUT: LazyLock<String> = LazyLock::new(|| {
+    let mut input = String::new();
+    let mut state = 0x2545_f491_u32;
+    let mut next = || {
+        state ^= state << 13;
+        state ^= state >> 17;
+        state ^= state << 5;
+        i64::from(state % 2001) - 1000
+    };
+    for _ in 0..10_000 {
+        let [x, y, dx, dy] = [next().abs(), next().abs(), next(), next()];
+        writeln!(input, "p={x},{y} v={dx},{dy}").unwrap();
+    }
+    input
+});
+
+#[divan::bench]
+fn unsigned(bencher: Bencher) {
+    bencher.bench(|| {
+        black_box(INPUT.as_str())
+            .unsigned_integers::<u64>()
+            .sum::<u64>()
+    });
+}
+
+#[divan::bench]
+fn signed(bencher: Bencher) {
+    bencher.bench(|| {
+        black_box(INPUT.as_str())
+            .signed_integers::<i64>()
+            .sum::<i64>()
+    });
+}
+
+#[divan::bench]
+fn signed_separate(bencher: Bencher) {
+    bencher.bench(|| {
+        black_box(INPUT.as_str())
+            .signed_integers_with::<i64>(Sign::Separate)
+            .sum::<i64>()
+    });
+}
+
+#[divan::bench]
+fn checked_signed(bencher: Bencher) {
+    bencher.bench(|| {
+        black_box(INPUT.as_str())
+            .checked_signed_integers::<i64>(Sign::Minus)
+            .map(Result::unwrap)
+            .sum::<i64>()
+    });
+}
+
+#[divan::bench]
+fn signed_chunks(bencher: Bencher) {
+    bencher.bench(|| {
+        black_box(INPUT.as_str())
+            .signed_integers::<i64>()
+            .chunks::<4>()
+            .map(|[x, y, dx, dy]| x + y + dx + dy)
+            .sum::<i64>()
+    });
+}
+
+#[divan::bench]
+fn lines_n(bencher: Bencher) {
+    bencher.bench(|| {
+        black_box(INPUT.as_str())
+            .lines_integers_n::<i64, 4>()
+            .unwrap()
+            .len()
+    });
+}
@@ -0,0 +1,80 @@
+//! Buffering and padding shared by the Merkle–Damgård hash functions (MD5,
+//! SHA-1 and SHA-256), which only differ in their compression function and
+//! the byte order of the message length.
+
+/// Byte order of the message length in the final block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Endian {
+    Little,
+    Big,
+}
+
+/// Collects input into 64-byte blocks for a compression function.
+#[derive(Debug, Clone)]
+pub(crate) struct BlockBuffer {
+    buffer: [u8; 64],
+    len: usize,
+}
+
+impl BlockBuffer {
+    /// Continues after `len` bytes, a multiple of the block size, were
+    /// already compressed.
+    pub(crate) fn new(len: usize) -> Self {
+        debug_assert!(len.is_multiple_of(64));
+        Self {
+            buffer: [0; 64],
+            len,
+        }
+    }
+
+    pub(crate) fn update(&mut self, mut data: &[u8], mut compress: impl FnMut(&[u8; 64])) {
+        let buffered = self.len % 64;
+        self.len += data.len();
+
+        if buffered > 0 {
+            let n = data.len().min(64 - buffered);
+            self.buffer[buffered..buffered + n].copy_from_slice(&data[..n]);
+            data = &data[n..];
+            if buffered + n < 64 {
+                return;
+            }
+            compress(&self.buffer);
+        }
+
+        let (blocks, remainder) = data.as_chunks::<64>();
+        for block in blocks {
+            compress(block);
+        }
+        self.buffer[..remainder.len()].copy_from_slice(remainder);
+    }
+
+    /// Pads the message and compresses the remaining one or two blocks.
+    pub(crate) fn finalize(mut self, endian: Endian, mut compress: impl FnMut(&[u8; 64])) {
+        let buffered = self.len % 64;
+        self.buffer[buffered] = 0x80;
+        self.buffer[buffered + 1..].fill(0);
+        if buffered >= 56 {
+            compress(&self.buffer);
+            self.buffer.fill(0);
+        }
+        write_len(self.buffer.last_chunk_mut().unwrap(), self.len, endian);
+        compress(&self.buffer);
+    }
+}
+
+/// Creates an already padded block for a message of `len < 56` bytes.
+pub(crate) fn single_block(len: usize, endian: Endian) -> [u8; 64] {
+    assert!(len < 56);
+    let mut buffer = [0; 64];
+    buffer[len] = 0x80;
+    write_len(buffer.last_chunk_mut().unwrap(), len, endian);
+    buffer
+}
+
+fn write_len(bytes: &mut [u8; 8], num_bytes: usize, endian: Endian) {
+    let num_bits = num_bytes as u64 * 8;
+    *bytes = match endian {
+        Endian::Little => num_bits.to_le_bytes(),
+        Endian::Big => num_bits.to_be_bytes(),
+    };
+}
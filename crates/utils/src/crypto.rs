@@ -0,0 +1,5 @@
+//! Hash functions used by puzzles besides MD5, which lives in [`crate::md5`].
+
+pub mod knot_hash;
+pub mod sha1;
+pub mod sha256;
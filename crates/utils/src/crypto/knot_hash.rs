@@ -1,6 +1,11 @@
+//! The Knot Hash from Advent of Code 2017 (days 10 and 14).
+
 use std::{iter, ops::BitXor};
 
-pub(crate) fn hash_lengths(lengths: impl IntoIterator<Item = u8>) -> [u8; 256] {
+/// Runs a single round of knot tying over the list `0..=255` with all
+/// `lengths`, returning the resulting list.
+#[must_use]
+pub fn hash_lengths(lengths: impl IntoIterator<Item = u8>) -> [u8; 256] {
     let mut nums = [0; 256];
     for i in 0..=255 {
         nums[usize::from(i)] = i;
@@ -17,7 +22,10 @@ pub(crate) fn hash_lengths(lengths: impl IntoIterator<Item = u8>) -> [u8; 256] {
     nums
 }
 
-pub(crate) fn hash_str(s: &str) -> u128 {
+/// Computes the full Knot Hash of `s`: 64 rounds including the standard
+/// length suffix, condensed to a dense hash.
+#[must_use]
+pub fn hash_str(s: &str) -> u128 {
     let lengths = s.bytes().chain([17, 31, 73, 47, 23]);
     let lengths = iter::repeat_n(lengths, 64).flatten();
     let bytes = hash_lengths(lengths);
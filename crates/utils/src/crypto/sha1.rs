@@ -0,0 +1,203 @@
+#![allow(clippy::unreadable_literal, clippy::many_single_char_names)]
+use std::{
+    array, fmt,
+    ops::{Deref, DerefMut},
+};
+
+use crate::block_buffer::{self, BlockBuffer, Endian};
+
+/// A SHA-1 digest, with the first word holding the first eight hex digits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Digest([u32; 5]);
+
+impl Digest {
+    #[must_use]
+    pub fn words(self) -> [u32; 5] {
+        self.0
+    }
+
+    #[must_use]
+    pub fn to_bytes(self) -> [u8; 20] {
+        let mut bytes = [0; 20];
+        for (chunk, word) in bytes.chunks_exact_mut(4).zip(self.0) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+        bytes
+    }
+
+    #[must_use]
+    pub fn to_hex(self) -> String {
+        format!("{self:x}")
+    }
+}
+
+impl fmt::LowerHex for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|word| write!(f, "{word:08x}"))
+    }
+}
+
+impl fmt::Display for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(self, f)
+    }
+}
+
+/// Streaming SHA-1 hasher for inputs of any length.
+#[derive(Debug, Clone)]
+pub struct Sha1 {
+    state: State,
+    buffer: BlockBuffer,
+}
+
+impl Sha1 {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            state: INIT,
+            buffer: BlockBuffer::new(0),
+        }
+    }
+
+    /// Hashes `data` in one go.
+    #[must_use]
+    pub fn digest(data: impl AsRef<[u8]>) -> Digest {
+        let mut sha1 = Self::new();
+        sha1.update(data);
+        sha1.finalize()
+    }
+
+    pub fn update(&mut self, data: impl AsRef<[u8]>) {
+        let state = &mut self.state;
+        self.buffer
+            .update(data.as_ref(), |block| *state = hash_block(block, *state));
+    }
+
+    #[must_use]
+    pub fn finalize(mut self) -> Digest {
+        let state = &mut self.state;
+        self.buffer
+            .finalize(Endian::Big, |block| *state = hash_block(block, *state));
+        Digest(self.state)
+    }
+}
+
+impl Default for Sha1 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A message of less than 56 bytes, which fits into a single block
+/// together with the padding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SingleBlock([u8; 64]);
+
+impl SingleBlock {
+    #[must_use]
+    pub fn new(len: usize) -> Self {
+        Self(block_buffer::single_block(len, Endian::Big))
+    }
+
+    #[must_use]
+    pub fn digest(&self) -> Digest {
+        Digest(hash_block(&self.0, INIT))
+    }
+}
+
+impl Deref for SingleBlock {
+    type Target = [u8; 64];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for SingleBlock {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+type State = [u32; 5];
+
+const INIT: State = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+fn hash_block(buffer: &[u8; 64], state: State) -> State {
+    let mut w = [0; 80];
+    for (i, chunk) in buffer.as_chunks::<4>().0.iter().enumerate() {
+        w[i] = u32::from_be_bytes(*chunk);
+    }
+    for i in 16..80 {
+        w[i] = (w[i - 3] ^ w[i - 8] ^ w[i - 14] ^ w[i - 16]).rotate_left(1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e] = state;
+    for (i, &w) in w.iter().enumerate() {
+        let (f, k) = match i {
+            0..20 => ((b & c) | (!b & d), 0x5a827999),
+            20..40 => (b ^ c ^ d, 0x6ed9eba1),
+            40..60 => ((b & c) | (b & d) | (c & d), 0x8f1bbcdc),
+            _ => (b ^ c ^ d, 0xca62c1d6),
+        };
+        let temp = a
+            .rotate_left(5)
+            .wrapping_add(f)
+            .wrapping_add(e)
+            .wrapping_add(k)
+            .wrapping_add(w);
+        (a, b, c, d, e) = (temp, a, b.rotate_left(30), c, d);
+    }
+
+    let result = [a, b, c, d, e];
+    array::from_fn(|i| state[i].wrapping_add(result[i]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VECTORS: [(&str, &str); 4] = [
+        ("", "da39a3ee5e6b4b0d3255bfef95601890afd80709"),
+        ("abc", "a9993e364706816aba3e25717850c26c9cd0d89d"),
+        (
+            "The quick brown fox jumps over the lazy dog",
+            "2fd4e1c67a2d28fced849ee1bb76e7391b93eb12",
+        ),
+        (
+            "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+            "84983e441c3bd26ebaae4aa1f95129e5e54670f1",
+        ),
+    ];
+
+    #[test]
+    fn streaming() {
+        for (input, expected) in VECTORS {
+            assert_eq!(Sha1::digest(input).to_hex(), expected);
+
+            let mut sha1 = Sha1::new();
+            for chunk in input.as_bytes().chunks(5) {
+                sha1.update(chunk);
+            }
+            assert_eq!(sha1.finalize().to_string(), expected);
+        }
+
+        assert_eq!(
+            Sha1::digest("a".repeat(1000)).to_hex(),
+            "291e9a6c66994949b57ba5e650361e98fc36b1ba"
+        );
+    }
+
+    #[test]
+    fn single_block() {
+        for (input, expected) in &VECTORS[..3] {
+            let mut block = SingleBlock::new(input.len());
+            block[..input.len()].copy_from_slice(input.as_bytes());
+            assert_eq!(block.digest().to_hex(), *expected);
+        }
+
+        let digest = Sha1::digest("abc");
+        assert_eq!(digest.words()[0], 0xa9993e36);
+        assert_eq!(digest.to_bytes()[..2], [0xa9, 0x99]);
+    }
+}
@@ -0,0 +1,226 @@
+#![allow(clippy::unreadable_literal, clippy::many_single_char_names)]
+use std::{
+    array, fmt,
+    ops::{Deref, DerefMut},
+};
+
+use crate::block_buffer::{self, BlockBuffer, Endian};
+
+/// A SHA-256 digest, with the first word holding the first eight hex digits.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Digest([u32; 8]);
+
+impl Digest {
+    #[must_use]
+    pub fn words(self) -> [u32; 8] {
+        self.0
+    }
+
+    #[must_use]
+    pub fn to_bytes(self) -> [u8; 32] {
+        let mut bytes = [0; 32];
+        for (chunk, word) in bytes.chunks_exact_mut(4).zip(self.0) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+        bytes
+    }
+
+    #[must_use]
+    pub fn to_hex(self) -> String {
+        format!("{self:x}")
+    }
+}
+
+impl fmt::LowerHex for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.iter().try_for_each(|word| write!(f, "{word:08x}"))
+    }
+}
+
+impl fmt::Display for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(self, f)
+    }
+}
+
+/// Streaming SHA-256 hasher for inputs of any length.
+#[derive(Debug, Clone)]
+pub struct Sha256 {
+    state: State,
+    buffer: BlockBuffer,
+}
+
+impl Sha256 {
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            state: INIT,
+            buffer: BlockBuffer::new(0),
+        }
+    }
+
+    /// Hashes `data` in one go.
+    #[must_use]
+    pub fn digest(data: impl AsRef<[u8]>) -> Digest {
+        let mut sha256 = Self::new();
+        sha256.update(data);
+        sha256.finalize()
+    }
+
+    pub fn update(&mut self, data: impl AsRef<[u8]>) {
+        let state = &mut self.state;
+        self.buffer
+            .update(data.as_ref(), |block| *state = hash_block(block, *state));
+    }
+
+    #[must_use]
+    pub fn finalize(mut self) -> Digest {
+        let state = &mut self.state;
+        self.buffer
+            .finalize(Endian::Big, |block| *state = hash_block(block, *state));
+        Digest(self.state)
+    }
+}
+
+impl Default for Sha256 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A message of less than 56 bytes, which fits into a single block
+/// together with the padding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SingleBlock([u8; 64]);
+
+impl SingleBlock {
+    #[must_use]
+    pub fn new(len: usize) -> Self {
+        Self(block_buffer::single_block(len, Endian::Big))
+    }
+
+    #[must_use]
+    pub fn digest(&self) -> Digest {
+        Digest(hash_block(&self.0, INIT))
+    }
+}
+
+impl Deref for SingleBlock {
+    type Target = [u8; 64];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for SingleBlock {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+type State = [u32; 8];
+
+const INIT: State = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+const K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+fn hash_block(buffer: &[u8; 64], state: State) -> State {
+    let mut w = [0; 64];
+    for (i, chunk) in buffer.as_chunks::<4>().0.iter().enumerate() {
+        w[i] = u32::from_be_bytes(*chunk);
+    }
+    for i in 16..64 {
+        let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+        let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+        w[i] = w[i - 16]
+            .wrapping_add(s0)
+            .wrapping_add(w[i - 7])
+            .wrapping_add(s1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+    for (&w, &k) in w.iter().zip(&K) {
+        let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let ch = (e & f) ^ (!e & g);
+        let temp1 = h
+            .wrapping_add(s1)
+            .wrapping_add(ch)
+            .wrapping_add(k)
+            .wrapping_add(w);
+        let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let temp2 = s0.wrapping_add(maj);
+
+        (h, g, f, e) = (g, f, e, d.wrapping_add(temp1));
+        (d, c, b, a) = (c, b, a, temp1.wrapping_add(temp2));
+    }
+
+    let result = [a, b, c, d, e, f, g, h];
+    array::from_fn(|i| state[i].wrapping_add(result[i]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VECTORS: [(&str, &str); 4] = [
+        (
+            "",
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+        ),
+        (
+            "abc",
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad",
+        ),
+        (
+            "The quick brown fox jumps over the lazy dog",
+            "d7a8fbb307d7809469ca9abcb0082e4f8d5651e46d3cdb762d02d0bf37c9e592",
+        ),
+        (
+            "abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+            "248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+        ),
+    ];
+
+    #[test]
+    fn streaming() {
+        for (input, expected) in VECTORS {
+            assert_eq!(Sha256::digest(input).to_hex(), expected);
+
+            let mut sha256 = Sha256::new();
+            for chunk in input.as_bytes().chunks(5) {
+                sha256.update(chunk);
+            }
+            assert_eq!(sha256.finalize().to_string(), expected);
+        }
+
+        assert_eq!(
+            Sha256::digest("a".repeat(1000)).to_hex(),
+            "41edece42d63e8d9bf515a9ba6932e1c20cbc9f5a5d134645adb5db1b9737ea3"
+        );
+    }
+
+    #[test]
+    fn single_block() {
+        for (input, expected) in &VECTORS[..3] {
+            let mut block = SingleBlock::new(input.len());
+            block[..input.len()].copy_from_slice(input.as_bytes());
+            assert_eq!(block.digest().to_hex(), *expected);
+        }
+
+        let digest = Sha256::digest("abc");
+        assert_eq!(digest.words()[0], 0xba7816bf);
+        assert_eq!(digest.to_bytes()[..2], [0xba, 0x78]);
+    }
+}
@@ -0,0 +1,474 @@
+use std::ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign};
+
+use anyhow::{Error, Result, bail};
+use num::{CheckedAdd, CheckedSub, One, Signed, Zero};
+
+/// A position in the plane, with `y` growing downwards as in puzzle inputs.
+///
+/// Converts from and into the `(y, x)` tuples used to index
+/// [`Grid`](crate::grid::Grid)s.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Point<T> {
+    pub x: T,
+    pub y: T,
+}
+
+/// The difference between two [`Point`]s.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Vector<T> {
+    pub x: T,
+    pub y: T,
+}
+
+impl<T> Point<T> {
+    pub const fn new(x: T, y: T) -> Self {
+        Self { x, y }
+    }
+}
+
+impl<T: Copy + PartialOrd + Add<Output = T> + Sub<Output = T>> Point<T> {
+    pub fn manhattan(self, other: Self) -> T {
+        abs_diff(self.x, other.x) + abs_diff(self.y, other.y)
+    }
+
+    pub fn chebyshev(self, other: Self) -> T {
+        let dx = abs_diff(self.x, other.x);
+        let dy = abs_diff(self.y, other.y);
+        if dx < dy { dy } else { dx }
+    }
+}
+
+impl<T> Vector<T> {
+    pub const fn new(x: T, y: T) -> Self {
+        Self { x, y }
+    }
+}
+
+impl<T: Signed + Copy + PartialOrd> Vector<T> {
+    #[must_use]
+    pub fn manhattan_len(self) -> T {
+        self.x.abs() + self.y.abs()
+    }
+
+    #[must_use]
+    pub fn chebyshev_len(self) -> T {
+        let (x, y) = (self.x.abs(), self.y.abs());
+        if x < y { y } else { x }
+    }
+
+    /// Rotates by 90 degrees counterclockwise (as displayed, with `y`
+    /// growing downwards).
+    #[must_use]
+    pub fn turn_left(self) -> Self {
+        Self::new(self.y, -self.x)
+    }
+
+    /// Rotates by 90 degrees clockwise (as displayed, with `y` growing
+    /// downwards).
+    #[must_use]
+    pub fn turn_right(self) -> Self {
+        Self::new(-self.y, self.x)
+    }
+}
+
+impl<T: Add<Output = T>> Add<Vector<T>> for Point<T> {
+    type Output = Self;
+
+    fn add(self, rhs: Vector<T>) -> Self::Output {
+        Self::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl<T: Sub<Output = T>> Sub<Vector<T>> for Point<T> {
+    type Output = Self;
+
+    fn sub(self, rhs: Vector<T>) -> Self::Output {
+        Self::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Point<T> {
+    type Output = Vector<T>;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Vector::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl<T: AddAssign> AddAssign<Vector<T>> for Point<T> {
+    fn add_assign(&mut self, rhs: Vector<T>) {
+        self.x += rhs.x;
+        self.y += rhs.y;
+    }
+}
+
+impl<T: SubAssign> SubAssign<Vector<T>> for Point<T> {
+    fn sub_assign(&mut self, rhs: Vector<T>) {
+        self.x -= rhs.x;
+        self.y -= rhs.y;
+    }
+}
+
+impl<T: Add<Output = T>> Add for Vector<T> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.x + rhs.x, self.y + rhs.y)
+    }
+}
+
+impl<T: Sub<Output = T>> Sub for Vector<T> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.x - rhs.x, self.y - rhs.y)
+    }
+}
+
+impl<T: AddAssign> AddAssign for Vector<T> {
+    fn add_assign(&mut self, rhs: Self) {
+        self.x += rhs.x;
+        self.y += rhs.y;
+    }
+}
+
+impl<T: SubAssign> SubAssign for Vector<T> {
+    fn sub_assign(&mut self, rhs: Self) {
+        self.x -= rhs.x;
+        self.y -= rhs.y;
+    }
+}
+
+impl<T: Copy + Mul<Output = T>> Mul<T> for Vector<T> {
+    type Output = Self;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        Self::new(self.x * rhs, self.y * rhs)
+    }
+}
+
+impl<T: Neg<Output = T>> Neg for Vector<T> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.x, -self.y)
+    }
+}
+
+impl From<(usize, usize)> for Point<usize> {
+    fn from((y, x): (usize, usize)) -> Self {
+        Self::new(x, y)
+    }
+}
+
+impl From<Point<usize>> for (usize, usize) {
+    fn from(point: Point<usize>) -> Self {
+        (point.y, point.x)
+    }
+}
+
+impl From<Vector<isize>> for (isize, isize) {
+    fn from(vector: Vector<isize>) -> Self {
+        (vector.y, vector.x)
+    }
+}
+
+/// One of the four cardinal directions, in clockwise order starting
+/// upwards.
+///
+/// Can be used as an index into per-direction arrays via `as usize`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum Direction {
+    Up,
+    Right,
+    Down,
+    Left,
+}
+
+impl Direction {
+    pub const ALL: [Self; 4] = [Self::Up, Self::Right, Self::Down, Self::Left];
+
+    #[must_use]
+    pub fn turn_left(self) -> Self {
+        Self::ALL[(self as usize + 3) % 4]
+    }
+
+    #[must_use]
+    pub fn turn_right(self) -> Self {
+        Self::ALL[(self as usize + 1) % 4]
+    }
+
+    #[must_use]
+    pub fn opposite(self) -> Self {
+        Self::ALL[(self as usize + 2) % 4]
+    }
+
+    #[must_use]
+    pub fn is_vertical(self) -> bool {
+        matches!(self, Self::Up | Self::Down)
+    }
+
+    #[must_use]
+    pub fn vector<T: Zero + One + Neg<Output = T>>(self) -> Vector<T> {
+        let (dx, dy) = self.unit();
+        Vector::new(signed_unit(dx), signed_unit(dy))
+    }
+
+    /// Moves `point` one step in this direction.
+    pub fn apply<T: Copy + One + Add<Output = T> + Sub<Output = T>>(
+        self,
+        point: Point<T>,
+    ) -> Point<T> {
+        let (dx, dy) = self.unit();
+        Point::new(shift(point.x, dx), shift(point.y, dy))
+    }
+
+    /// Moves `point` one step in this direction, returning `None` on
+    /// overflow (e.g. when moving up from `y = 0` with unsigned
+    /// coordinates).
+    pub fn checked_apply<T: Copy + One + CheckedAdd + CheckedSub>(
+        self,
+        point: Point<T>,
+    ) -> Option<Point<T>> {
+        let (dx, dy) = self.unit();
+        Some(Point::new(
+            checked_shift(point.x, dx)?,
+            checked_shift(point.y, dy)?,
+        ))
+    }
+
+    fn unit(self) -> (i8, i8) {
+        match self {
+            Self::Up => (0, -1),
+            Self::Right => (1, 0),
+            Self::Down => (0, 1),
+            Self::Left => (-1, 0),
+        }
+    }
+}
+
+/// Returns the `(dy, dx)` offset for moving on a [`Grid`](crate::grid::Grid).
+impl From<Direction> for (isize, isize) {
+    fn from(direction: Direction) -> Self {
+        direction.vector::<isize>().into()
+    }
+}
+
+/// Accepts arrows (`^>v<`), compass directions (`NESW`) and letters (`UDLR`).
+impl TryFrom<u8> for Direction {
+    type Error = Error;
+
+    fn try_from(byte: u8) -> Result<Self> {
+        Ok(match byte {
+            b'^' | b'N' | b'U' => Self::Up,
+            b'>' | b'E' | b'R' => Self::Right,
+            b'v' | b'S' | b'D' => Self::Down,
+            b'<' | b'W' | b'L' => Self::Left,
+            _ => bail!("invalid direction: {:?}", char::from(byte)),
+        })
+    }
+}
+
+impl TryFrom<char> for Direction {
+    type Error = Error;
+
+    fn try_from(c: char) -> Result<Self> {
+        match u8::try_from(c) {
+            Ok(byte) => byte.try_into(),
+            Err(_) => bail!("invalid direction: {c:?}"),
+        }
+    }
+}
+
+/// One of the four cardinal or four diagonal directions, in clockwise order
+/// starting upwards.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[repr(u8)]
+pub enum Direction8 {
+    Up,
+    UpRight,
+    Right,
+    DownRight,
+    Down,
+    DownLeft,
+    Left,
+    UpLeft,
+}
+
+impl Direction8 {
+    pub const ALL: [Self; 8] = [
+        Self::Up,
+        Self::UpRight,
+        Self::Right,
+        Self::DownRight,
+        Self::Down,
+        Self::DownLeft,
+        Self::Left,
+        Self::UpLeft,
+    ];
+
+    /// Turns by 45 degrees counterclockwise.
+    #[must_use]
+    pub fn turn_left(self) -> Self {
+        Self::ALL[(self as usize + 7) % 8]
+    }
+
+    /// Turns by 45 degrees clockwise.
+    #[must_use]
+    pub fn turn_right(self) -> Self {
+        Self::ALL[(self as usize + 1) % 8]
+    }
+
+    #[must_use]
+    pub fn opposite(self) -> Self {
+        Self::ALL[(self as usize + 4) % 8]
+    }
+
+    #[must_use]
+    pub fn is_diagonal(self) -> bool {
+        self as u8 % 2 == 1
+    }
+
+    #[must_use]
+    pub fn vector<T: Zero + One + Neg<Output = T>>(self) -> Vector<T> {
+        let (dx, dy) = self.unit();
+        Vector::new(signed_unit(dx), signed_unit(dy))
+    }
+
+    /// Moves `point` one step in this direction.
+    pub fn apply<T: Copy + One + Add<Output = T> + Sub<Output = T>>(
+        self,
+        point: Point<T>,
+    ) -> Point<T> {
+        let (dx, dy) = self.unit();
+        Point::new(shift(point.x, dx), shift(point.y, dy))
+    }
+
+    /// Moves `point` one step in this direction, returning `None` on
+    /// overflow.
+    pub fn checked_apply<T: Copy + One + CheckedAdd + CheckedSub>(
+        self,
+        point: Point<T>,
+    ) -> Option<Point<T>> {
+        let (dx, dy) = self.unit();
+        Some(Point::new(
+            checked_shift(point.x, dx)?,
+            checked_shift(point.y, dy)?,
+        ))
+    }
+
+    fn unit(self) -> (i8, i8) {
+        match self {
+            Self::Up => (0, -1),
+            Self::UpRight => (1, -1),
+            Self::Right => (1, 0),
+            Self::DownRight => (1, 1),
+            Self::Down => (0, 1),
+            Self::DownLeft => (-1, 1),
+            Self::Left => (-1, 0),
+            Self::UpLeft => (-1, -1),
+        }
+    }
+}
+
+impl From<Direction> for Direction8 {
+    fn from(direction: Direction) -> Self {
+        Self::ALL[2 * direction as usize]
+    }
+}
+
+/// Returns the `(dy, dx)` offset for moving on a [`Grid`](crate::grid::Grid).
+impl From<Direction8> for (isize, isize) {
+    fn from(direction: Direction8) -> Self {
+        direction.vector::<isize>().into()
+    }
+}
+
+fn abs_diff<T: PartialOrd + Sub<Output = T>>(a: T, b: T) -> T {
+    if a < b { b - a } else { a - b }
+}
+
+fn signed_unit<T: Zero + One + Neg<Output = T>>(d: i8) -> T {
+    match d {
+        -1 => -T::one(),
+        0 => T::zero(),
+        _ => T::one(),
+    }
+}
+
+fn shift<T: Copy + One + Add<Output = T> + Sub<Output = T>>(value: T, d: i8) -> T {
+    match d {
+        -1 => value - T::one(),
+        0 => value,
+        _ => value + T::one(),
+    }
+}
+
+fn checked_shift<T: Copy + One + CheckedAdd + CheckedSub>(value: T, d: i8) -> Option<T> {
+    match d {
+        -1 => value.checked_sub(&T::one()),
+        0 => Some(value),
+        _ => value.checked_add(&T::one()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distances() {
+        let (a, b) = (Point::new(1_u32, 5), Point::new(4, 1));
+        assert_eq!(a.manhattan(b), 7);
+        assert_eq!(a.chebyshev(b), 4);
+
+        let (a, b) = (Point::new(1_i32, 5), Point::new(4, 1));
+        assert_eq!((b - a).manhattan_len(), 7);
+        assert_eq!((b - a).chebyshev_len(), 4);
+    }
+
+    #[test]
+    fn turns() {
+        for dir in Direction::ALL {
+            assert_eq!(dir.turn_left().turn_right(), dir);
+            assert_eq!(dir.turn_right().turn_right(), dir.opposite());
+            assert_eq!(dir.vector::<i32>().turn_right(), dir.turn_right().vector());
+            assert_eq!(-dir.vector::<i32>(), dir.opposite().vector());
+            assert_eq!(
+                Direction8::from(dir).turn_right().turn_right(),
+                dir.turn_right().into()
+            );
+        }
+        assert_eq!(Direction8::UpLeft.opposite(), Direction8::DownRight);
+        assert!(Direction8::DownLeft.is_diagonal());
+    }
+
+    #[test]
+    fn apply() {
+        let origin = Point::new(0_usize, 0);
+        assert_eq!(Direction::Up.checked_apply(origin), None);
+        assert_eq!(Direction::Right.apply(origin), Point::new(1, 0));
+        assert_eq!(Direction8::DownRight.apply(origin), Point::new(1, 1));
+        assert_eq!(
+            <(usize, usize)>::from(Direction::Down.apply(origin)),
+            (1, 0)
+        );
+        assert_eq!(<(isize, isize)>::from(Direction::Left), (0, -1));
+        assert_eq!(
+            Point::new(2, 3) + Direction::Up.vector() * 2,
+            Point::new(2, 1)
+        );
+    }
+
+    #[test]
+    fn parse() {
+        let parsed: Vec<_> = "^>v<NESWURDL"
+            .bytes()
+            .map(|byte| Direction::try_from(byte).unwrap())
+            .collect();
+        assert_eq!(parsed, [Direction::ALL; 3].concat());
+        assert!(Direction::try_from('x').is_err());
+        assert!(Direction::try_from('→').is_err());
+    }
+}
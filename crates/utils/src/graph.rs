@@ -0,0 +1,352 @@
+//! Graph algorithms over vertices `0..n`.
+//!
+//! Graphs are given as adjacency lists, i.e. a slice with the out-neighbors
+//! of every vertex. [`adjacency_list`] builds one from edges between
+//! arbitrary hashable labels using an [`Indexer`]. Small dense graphs are
+//! better served by [`bitset::BitsetGraph`].
+
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    hash::Hash,
+};
+
+use crate::hash::Indexer;
+
+pub mod bitset;
+
+/// Builds a directed adjacency list from labelled edges, assigning vertex
+/// indices in order of first appearance.
+pub fn adjacency_list<T: Eq + Hash>(
+    edges: impl IntoIterator<Item = (T, T)>,
+) -> (Indexer<T>, Vec<Vec<usize>>) {
+    let mut indexer = Indexer::new();
+    let mut adj = Vec::new();
+    for (from, to) in edges {
+        let from = indexer.index_of(from);
+        let to = indexer.index_of(to);
+        if adj.len() < indexer.len() {
+            adj.resize_with(indexer.len(), Vec::new);
+        }
+        adj[from].push(to);
+    }
+    (indexer, adj)
+}
+
+/// Disjoint-set union with union by size and path compression.
+#[derive(Debug, Clone)]
+pub struct DisjointSetUnion {
+    /// Parent of each non-root, or the negated set size for roots.
+    parents: Vec<isize>,
+    num_sets: usize,
+}
+
+impl DisjointSetUnion {
+    /// Creates `size` singleton sets.
+    #[must_use]
+    pub fn new(size: usize) -> Self {
+        Self {
+            parents: vec![-1; size],
+            num_sets: size,
+        }
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.parents.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.parents.is_empty()
+    }
+
+    /// Number of disjoint sets.
+    #[must_use]
+    pub fn num_sets(&self) -> usize {
+        self.num_sets
+    }
+
+    /// Returns the representative of the set containing `i`.
+    pub fn find(&mut self, i: usize) -> usize {
+        let mut root = i;
+        while let Ok(parent) = usize::try_from(self.parents[root]) {
+            root = parent;
+        }
+
+        let mut i = i;
+        while let Ok(parent) = usize::try_from(self.parents[i]) {
+            self.parents[i] = root as isize;
+            i = parent;
+        }
+        root
+    }
+
+    /// Merges the sets containing `i` and `j`, returning whether they were
+    /// disjoint before.
+    pub fn join(&mut self, i: usize, j: usize) -> bool {
+        let i = self.find(i);
+        let j = self.find(j);
+        if i == j {
+            return false;
+        }
+
+        let (large, small) = if self.parents[i] <= self.parents[j] {
+            (i, j)
+        } else {
+            (j, i)
+        };
+        self.parents[large] += self.parents[small];
+        self.parents[small] = large as isize;
+        self.num_sets -= 1;
+        true
+    }
+
+    pub fn same_set(&mut self, i: usize, j: usize) -> bool {
+        self.find(i) == self.find(j)
+    }
+
+    /// Size of the set containing `i`.
+    pub fn set_size(&mut self, i: usize) -> usize {
+        let root = self.find(i);
+        self.parents[root].unsigned_abs()
+    }
+
+    /// Sizes of all sets, in order of their representatives.
+    pub fn root_sizes(&self) -> impl Iterator<Item = usize> {
+        self.parents
+            .iter()
+            .filter(|&&parent| parent < 0)
+            .map(|&parent| parent.unsigned_abs())
+    }
+}
+
+/// Connected components of the graph with all edges taken as undirected.
+///
+/// Components are ordered by their smallest vertex, and the vertices within
+/// each component are sorted.
+pub fn connected_components<A: AsRef<[usize]>>(adj: &[A]) -> Vec<Vec<usize>> {
+    let mut dsu = DisjointSetUnion::new(adj.len());
+    for (v, neighbors) in adj.iter().enumerate() {
+        for &v2 in neighbors.as_ref() {
+            dsu.join(v, v2);
+        }
+    }
+
+    let mut component_of_root = vec![usize::MAX; adj.len()];
+    let mut components: Vec<Vec<usize>> = Vec::with_capacity(dsu.num_sets());
+    for v in 0..adj.len() {
+        let root = dsu.find(v);
+        if component_of_root[root] == usize::MAX {
+            component_of_root[root] = components.len();
+            components.push(Vec::new());
+        }
+        components[component_of_root[root]].push(v);
+    }
+    components
+}
+
+/// Error returned by [`topological_sort`] for graphs with a cycle.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle(pub Vec<usize>);
+
+impl Display for Cycle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "graph contains a cycle: ")?;
+        for v in &self.0 {
+            write!(f, "{v} -> ")?;
+        }
+        write!(f, "{}", self.0[0])
+    }
+}
+
+impl Error for Cycle {}
+
+/// Orders the vertices such that every edge points forwards, or returns
+/// the vertices of some cycle in the order they are traversed.
+pub fn topological_sort<A: AsRef<[usize]>>(adj: &[A]) -> Result<Vec<usize>, Cycle> {
+    #[derive(Clone, Copy, PartialEq, Eq)]
+    enum State {
+        Unvisited,
+        OnStack,
+        Done,
+    }
+
+    let mut state = vec![State::Unvisited; adj.len()];
+    let mut order = Vec::with_capacity(adj.len());
+    // Iterative DFS, storing the index of the next neighbor to visit
+    let mut stack: Vec<(usize, usize)> = Vec::new();
+    for v0 in 0..adj.len() {
+        if state[v0] != State::Unvisited {
+            continue;
+        }
+        state[v0] = State::OnStack;
+        stack.push((v0, 0));
+
+        while let Some((v, next)) = stack.last_mut() {
+            let Some(&v2) = adj[*v].as_ref().get(*next) else {
+                state[*v] = State::Done;
+                order.push(*v);
+                stack.pop();
+                continue;
+            };
+            *next += 1;
+
+            match state[v2] {
+                State::Unvisited => {
+                    state[v2] = State::OnStack;
+                    stack.push((v2, 0));
+                }
+                State::OnStack => {
+                    let start = stack.iter().position(|&(v, _)| v == v2).unwrap();
+                    return Err(Cycle(stack[start..].iter().map(|&(v, _)| v).collect()));
+                }
+                State::Done => {}
+            }
+        }
+    }
+
+    order.reverse();
+    Ok(order)
+}
+
+/// Strongly connected components using Tarjan's algorithm.
+///
+/// Components are returned in reverse topological order, i.e. no edge
+/// leads from a component to a later one.
+pub fn strongly_connected_components<A: AsRef<[usize]>>(adj: &[A]) -> Vec<Vec<usize>> {
+    const UNVISITED: usize = usize::MAX;
+
+    let n = adj.len();
+    let mut index = vec![UNVISITED; n];
+    let mut low_link = vec![0; n];
+    let mut on_stack = vec![false; n];
+    let mut stack = Vec::new();
+    let mut components = Vec::new();
+    let mut next_index = 0;
+
+    // Iterative DFS, storing the index of the next neighbor to visit
+    let mut call_stack: Vec<(usize, usize)> = Vec::new();
+    for v0 in 0..n {
+        if index[v0] != UNVISITED {
+            continue;
+        }
+        call_stack.push((v0, 0));
+        index[v0] = next_index;
+        low_link[v0] = next_index;
+        next_index += 1;
+        stack.push(v0);
+        on_stack[v0] = true;
+
+        while let Some(&mut (v, ref mut next)) = call_stack.last_mut() {
+            if let Some(&v2) = adj[v].as_ref().get(*next) {
+                *next += 1;
+                if index[v2] == UNVISITED {
+                    index[v2] = next_index;
+                    low_link[v2] = next_index;
+                    next_index += 1;
+                    stack.push(v2);
+                    on_stack[v2] = true;
+                    call_stack.push((v2, 0));
+                } else if on_stack[v2] {
+                    low_link[v] = low_link[v].min(index[v2]);
+                }
+                continue;
+            }
+
+            call_stack.pop();
+            if let Some(&(parent, _)) = call_stack.last() {
+                low_link[parent] = low_link[parent].min(low_link[v]);
+            }
+            if low_link[v] == index[v] {
+                let start = stack.iter().rposition(|&v2| v2 == v).unwrap();
+                let component = stack.split_off(start);
+                for &v2 in &component {
+                    on_stack[v2] = false;
+                }
+                components.push(component);
+            }
+        }
+    }
+
+    components
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dsu() {
+        let mut dsu = DisjointSetUnion::new(6);
+        assert!(dsu.join(0, 1));
+        assert!(dsu.join(2, 3));
+        assert!(dsu.join(1, 3));
+        assert!(!dsu.join(0, 2));
+        assert!(dsu.same_set(0, 3));
+        assert!(!dsu.same_set(0, 4));
+        assert_eq!(dsu.set_size(2), 4);
+        assert_eq!(dsu.num_sets(), 3);
+
+        let mut sizes: Vec<_> = dsu.root_sizes().collect();
+        sizes.sort_unstable();
+        assert_eq!(sizes, [1, 1, 4]);
+    }
+
+    #[test]
+    fn components() {
+        let adj = [vec![2], vec![1], vec![3, 4], vec![], vec![], vec![4]];
+        assert_eq!(connected_components(&adj), [vec![0, 2, 3, 4, 5], vec![1]]);
+    }
+
+    #[test]
+    fn topological() {
+        let (indexer, adj) = adjacency_list([
+            ("shirt", "tie"),
+            ("tie", "jacket"),
+            ("trousers", "shoes"),
+            ("trousers", "belt"),
+            ("shirt", "belt"),
+            ("belt", "jacket"),
+            ("socks", "shoes"),
+        ]);
+        let order = topological_sort(&adj).unwrap();
+        assert_eq!(order.len(), indexer.len());
+        let mut position = vec![0; order.len()];
+        for (i, &v) in order.iter().enumerate() {
+            position[v] = i;
+        }
+        for (v, neighbors) in adj.iter().enumerate() {
+            for &v2 in neighbors {
+                assert!(position[v] < position[v2]);
+            }
+        }
+
+        let adj = [vec![1], vec![2], vec![3, 4], vec![1], vec![]];
+        let cycle = topological_sort(&adj).unwrap_err();
+        assert_eq!(cycle, Cycle(vec![1, 2, 3]));
+        assert_eq!(
+            cycle.to_string(),
+            "graph contains a cycle: 1 -> 2 -> 3 -> 1"
+        );
+    }
+
+    #[test]
+    fn tarjan() {
+        let adj = [
+            vec![1],
+            vec![2],
+            vec![0, 3],
+            vec![4],
+            vec![5, 7],
+            vec![6],
+            vec![4],
+            vec![],
+        ];
+        let mut components = strongly_connected_components(&adj);
+        for component in &mut components {
+            component.sort_unstable();
+        }
+        assert_eq!(components, [vec![7], vec![4, 5, 6], vec![3], vec![0, 1, 2]]);
+    }
+}
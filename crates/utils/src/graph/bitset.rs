@@ -0,0 +1,276 @@
+//! Dense graphs stored as adjacency bitsets, and bitmask DP over vertex
+//! subsets.
+
+use std::ops::Add;
+
+use num::Zero;
+use tinybitset::TinyBitSet;
+
+use crate::num::bits;
+
+/// A set of vertices of a [`BitsetGraph`].
+pub type VertexSet<const W: usize> = TinyBitSet<u64, W>;
+
+/// An undirected graph with up to `64 * W` vertices, storing the neighbors
+/// of each vertex as a bitset.
+#[derive(Debug, Clone)]
+pub struct BitsetGraph<const W: usize> {
+    adj: Vec<VertexSet<W>>,
+}
+
+impl<const W: usize> BitsetGraph<W> {
+    pub const MAX_VERTICES: usize = VertexSet::<W>::CAPACITY;
+
+    /// Creates a graph with `n` vertices and no edges.
+    #[must_use]
+    pub fn new(n: usize) -> Self {
+        assert!(n <= Self::MAX_VERTICES, "too many vertices: {n}");
+        Self {
+            adj: vec![VertexSet::new(); n],
+        }
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.adj.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.adj.is_empty()
+    }
+
+    pub fn add_edge(&mut self, v1: usize, v2: usize) {
+        self.adj[v1].insert(v2);
+        self.adj[v2].insert(v1);
+    }
+
+    #[must_use]
+    pub fn has_edge(&self, v1: usize, v2: usize) -> bool {
+        self.adj[v1][v2]
+    }
+
+    #[must_use]
+    pub fn neighbors(&self, v: usize) -> VertexSet<W> {
+        self.adj[v]
+    }
+
+    /// All vertices with at least one neighbor.
+    #[must_use]
+    pub fn non_isolated(&self) -> VertexSet<W> {
+        (0..self.len())
+            .filter(|&v| !self.adj[v].is_empty())
+            .collect()
+    }
+
+    /// All triangles `[v1, v2, v3]` with `v1 < v2 < v3`.
+    pub fn triangles(&self) -> impl Iterator<Item = [usize; 3]> {
+        (0..self.len()).flat_map(move |v1| {
+            self.adj[v1]
+                .into_iter()
+                .filter(move |&v2| v2 > v1)
+                .flat_map(move |v2| {
+                    (self.adj[v1] & self.adj[v2])
+                        .into_iter()
+                        .filter(move |&v3| v3 > v2)
+                        .map(move |v3| [v1, v2, v3])
+                })
+        })
+    }
+
+    /// A clique of maximum size, found by branch and bound.
+    #[must_use]
+    pub fn max_clique(&self) -> VertexSet<W> {
+        let mut largest = VertexSet::new();
+        self.extend_clique(VertexSet::new(), self.non_isolated(), &mut largest);
+        if largest.is_empty() && !self.is_empty() {
+            largest.insert(0);
+        }
+        largest
+    }
+
+    fn extend_clique(
+        &self,
+        clique: VertexSet<W>,
+        mut candidates: VertexSet<W>,
+        largest: &mut VertexSet<W>,
+    ) {
+        if candidates.is_empty() {
+            if clique.len() > largest.len() {
+                *largest = clique;
+            }
+            return;
+        }
+        if clique.len() + candidates.len() <= largest.len() {
+            return;
+        }
+
+        for v in candidates {
+            candidates.remove(v);
+            self.extend_clique(clique.inserted(v), candidates & self.adj[v], largest);
+        }
+    }
+
+    /// All maximal cliques, using Bron–Kerbosch with pivoting.
+    #[must_use]
+    pub fn maximal_cliques(&self) -> Vec<VertexSet<W>> {
+        let mut cliques = Vec::new();
+        let all = (0..self.len()).collect();
+        self.bron_kerbosch(VertexSet::new(), all, VertexSet::new(), &mut cliques);
+        cliques
+    }
+
+    fn bron_kerbosch(
+        &self,
+        clique: VertexSet<W>,
+        mut candidates: VertexSet<W>,
+        mut excluded: VertexSet<W>,
+        cliques: &mut Vec<VertexSet<W>>,
+    ) {
+        let Some(pivot) = (candidates | excluded)
+            .into_iter()
+            .max_by_key(|&v| (candidates & self.adj[v]).len())
+        else {
+            cliques.push(clique);
+            return;
+        };
+
+        for v in candidates & !self.adj[pivot] {
+            self.bron_kerbosch(
+                clique.inserted(v),
+                candidates & self.adj[v],
+                excluded & self.adj[v],
+                cliques,
+            );
+            candidates.remove(v);
+            excluded.insert(v);
+        }
+    }
+}
+
+/// Best total weight of a path visiting each of the `n` vertices exactly
+/// once, using the Held–Karp bitmask DP in `O(2^n n^2)`.
+///
+/// The path starts at `start` if given and anywhere otherwise. `pick`
+/// selects the better of two weights, e.g. [`Ord::min`] or [`Ord::max`].
+/// Returns `None` if there are no vertices.
+pub fn hamiltonian_path<C: Copy + Add<Output = C> + Zero>(
+    n: usize,
+    start: Option<usize>,
+    weight: impl Fn(usize, usize) -> C,
+    pick: impl Fn(C, C) -> C,
+) -> Option<C> {
+    let table = held_karp(n, start, &weight, &pick);
+    let all = (1 << n) - 1;
+    table[all * n..].iter().flatten().copied().reduce(pick)
+}
+
+/// Best total weight of a cycle visiting each of the `n` vertices exactly
+/// once, i.e. the travelling salesman problem.
+///
+/// See [`hamiltonian_path`] for the parameters.
+pub fn hamiltonian_cycle<C: Copy + Add<Output = C> + Zero>(
+    n: usize,
+    weight: impl Fn(usize, usize) -> C,
+    pick: impl Fn(C, C) -> C,
+) -> Option<C> {
+    if n == 0 {
+        return None;
+    }
+    let table = held_karp(n, Some(0), &weight, &pick);
+    let all = (1 << n) - 1;
+    (0..n)
+        .filter_map(|last| Some(table[all * n + last]? + weight(last, 0)))
+        .reduce(pick)
+}
+
+/// Computes the best weights of paths through each subset of vertices,
+/// indexed by `mask * n + last`.
+fn held_karp<C: Copy + Add<Output = C> + Zero>(
+    n: usize,
+    start: Option<usize>,
+    weight: impl Fn(usize, usize) -> C,
+    pick: impl Fn(C, C) -> C,
+) -> Vec<Option<C>> {
+    assert!(n < usize::BITS as usize, "too many vertices: {n}");
+    let mut table = vec![None; n << n];
+    for v in start.map_or(0..n, |start| start..start + 1) {
+        table[(1 << v) * n + v] = Some(C::zero());
+    }
+
+    for mask in 1_usize..1 << n {
+        for last in bits(mask) {
+            let Some(cost) = table[mask * n + last] else {
+                continue;
+            };
+            for next in bits(!mask & ((1 << n) - 1)) {
+                let entry = &mut table[(mask | 1 << next) * n + next];
+                let cost = cost + weight(last, next);
+                *entry = Some(entry.map_or(cost, |old| pick(old, cost)));
+            }
+        }
+    }
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn graph(n: usize, edges: &[(usize, usize)]) -> BitsetGraph<1> {
+        let mut graph = BitsetGraph::new(n);
+        for &(v1, v2) in edges {
+            graph.add_edge(v1, v2);
+        }
+        graph
+    }
+
+    #[test]
+    fn cliques() {
+        // Two triangles sharing an edge, plus a K4 on 4..8 and a pendant
+        let graph = graph(
+            9,
+            &[
+                (0, 1),
+                (0, 2),
+                (1, 2),
+                (1, 3),
+                (2, 3),
+                (4, 5),
+                (4, 6),
+                (4, 7),
+                (5, 6),
+                (5, 7),
+                (6, 7),
+                (7, 8),
+            ],
+        );
+        assert_eq!(graph.max_clique(), (4..8).collect());
+        assert_eq!(graph.triangles().count(), 2 + 4);
+        assert!(graph.triangles().all(|[v1, v2, v3]| v1 < v2 && v2 < v3));
+
+        let mut cliques: Vec<Vec<usize>> = graph
+            .maximal_cliques()
+            .into_iter()
+            .map(|clique| clique.into_iter().collect())
+            .collect();
+        cliques.sort_unstable();
+        assert_eq!(
+            cliques,
+            [vec![0, 1, 2], vec![1, 2, 3], vec![4, 5, 6, 7], vec![7, 8]]
+        );
+    }
+
+    #[test]
+    fn held_karp() {
+        // Distances between four points on a line at 0, 1, 3 and 7
+        let points = [0_u32, 1, 3, 7];
+        let weight = |i: usize, j: usize| points[i].abs_diff(points[j]);
+        assert_eq!(hamiltonian_path(4, None, weight, u32::min), Some(7));
+        assert_eq!(hamiltonian_path(4, Some(2), weight, u32::min), Some(3 + 7));
+        assert_eq!(hamiltonian_path(4, None, weight, u32::max), Some(6 + 7 + 3));
+        assert_eq!(hamiltonian_cycle(4, weight, u32::min), Some(14));
+        assert_eq!(hamiltonian_path(0, None, weight, u32::min), None);
+        assert_eq!(hamiltonian_cycle(0, weight, u32::min), None);
+    }
+}
@@ -1,5 +1,33 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    marker::PhantomData,
+    ops::{Index, IndexMut},
+};
+
+use anyhow::{Result, ensure};
 use ndarray::{ArrayView2, ShapeBuilder};
 
+use crate::geom::Point;
+
+mod infinite;
+
+pub use infinite::InfiniteGrid;
+
+/// Offsets of the four orthogonal neighbours, clockwise starting upwards.
+pub const OFFSETS4: [(isize, isize); 4] = [(-1, 0), (0, 1), (1, 0), (0, -1)];
+
+/// Offsets of all eight neighbours, clockwise starting upwards.
+pub const OFFSETS8: [(isize, isize); 8] = [
+    (-1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+    (1, 0),
+    (1, -1),
+    (0, -1),
+    (-1, -1),
+];
+
 pub fn from_lines(lines: &str) -> ArrayView2<'_, u8> {
     let lines = lines.trim_end();
     let width = lines.lines().next().map_or(0, str::len);
@@ -10,3 +38,531 @@ pub fn from_lines(lines: &str) -> ArrayView2<'_, u8> {
     ArrayView2::from_shape((height, width).strides((width + 1, 1)), lines.as_bytes())
         .expect("data size error")
 }
+
+/// A dense 2D grid indexed by `(y, x)` positions.
+///
+/// The grid either owns its cells (the default) or borrows them, see
+/// [`GridView`]. Rows may be separated by padding, which allows viewing
+/// the lines of a puzzle input in place.
+#[derive(Debug, Clone, Copy)]
+pub struct Grid<T, S = Vec<T>> {
+    data: S,
+    height: usize,
+    width: usize,
+    stride: usize,
+    _cells: PhantomData<T>,
+}
+
+/// A grid borrowing its cells, e.g. directly from the puzzle input.
+pub type GridView<'a, T> = Grid<T, &'a [T]>;
+
+impl<T> Grid<T> {
+    pub fn new(height: usize, width: usize, value: T) -> Self
+    where
+        T: Clone,
+    {
+        Self::from_vec(width, vec![value; height * width])
+    }
+
+    pub fn from_fn(height: usize, width: usize, mut f: impl FnMut((usize, usize)) -> T) -> Self {
+        let data = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (y, x)))
+            .map(&mut f)
+            .collect();
+        Self::from_vec(width, data)
+    }
+
+    /// Creates a grid from its cells in row-major order.
+    #[must_use]
+    pub fn from_vec(width: usize, data: Vec<T>) -> Self {
+        let height = data.len().checked_div(width).unwrap_or(0);
+        assert_eq!(
+            height * width,
+            data.len(),
+            "data is not a multiple of width"
+        );
+        Self {
+            data,
+            height,
+            width,
+            stride: width,
+            _cells: PhantomData,
+        }
+    }
+
+    /// Parses one row per line, mapping each byte to a cell.
+    pub fn parse(input: &str, mut f: impl FnMut(u8) -> T) -> Result<Self> {
+        Self::try_parse(input, |byte| Ok(f(byte)))
+    }
+
+    /// Parses one row per line, mapping each byte to a cell or an error.
+    pub fn try_parse(input: &str, mut f: impl FnMut(u8) -> Result<T>) -> Result<Self> {
+        let (_, width) = check_lines(input)?;
+        let data = input
+            .lines()
+            .flat_map(str::bytes)
+            .map(&mut f)
+            .collect::<Result<_>>()?;
+        Ok(Self::from_vec(width, data))
+    }
+
+    #[must_use]
+    pub fn into_vec(self) -> Vec<T> {
+        self.data
+    }
+}
+
+impl<'a> GridView<'a, u8> {
+    /// Views the lines of `input` as a grid of bytes without copying.
+    ///
+    /// All lines must end in the same line break, either `\n` or `\r\n`.
+    pub fn from_lines(input: &'a str) -> Result<Self> {
+        let (height, width) = check_lines(input)?;
+        let input = input.trim_end_matches(['\r', '\n']);
+        let data = input.as_bytes();
+        let line_break: &[u8] = if input.contains("\r\n") {
+            b"\r\n"
+        } else {
+            b"\n"
+        };
+        let stride = width + line_break.len();
+        ensure!(
+            (1..height)
+                .all(|y| data.get(y * stride - line_break.len()..y * stride) == Some(line_break)),
+            "lines end in different line breaks"
+        );
+        Ok(Self {
+            data,
+            height,
+            width,
+            stride,
+            _cells: PhantomData,
+        })
+    }
+}
+
+impl<T, S: AsRef<[T]>> Grid<T, S> {
+    #[must_use]
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    #[must_use]
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns `(height, width)`.
+    #[must_use]
+    pub fn dim(&self) -> (usize, usize) {
+        (self.height, self.width)
+    }
+
+    /// Borrows the cells of the grid.
+    #[must_use]
+    pub fn view(&self) -> GridView<'_, T> {
+        Grid {
+            data: self.data.as_ref(),
+            height: self.height,
+            width: self.width,
+            stride: self.stride,
+            _cells: PhantomData,
+        }
+    }
+
+    #[must_use]
+    pub fn contains(&self, (y, x): (usize, usize)) -> bool {
+        y < self.height && x < self.width
+    }
+
+    #[must_use]
+    pub fn get(&self, pos: (usize, usize)) -> Option<&T> {
+        self.contains(pos)
+            .then(|| &self.data.as_ref()[self.offset_of(pos)])
+    }
+
+    #[must_use]
+    pub fn row(&self, y: usize) -> &[T] {
+        assert!(y < self.height, "row {y} out of bounds");
+        let start = y * self.stride;
+        &self.data.as_ref()[start..start + self.width]
+    }
+
+    pub fn rows(&self) -> impl DoubleEndedIterator<Item = &[T]> + ExactSizeIterator {
+        (0..self.height).map(|y| self.row(y))
+    }
+
+    pub fn column(&self, x: usize) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        assert!(x < self.width, "column {x} out of bounds");
+        (0..self.height).map(move |y| &self[(y, x)])
+    }
+
+    pub fn columns(
+        &self,
+    ) -> impl DoubleEndedIterator<Item: Iterator<Item = &T>> + ExactSizeIterator {
+        (0..self.width).map(|x| self.column(x))
+    }
+
+    /// Iterates over all cells in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.rows().flatten()
+    }
+
+    /// Iterates over all positions in row-major order.
+    pub fn positions(&self) -> impl Iterator<Item = (usize, usize)> + use<T, S> {
+        let width = self.width;
+        (0..self.height).flat_map(move |y| (0..width).map(move |x| (y, x)))
+    }
+
+    pub fn indexed_iter(&self) -> impl Iterator<Item = ((usize, usize), &T)> {
+        self.positions().zip(self.iter())
+    }
+
+    /// Returns the first position (in row-major order) of a cell matching
+    /// `pred`.
+    pub fn position(&self, mut pred: impl FnMut(&T) -> bool) -> Option<(usize, usize)> {
+        self.indexed_iter()
+            .find_map(|(pos, cell)| pred(cell).then_some(pos))
+    }
+
+    /// Returns the first position (in row-major order) of `value`, e.g. a
+    /// start marker.
+    #[must_use]
+    pub fn find(&self, value: &T) -> Option<(usize, usize)>
+    where
+        T: PartialEq,
+    {
+        self.position(|cell| cell == value)
+    }
+
+    /// Moves `pos` by a `(dy, dx)` offset or a [`Direction`], returning
+    /// `None` if it leaves the grid.
+    ///
+    /// [`Direction`]: crate::geom::Direction
+    #[must_use]
+    pub fn offset(
+        &self,
+        pos: (usize, usize),
+        offset: impl Into<(isize, isize)>,
+    ) -> Option<(usize, usize)> {
+        offset_within(self.dim(), pos, offset.into())
+    }
+
+    /// Orthogonal neighbours within the grid, clockwise starting upwards.
+    pub fn neighbors4(
+        &self,
+        pos: (usize, usize),
+    ) -> impl Iterator<Item = (usize, usize)> + use<T, S> {
+        let dim = self.dim();
+        OFFSETS4
+            .into_iter()
+            .filter_map(move |offset| offset_within(dim, pos, offset))
+    }
+
+    /// Orthogonal and diagonal neighbours within the grid, clockwise starting
+    /// upwards.
+    pub fn neighbors8(
+        &self,
+        pos: (usize, usize),
+    ) -> impl Iterator<Item = (usize, usize)> + use<T, S> {
+        let dim = self.dim();
+        OFFSETS8
+            .into_iter()
+            .filter_map(move |offset| offset_within(dim, pos, offset))
+    }
+
+    pub fn map<U>(&self, mut f: impl FnMut(&T) -> U) -> Grid<U> {
+        Grid::from_vec(self.width, self.iter().map(&mut f).collect())
+    }
+
+    #[must_use]
+    pub fn transpose(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        Grid::from_fn(self.width, self.height, |(y, x)| self[(x, y)].clone())
+    }
+
+    /// Rotates the grid by 90 degrees clockwise.
+    #[must_use]
+    pub fn rotate_right(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let height = self.height;
+        Grid::from_fn(self.width, height, |(y, x)| {
+            self[(height - 1 - x, y)].clone()
+        })
+    }
+
+    /// Rotates the grid by 90 degrees counterclockwise.
+    #[must_use]
+    pub fn rotate_left(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let width = self.width;
+        Grid::from_fn(width, self.height, |(y, x)| {
+            self[(x, width - 1 - y)].clone()
+        })
+    }
+
+    /// Mirrors the grid left to right.
+    #[must_use]
+    pub fn flip_horizontal(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let width = self.width;
+        Grid::from_fn(self.height, width, |(y, x)| {
+            self[(y, width - 1 - x)].clone()
+        })
+    }
+
+    /// Mirrors the grid top to bottom.
+    #[must_use]
+    pub fn flip_vertical(&self) -> Grid<T>
+    where
+        T: Clone,
+    {
+        let height = self.height;
+        Grid::from_fn(height, self.width, |(y, x)| {
+            self[(height - 1 - y, x)].clone()
+        })
+    }
+
+    /// Displays the grid with one character per cell.
+    pub fn display_with(&self, f: impl Fn(&T) -> char) -> impl Display {
+        DisplayWith { grid: self, f }
+    }
+
+    fn offset_of(&self, (y, x): (usize, usize)) -> usize {
+        y * self.stride + x
+    }
+}
+
+impl<T, S: AsRef<[T]> + AsMut<[T]>> Grid<T, S> {
+    #[must_use]
+    pub fn get_mut(&mut self, pos: (usize, usize)) -> Option<&mut T> {
+        let offset = self.offset_of(pos);
+        self.contains(pos).then(|| &mut self.data.as_mut()[offset])
+    }
+
+    #[must_use]
+    pub fn row_mut(&mut self, y: usize) -> &mut [T] {
+        assert!(y < self.height, "row {y} out of bounds");
+        let start = y * self.stride;
+        &mut self.data.as_mut()[start..start + self.width]
+    }
+
+    pub fn swap(&mut self, pos1: (usize, usize), pos2: (usize, usize)) {
+        assert!(
+            self.contains(pos1) && self.contains(pos2),
+            "position out of bounds"
+        );
+        let (offset1, offset2) = (self.offset_of(pos1), self.offset_of(pos2));
+        self.data.as_mut().swap(offset1, offset2);
+    }
+
+    pub fn fill(&mut self, value: T)
+    where
+        T: Clone,
+    {
+        for y in 0..self.height {
+            self.row_mut(y).fill(value.clone());
+        }
+    }
+}
+
+impl<T, S: AsRef<[T]>> Index<(usize, usize)> for Grid<T, S> {
+    type Output = T;
+
+    fn index(&self, pos: (usize, usize)) -> &Self::Output {
+        assert!(self.contains(pos), "position {pos:?} out of bounds");
+        &self.data.as_ref()[self.offset_of(pos)]
+    }
+}
+
+impl<T, S: AsRef<[T]> + AsMut<[T]>> IndexMut<(usize, usize)> for Grid<T, S> {
+    fn index_mut(&mut self, pos: (usize, usize)) -> &mut Self::Output {
+        assert!(self.contains(pos), "position {pos:?} out of bounds");
+        let offset = self.offset_of(pos);
+        &mut self.data.as_mut()[offset]
+    }
+}
+
+impl<T, S: AsRef<[T]>> Index<Point<usize>> for Grid<T, S> {
+    type Output = T;
+
+    fn index(&self, point: Point<usize>) -> &Self::Output {
+        &self[<(usize, usize)>::from(point)]
+    }
+}
+
+impl<T, S: AsRef<[T]> + AsMut<[T]>> IndexMut<Point<usize>> for Grid<T, S> {
+    fn index_mut(&mut self, point: Point<usize>) -> &mut Self::Output {
+        &mut self[<(usize, usize)>::from(point)]
+    }
+}
+
+impl<T: PartialEq, S1: AsRef<[T]>, S2: AsRef<[T]>> PartialEq<Grid<T, S2>> for Grid<T, S1> {
+    fn eq(&self, other: &Grid<T, S2>) -> bool {
+        self.dim() == other.dim() && self.rows().eq(other.rows())
+    }
+}
+
+impl<T: Eq, S: AsRef<[T]>> Eq for Grid<T, S> {}
+
+impl<T: Copy + Into<char>, S: AsRef<[T]>> Display for Grid<T, S> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.display_with(|&cell| cell.into()).fmt(f)
+    }
+}
+
+struct DisplayWith<'a, T, S, F> {
+    grid: &'a Grid<T, S>,
+    f: F,
+}
+
+impl<T, S: AsRef<[T]>, F: Fn(&T) -> char> Display for DisplayWith<'_, T, S, F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (y, row) in self.grid.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", (self.f)(cell))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Checks that `input` consists of non-empty lines of equal length and
+/// returns `(height, width)`.
+fn check_lines(input: &str) -> Result<(usize, usize)> {
+    let input = input.trim_end_matches(['\r', '\n']);
+    let mut lines = input.lines();
+    let width = lines.next().map_or(0, str::len);
+    ensure!(width > 0, "empty grid");
+
+    let mut height = 1;
+    for line in lines {
+        height += 1;
+        ensure!(
+            line.len() == width,
+            "line {height} has length {}, expected {width}",
+            line.len()
+        );
+    }
+    Ok((height, width))
+}
+
+fn offset_within(
+    (height, width): (usize, usize),
+    (y, x): (usize, usize),
+    (dy, dx): (isize, isize),
+) -> Option<(usize, usize)> {
+    let pos = (y.checked_add_signed(dy)?, x.checked_add_signed(dx)?);
+    (pos.0 < height && pos.1 < width).then_some(pos)
+}
+
+impl<T> FromIterator<Vec<T>> for Grid<T> {
+    /// Collects rows into a grid, panicking if they differ in length.
+    fn from_iter<I: IntoIterator<Item = Vec<T>>>(rows: I) -> Self {
+        let mut width = None;
+        let data = rows
+            .into_iter()
+            .flat_map(|row| {
+                let expected = *width.get_or_insert(row.len());
+                assert_eq!(row.len(), expected, "rows differ in length");
+                row
+            })
+            .collect();
+        Self::from_vec(width.unwrap_or(0), data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const INPUT: &str = "\
+S.#
+..#
+.#E";
+
+    #[test]
+    fn parse_and_view_agree() {
+        let owned = Grid::parse(INPUT, |byte| byte).unwrap();
+        let view = GridView::from_lines(INPUT).unwrap();
+        assert_eq!(owned, view);
+        assert_eq!(view.dim(), (3, 3));
+        assert_eq!(view.find(&b'E'), Some((2, 2)));
+        assert_eq!(view.position(|&cell| cell == b'#'), Some((0, 2)));
+        assert_eq!(view.to_string(), INPUT);
+
+        let crlf = INPUT.replace('\n', "\r\n") + "\r\n";
+        assert_eq!(GridView::from_lines(&crlf).unwrap(), owned);
+        assert_eq!(Grid::parse(&crlf, |byte| byte).unwrap(), owned);
+    }
+
+    #[test]
+    fn parse_errors() {
+        assert!(GridView::from_lines("").is_err());
+        assert!(GridView::from_lines("ab\nc").is_err());
+        assert!(GridView::from_lines("ab\ncd\r\nef").is_err());
+        assert!(GridView::from_lines("ab\r\ncd\nef").is_err());
+        assert!(GridView::from_lines("a\r\nb\nc\nd\ne").is_err());
+        assert!(Grid::<u8>::try_parse("ab", |_| anyhow::bail!("invalid")).is_err());
+    }
+
+    #[test]
+    fn neighbors() {
+        let grid = Grid::new(3, 4, 0);
+        assert_eq!(
+            grid.neighbors4((0, 0)).collect::<Vec<_>>(),
+            [(0, 1), (1, 0)]
+        );
+        assert_eq!(grid.neighbors4((1, 1)).count(), 4);
+        assert_eq!(
+            grid.neighbors8((2, 3)).collect::<Vec<_>>(),
+            [(1, 3), (2, 2), (1, 2)]
+        );
+        assert_eq!(grid.neighbors8((1, 1)).count(), 8);
+        assert_eq!(grid.offset((1, 1), (-1, 2)), Some((0, 3)));
+        assert_eq!(grid.offset((1, 1), (-2, 0)), None);
+        assert_eq!(grid.offset((1, 1), (0, 3)), None);
+    }
+
+    #[test]
+    fn rows_and_columns() {
+        let grid = Grid::from_fn(2, 3, |(y, x)| 3 * y + x);
+        assert_eq!(grid.rows().collect::<Vec<_>>(), [[0, 1, 2], [3, 4, 5]]);
+        let columns: Vec<Vec<_>> = grid
+            .columns()
+            .map(|column| column.copied().collect())
+            .collect();
+        assert_eq!(columns, [[0, 3], [1, 4], [2, 5]]);
+    }
+
+    #[test]
+    fn transformations() {
+        let grid = Grid::parse("ab\ncd\nef", char::from).unwrap();
+        assert_eq!(grid.transpose().to_string(), "ace\nbdf");
+        assert_eq!(grid.rotate_right().to_string(), "eca\nfdb");
+        assert_eq!(grid.rotate_left().to_string(), "bdf\nace");
+        assert_eq!(grid.flip_horizontal().to_string(), "ba\ndc\nfe");
+        assert_eq!(grid.flip_vertical().to_string(), "ef\ncd\nab");
+        assert_eq!(grid.rotate_right().rotate_left(), grid);
+    }
+
+    #[test]
+    fn mutation() {
+        let mut grid = Grid::new(2, 2, false);
+        grid[(0, 1)] = true;
+        grid.swap((0, 1), (1, 0));
+        let rendered = grid.display_with(|&on| if on { '#' } else { '.' });
+        assert_eq!(rendered.to_string(), "..\n#.");
+    }
+}
@@ -0,0 +1,222 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    mem,
+    ops::{Index, IndexMut},
+};
+
+use crate::{geom::Point, grid::Grid};
+
+/// A grid that is unbounded in all directions, with every cell initially
+/// holding a default value.
+///
+/// Cells are stored densely and the backing store grows automatically (at
+/// least doubling in size) whenever a cell outside of it is accessed
+/// mutably.
+#[derive(Debug, Clone)]
+pub struct InfiniteGrid<T> {
+    cells: Grid<T>,
+    /// Position of the top left cell of `cells`.
+    origin: Point<isize>,
+    default: T,
+}
+
+impl<T: Clone> InfiniteGrid<T> {
+    pub fn new(default: T) -> Self {
+        Self {
+            cells: Grid::from_vec(0, Vec::new()),
+            origin: Point::new(0, 0),
+            default,
+        }
+    }
+
+    /// Places the cells of `grid` with its top left corner at the origin.
+    pub fn from_grid<S: AsRef<[T]>>(grid: &Grid<T, S>, default: T) -> Self {
+        Self {
+            cells: grid.map(T::clone),
+            origin: Point::new(0, 0),
+            default,
+        }
+    }
+
+    #[must_use]
+    pub fn get(&self, pos: Point<isize>) -> &T {
+        self.local(pos)
+            .map_or(&self.default, |local| &self.cells[local])
+    }
+
+    /// Returns a mutable reference to the cell at `pos`, growing the grid if
+    /// necessary.
+    pub fn get_mut(&mut self, pos: Point<isize>) -> &mut T {
+        if self.local(pos).is_none() {
+            self.grow_to(pos);
+        }
+        let local = self
+            .local(pos)
+            .expect("grid should contain position after growing");
+        &mut self.cells[local]
+    }
+
+    fn grow_to(&mut self, pos: Point<isize>) {
+        if self.cells.width() == 0 {
+            self.cells = Grid::new(1, 1, self.default.clone());
+            self.origin = pos;
+            return;
+        }
+
+        let (height, width) = (self.cells.height() as isize, self.cells.width() as isize);
+        let min = self.origin;
+        let max = Point::new(min.x + width, min.y + height);
+        let mut new_min = min;
+        let mut new_max = max;
+        if pos.x < min.x {
+            new_min.x = pos.x.min(min.x - width);
+        } else if pos.x >= max.x {
+            new_max.x = (pos.x + 1).max(max.x + width);
+        }
+        if pos.y < min.y {
+            new_min.y = pos.y.min(min.y - height);
+        } else if pos.y >= max.y {
+            new_max.y = (pos.y + 1).max(max.y + height);
+        }
+
+        let new_width = (new_max.x - new_min.x) as usize;
+        let new_height = (new_max.y - new_min.y) as usize;
+        let mut cells = Grid::new(new_height, new_width, self.default.clone());
+        let (dy, dx) = ((min.y - new_min.y) as usize, (min.x - new_min.x) as usize);
+        let old = mem::replace(&mut self.cells, Grid::from_vec(0, Vec::new()));
+        for (i, cell) in old.into_vec().into_iter().enumerate() {
+            let (y, x) = (i / width as usize, i % width as usize);
+            cells[(y + dy, x + dx)] = cell;
+        }
+        self.cells = cells;
+        self.origin = new_min;
+    }
+}
+
+impl<T> InfiniteGrid<T> {
+    /// Iterates over all cells in the backing store, which includes all
+    /// cells that were ever accessed mutably.
+    pub fn iter(&self) -> impl Iterator<Item = (Point<isize>, &T)> {
+        let origin = self.origin;
+        self.cells.indexed_iter().map(move |((y, x), cell)| {
+            (
+                Point::new(origin.x + x as isize, origin.y + y as isize),
+                cell,
+            )
+        })
+    }
+
+    /// Returns the inclusive `(min, max)` corners of the smallest rectangle
+    /// containing all non-default cells, or `None` if there are none.
+    pub fn bounding_box(&self) -> Option<(Point<isize>, Point<isize>)>
+    where
+        T: PartialEq,
+    {
+        self.iter()
+            .filter(|&(_, cell)| *cell != self.default)
+            .map(|(pos, _)| (pos, pos))
+            .reduce(|(min, max), (pos, _)| {
+                (
+                    Point::new(min.x.min(pos.x), min.y.min(pos.y)),
+                    Point::new(max.x.max(pos.x), max.y.max(pos.y)),
+                )
+            })
+    }
+
+    /// Displays the bounding box of the grid with one character per cell.
+    pub fn display_with(&self, f: impl Fn(&T) -> char) -> impl Display
+    where
+        T: PartialEq,
+    {
+        DisplayWith { grid: self, f }
+    }
+
+    fn local(&self, pos: Point<isize>) -> Option<(usize, usize)> {
+        let y = usize::try_from(pos.y - self.origin.y).ok()?;
+        let x = usize::try_from(pos.x - self.origin.x).ok()?;
+        self.cells.contains((y, x)).then_some((y, x))
+    }
+}
+
+impl<T: Clone> Index<Point<isize>> for InfiniteGrid<T> {
+    type Output = T;
+
+    fn index(&self, pos: Point<isize>) -> &Self::Output {
+        self.get(pos)
+    }
+}
+
+impl<T: Clone> IndexMut<Point<isize>> for InfiniteGrid<T> {
+    fn index_mut(&mut self, pos: Point<isize>) -> &mut Self::Output {
+        self.get_mut(pos)
+    }
+}
+
+impl<T: Copy + PartialEq + Into<char>> Display for InfiniteGrid<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.display_with(|&cell| cell.into()).fmt(f)
+    }
+}
+
+struct DisplayWith<'a, T, F> {
+    grid: &'a InfiniteGrid<T>,
+    f: F,
+}
+
+impl<T: PartialEq, F: Fn(&T) -> char> Display for DisplayWith<'_, T, F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let Some((min, max)) = self.grid.bounding_box() else {
+            return Ok(());
+        };
+
+        for y in min.y..=max.y {
+            if y > min.y {
+                writeln!(f)?;
+            }
+            for x in min.x..=max.x {
+                let pos = Point::new(x, y);
+                let cell = self
+                    .grid
+                    .local(pos)
+                    .map_or(&self.grid.default, |local| &self.grid.cells[local]);
+                write!(f, "{}", (self.f)(cell))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grows_in_all_directions() {
+        let mut grid = InfiniteGrid::new(false);
+        assert_eq!(grid.bounding_box(), None);
+        assert_eq!(grid.display_with(|_| '#').to_string(), "");
+
+        let points = [(0, 0), (3, -2), (-5, 1), (2, 7), (-1, -9)];
+        for (x, y) in points {
+            grid[Point::new(x, y)] = true;
+        }
+        for (x, y) in points {
+            assert!(grid[Point::new(x, y)], "lost ({x}, {y})");
+        }
+        assert!(!grid[Point::new(100, -100)]);
+        assert_eq!(grid.iter().filter(|&(_, &cell)| cell).count(), points.len());
+        assert_eq!(
+            grid.bounding_box(),
+            Some((Point::new(-5, -9), Point::new(3, 7)))
+        );
+    }
+
+    #[test]
+    fn render() {
+        let grid = Grid::parse("#.\n.#", |byte| byte).unwrap();
+        let mut grid = InfiniteGrid::from_grid(&grid, b'.');
+        grid[Point::new(-1, 2)] = b'#';
+        grid[Point::new(1, 1)] = b'.';
+        assert_eq!(grid.to_string(), ".#\n..\n#.");
+    }
+}
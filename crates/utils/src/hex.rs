@@ -0,0 +1,375 @@
+//! Hexagonal grids in axial coordinates.
+//!
+//! Based on: <https://www.redblobgames.com/grids/hexagons/>
+
+use std::{
+    iter,
+    ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
+};
+
+use anyhow::{Context, Result};
+
+use crate::geom::Point;
+
+/// Axial coordinates of a hexagon, with the implicit third cube coordinate
+/// `s = -q - r`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Hex {
+    pub q: i32,
+    pub r: i32,
+}
+
+/// Axial direction vectors, in the order used for walking rings.
+const AXIAL_DIRECTIONS: [Hex; 6] = [
+    Hex::new(1, 0),
+    Hex::new(1, -1),
+    Hex::new(0, -1),
+    Hex::new(-1, 0),
+    Hex::new(-1, 1),
+    Hex::new(0, 1),
+];
+
+impl Hex {
+    pub const ORIGIN: Self = Self::new(0, 0);
+
+    #[must_use]
+    pub const fn new(q: i32, r: i32) -> Self {
+        Self { q, r }
+    }
+
+    #[must_use]
+    pub fn from_cube(q: i32, r: i32, s: i32) -> Self {
+        debug_assert_eq!(q + r + s, 0, "cube coordinates must sum to zero");
+        Self::new(q, r)
+    }
+
+    #[must_use]
+    pub fn s(self) -> i32 {
+        -self.q - self.r
+    }
+
+    /// Returns the `(q, r, s)` cube coordinates.
+    #[must_use]
+    pub fn cube(self) -> (i32, i32, i32) {
+        (self.q, self.r, self.s())
+    }
+
+    #[must_use]
+    pub fn neighbor(self, direction: impl HexDirection) -> Self {
+        self + direction.vector()
+    }
+
+    #[must_use]
+    pub fn distance_to_origin(self) -> u32 {
+        (self.q.unsigned_abs() + self.r.unsigned_abs() + self.s().unsigned_abs()) / 2
+    }
+
+    #[must_use]
+    pub fn distance(self, other: Self) -> u32 {
+        (self - other).distance_to_origin()
+    }
+
+    /// All hexagons at exactly `radius` steps from `self`.
+    pub fn ring(self, radius: u32) -> impl Iterator<Item = Self> {
+        let radius = radius as i32;
+        let start = self + AXIAL_DIRECTIONS[4] * radius;
+        let steps = if radius == 0 { 1 } else { 6 * radius };
+        (0..steps).scan(start, move |hex, i| {
+            let current = *hex;
+            *hex += AXIAL_DIRECTIONS[(i / radius.max(1)) as usize % 6];
+            Some(current)
+        })
+    }
+
+    /// All hexagons at most `radius` steps from `self`.
+    pub fn range(self, radius: u32) -> impl Iterator<Item = Self> {
+        let n = radius as i32;
+        (-n..=n).flat_map(move |q| {
+            let rs = (-n).max(-q - n)..=n.min(-q + n);
+            rs.map(move |r| self + Self::new(q, r))
+        })
+    }
+
+    /// Converts to "odd-q" offset coordinates (shoving odd columns down),
+    /// for rendering flat-top layouts.
+    #[must_use]
+    pub fn to_odd_q(self) -> Point<i32> {
+        Point::new(self.q, self.r + (self.q - (self.q & 1)) / 2)
+    }
+
+    #[must_use]
+    pub fn from_odd_q(point: Point<i32>) -> Self {
+        Self::new(point.x, point.y - (point.x - (point.x & 1)) / 2)
+    }
+
+    /// Converts to "odd-r" offset coordinates (shoving odd rows right), for
+    /// rendering pointy-top layouts.
+    #[must_use]
+    pub fn to_odd_r(self) -> Point<i32> {
+        Point::new(self.q + (self.r - (self.r & 1)) / 2, self.r)
+    }
+
+    #[must_use]
+    pub fn from_odd_r(point: Point<i32>) -> Self {
+        Self::new(point.x - (point.y - (point.y & 1)) / 2, point.y)
+    }
+}
+
+impl Add for Hex {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Self::new(self.q + rhs.q, self.r + rhs.r)
+    }
+}
+
+impl Sub for Hex {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self::new(self.q - rhs.q, self.r - rhs.r)
+    }
+}
+
+impl AddAssign for Hex {
+    fn add_assign(&mut self, rhs: Self) {
+        *self = *self + rhs;
+    }
+}
+
+impl SubAssign for Hex {
+    fn sub_assign(&mut self, rhs: Self) {
+        *self = *self - rhs;
+    }
+}
+
+impl Mul<i32> for Hex {
+    type Output = Self;
+
+    fn mul(self, rhs: i32) -> Self::Output {
+        Self::new(self.q * rhs, self.r * rhs)
+    }
+}
+
+impl Neg for Hex {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.q, -self.r)
+    }
+}
+
+/// The six directions of a hex layout, in clockwise order.
+pub trait HexDirection: Copy + Eq + 'static {
+    const ALL: [Self; 6];
+
+    /// Lowercase compass name, e.g. `"ne"`.
+    fn name(self) -> &'static str;
+
+    fn vector(self) -> Hex;
+
+    #[must_use]
+    fn turn_left(self) -> Self {
+        Self::ALL[(self.index() + 5) % 6]
+    }
+
+    #[must_use]
+    fn turn_right(self) -> Self {
+        Self::ALL[(self.index() + 1) % 6]
+    }
+
+    #[must_use]
+    fn opposite(self) -> Self {
+        Self::ALL[(self.index() + 3) % 6]
+    }
+
+    #[doc(hidden)]
+    fn index(self) -> usize {
+        Self::ALL
+            .iter()
+            .position(|&dir| dir == self)
+            .expect("ALL should contain every direction")
+    }
+}
+
+/// Directions of a flat-top layout, where columns are vertical.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FlatDirection {
+    N,
+    NE,
+    SE,
+    S,
+    SW,
+    NW,
+}
+
+impl HexDirection for FlatDirection {
+    const ALL: [Self; 6] = [Self::N, Self::NE, Self::SE, Self::S, Self::SW, Self::NW];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::N => "n",
+            Self::NE => "ne",
+            Self::SE => "se",
+            Self::S => "s",
+            Self::SW => "sw",
+            Self::NW => "nw",
+        }
+    }
+
+    fn vector(self) -> Hex {
+        match self {
+            Self::N => Hex::new(0, -1),
+            Self::NE => Hex::new(1, -1),
+            Self::SE => Hex::new(1, 0),
+            Self::S => Hex::new(0, 1),
+            Self::SW => Hex::new(-1, 1),
+            Self::NW => Hex::new(-1, 0),
+        }
+    }
+}
+
+/// Directions of a pointy-top layout, where rows are horizontal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum PointyDirection {
+    NE,
+    E,
+    SE,
+    SW,
+    W,
+    NW,
+}
+
+impl HexDirection for PointyDirection {
+    const ALL: [Self; 6] = [Self::NE, Self::E, Self::SE, Self::SW, Self::W, Self::NW];
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::NE => "ne",
+            Self::E => "e",
+            Self::SE => "se",
+            Self::SW => "sw",
+            Self::W => "w",
+            Self::NW => "nw",
+        }
+    }
+
+    fn vector(self) -> Hex {
+        match self {
+            Self::NE => Hex::new(1, -1),
+            Self::E => Hex::new(1, 0),
+            Self::SE => Hex::new(0, 1),
+            Self::SW => Hex::new(-1, 1),
+            Self::W => Hex::new(-1, 0),
+            Self::NW => Hex::new(0, -1),
+        }
+    }
+}
+
+/// Parses a sequence of steps like `n,ne,se` or `esenee`.
+///
+/// Steps may be separated by commas or whitespace, or not at all. Names are
+/// case-insensitive and matched greedily, so `ne` is never read as `n`
+/// followed by `e`.
+pub fn steps<D: HexDirection>(s: &str) -> impl Iterator<Item = Result<D>> {
+    let mut rest = s;
+    iter::from_fn(move || {
+        rest = rest.trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+        if rest.is_empty() {
+            return None;
+        }
+
+        let step = D::ALL
+            .into_iter()
+            .filter(|dir| {
+                rest.get(..dir.name().len())
+                    .is_some_and(|prefix| prefix.eq_ignore_ascii_case(dir.name()))
+            })
+            .max_by_key(|dir| dir.name().len());
+        let result = step
+            .inspect(|dir| rest = &rest[dir.name().len()..])
+            .with_context(|| {
+                let invalid = rest.split([',', ' ']).next().unwrap_or(rest);
+                format!("invalid step: {invalid:?}")
+            });
+        if result.is_err() {
+            rest = "";
+        }
+        Some(result)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_steps() {
+        let flat: Vec<FlatDirection> = steps("n,ne, se,s,SW,nw").collect::<Result<_>>().unwrap();
+        assert_eq!(flat, FlatDirection::ALL);
+
+        let pointy: Vec<PointyDirection> = steps("esenee").collect::<Result<_>>().unwrap();
+        assert_eq!(
+            pointy,
+            [
+                PointyDirection::E,
+                PointyDirection::SE,
+                PointyDirection::NE,
+                PointyDirection::E
+            ]
+        );
+
+        assert!(steps::<FlatDirection>("n,x,s").any(|step| step.is_err()));
+    }
+
+    #[test]
+    fn directions() {
+        for dir in FlatDirection::ALL {
+            assert_eq!(dir.opposite().vector(), -dir.vector());
+            assert_eq!(dir.turn_left().turn_right(), dir);
+            assert_eq!(dir.vector().distance_to_origin(), 1);
+        }
+        for dir in PointyDirection::ALL {
+            assert_eq!(dir.opposite().vector(), -dir.vector());
+            assert_eq!(dir.vector().distance_to_origin(), 1);
+        }
+    }
+
+    #[test]
+    fn distances() {
+        let hex = Hex::from_cube(3, -1, -2);
+        assert_eq!(hex.distance_to_origin(), 3);
+        assert_eq!(hex.distance(Hex::new(1, 1)), 2);
+    }
+
+    #[test]
+    fn rings_and_ranges() {
+        let center = Hex::new(2, -3);
+        assert_eq!(center.ring(0).collect::<Vec<_>>(), [center]);
+        for radius in 1..5 {
+            let ring: Vec<_> = center.ring(radius).collect();
+            assert_eq!(ring.len(), 6 * radius as usize);
+            assert!(ring.iter().all(|hex| hex.distance(center) == radius));
+            let mut unique = ring.clone();
+            unique.sort_unstable();
+            unique.dedup();
+            assert_eq!(unique.len(), ring.len());
+        }
+
+        let range: Vec<_> = center.range(3).collect();
+        assert_eq!(range.len(), 1 + 6 + 12 + 18);
+        assert!(range.iter().all(|hex| hex.distance(center) <= 3));
+    }
+
+    #[test]
+    fn offset_coordinates() {
+        for hex in Hex::ORIGIN.range(4) {
+            assert_eq!(Hex::from_odd_q(hex.to_odd_q()), hex);
+            assert_eq!(Hex::from_odd_r(hex.to_odd_r()), hex);
+        }
+        assert_eq!(Hex::new(1, 0).to_odd_q(), Point::new(1, 0));
+        assert_eq!(Hex::new(1, -1).to_odd_q(), Point::new(1, -1));
+        assert_eq!(Hex::new(0, 1).to_odd_r(), Point::new(0, 1));
+        assert_eq!(Hex::new(-1, 1).to_odd_r(), Point::new(-1, 1));
+    }
+}
@@ -0,0 +1,609 @@
+//! Small integer linear programs, solved by branch and bound over a simplex
+//! relaxation.
+//!
+//! ```
+//! # use utils::ilp::Model;
+//! // Fewest coins of values 1, 5 and 12 summing to 20
+//! let mut model = Model::new();
+//! let coins = [1, 5, 12].map(|_| model.int_var(0..));
+//! model.add_eq(coins[0] + 5 * coins[1] + 12 * coins[2], 20);
+//! let solution = model.minimize(coins[0] + coins[1] + coins[2]).unwrap();
+//! assert_eq!(solution.objective(), 4);
+//! assert_eq!(solution[coins[1]], 4);
+//! ```
+
+use std::{
+    iter::Sum,
+    ops::{Add, Bound, Index, Mul, Neg, RangeBounds, Sub},
+};
+
+const EPS: f64 = 1e-9;
+
+/// An integer variable of a [`Model`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Var(usize);
+
+/// A linear expression with integer coefficients.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinExpr {
+    terms: Vec<(usize, i64)>,
+    constant: i64,
+}
+
+impl LinExpr {
+    fn eval(&self, values: &[i64]) -> i64 {
+        self.terms
+            .iter()
+            .map(|&(var, coeff)| coeff * values[var])
+            .sum::<i64>()
+            + self.constant
+    }
+
+    /// Coefficients for each of the `n` variables.
+    fn dense(&self, n: usize) -> Vec<i64> {
+        let mut coeffs = vec![0; n];
+        for &(var, coeff) in &self.terms {
+            coeffs[var] += coeff;
+        }
+        coeffs
+    }
+}
+
+impl From<Var> for LinExpr {
+    fn from(var: Var) -> Self {
+        Self {
+            terms: vec![(var.0, 1)],
+            constant: 0,
+        }
+    }
+}
+
+impl From<i64> for LinExpr {
+    fn from(constant: i64) -> Self {
+        Self {
+            terms: Vec::new(),
+            constant,
+        }
+    }
+}
+
+impl<T: Into<LinExpr>> Add<T> for LinExpr {
+    type Output = Self;
+
+    fn add(mut self, rhs: T) -> Self::Output {
+        let rhs = rhs.into();
+        self.terms.extend(rhs.terms);
+        self.constant += rhs.constant;
+        self
+    }
+}
+
+impl<T: Into<LinExpr>> Sub<T> for LinExpr {
+    type Output = Self;
+
+    fn sub(self, rhs: T) -> Self::Output {
+        self + -rhs.into()
+    }
+}
+
+impl Mul<i64> for LinExpr {
+    type Output = Self;
+
+    fn mul(mut self, rhs: i64) -> Self::Output {
+        for (_, coeff) in &mut self.terms {
+            *coeff *= rhs;
+        }
+        self.constant *= rhs;
+        self
+    }
+}
+
+impl Neg for LinExpr {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        self * -1
+    }
+}
+
+impl<T: Into<LinExpr>> Sum<T> for LinExpr {
+    fn sum<I: Iterator<Item = T>>(iter: I) -> Self {
+        iter.fold(Self::default(), Add::add)
+    }
+}
+
+impl<T: Into<LinExpr>> Add<T> for Var {
+    type Output = LinExpr;
+
+    fn add(self, rhs: T) -> Self::Output {
+        LinExpr::from(self) + rhs
+    }
+}
+
+impl<T: Into<LinExpr>> Sub<T> for Var {
+    type Output = LinExpr;
+
+    fn sub(self, rhs: T) -> Self::Output {
+        LinExpr::from(self) - rhs
+    }
+}
+
+impl Mul<i64> for Var {
+    type Output = LinExpr;
+
+    fn mul(self, rhs: i64) -> Self::Output {
+        LinExpr::from(self) * rhs
+    }
+}
+
+impl Mul<Var> for i64 {
+    type Output = LinExpr;
+
+    fn mul(self, rhs: Var) -> Self::Output {
+        LinExpr::from(rhs) * self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Cmp {
+    Le,
+    Ge,
+    Eq,
+}
+
+#[derive(Debug, Clone)]
+struct Constraint {
+    coeffs: Vec<i64>,
+    cmp: Cmp,
+    rhs: i64,
+}
+
+/// An integer linear program: integer variables with bounds, linear
+/// constraints and a linear objective.
+#[derive(Debug, Clone, Default)]
+pub struct Model {
+    lower: Vec<i64>,
+    upper: Vec<Option<i64>>,
+    constraints: Vec<(LinExpr, Cmp)>,
+}
+
+impl Model {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds an integer variable, which must have a lower bound.
+    pub fn int_var(&mut self, bounds: impl RangeBounds<i64>) -> Var {
+        let lower = match bounds.start_bound() {
+            Bound::Included(&lower) => lower,
+            Bound::Excluded(&lower) => lower + 1,
+            Bound::Unbounded => panic!("variables must have a lower bound"),
+        };
+        let upper = match bounds.end_bound() {
+            Bound::Included(&upper) => Some(upper),
+            Bound::Excluded(&upper) => Some(upper - 1),
+            Bound::Unbounded => None,
+        };
+        self.lower.push(lower);
+        self.upper.push(upper);
+        Var(self.lower.len() - 1)
+    }
+
+    /// Adds a variable that is either zero or one.
+    pub fn bool_var(&mut self) -> Var {
+        self.int_var(0..=1)
+    }
+
+    /// Requires `lhs <= rhs`.
+    pub fn add_le(&mut self, lhs: impl Into<LinExpr>, rhs: impl Into<LinExpr>) {
+        self.constraints.push((lhs.into() - rhs, Cmp::Le));
+    }
+
+    /// Requires `lhs >= rhs`.
+    pub fn add_ge(&mut self, lhs: impl Into<LinExpr>, rhs: impl Into<LinExpr>) {
+        self.constraints.push((lhs.into() - rhs, Cmp::Ge));
+    }
+
+    /// Requires `lhs == rhs`.
+    pub fn add_eq(&mut self, lhs: impl Into<LinExpr>, rhs: impl Into<LinExpr>) {
+        self.constraints.push((lhs.into() - rhs, Cmp::Eq));
+    }
+
+    /// Finds a solution minimising `objective`, or `None` if the problem is
+    /// infeasible.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the objective is unbounded.
+    pub fn minimize(&self, objective: impl Into<LinExpr>) -> Option<Solution> {
+        let objective = objective.into();
+        let mut solution = self.maximize(-objective)?;
+        solution.objective = -solution.objective;
+        Some(solution)
+    }
+
+    /// Finds a solution maximising `objective`, or `None` if the problem is
+    /// infeasible.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the objective is unbounded.
+    pub fn maximize(&self, objective: impl Into<LinExpr>) -> Option<Solution> {
+        let objective = objective.into();
+        let n = self.lower.len();
+        let constraints: Vec<_> = self
+            .constraints
+            .iter()
+            .map(|(expr, cmp)| Constraint {
+                coeffs: expr.dense(n),
+                cmp: *cmp,
+                rhs: -expr.constant,
+            })
+            .collect();
+
+        let mut search = BranchAndBound {
+            constraints: &constraints,
+            objective: objective.dense(n),
+            best: None,
+        };
+        search.branch(self.lower.clone(), self.upper.clone());
+        let (_, values) = search.best?;
+        Some(Solution {
+            objective: objective.eval(&values),
+            values,
+        })
+    }
+
+    /// Finds any solution satisfying all constraints.
+    #[must_use]
+    pub fn find_feasible(&self) -> Option<Solution> {
+        self.maximize(0)
+    }
+}
+
+/// Optimal values of the variables of a [`Model`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Solution {
+    objective: i64,
+    values: Vec<i64>,
+}
+
+impl Solution {
+    #[must_use]
+    pub fn objective(&self) -> i64 {
+        self.objective
+    }
+
+    #[must_use]
+    pub fn eval(&self, expr: &LinExpr) -> i64 {
+        expr.eval(&self.values)
+    }
+}
+
+impl Index<Var> for Solution {
+    type Output = i64;
+
+    fn index(&self, var: Var) -> &Self::Output {
+        &self.values[var.0]
+    }
+}
+
+struct BranchAndBound<'a> {
+    constraints: &'a [Constraint],
+    objective: Vec<i64>,
+    best: Option<(i64, Vec<i64>)>,
+}
+
+impl BranchAndBound<'_> {
+    fn branch(&mut self, lower: Vec<i64>, upper: Vec<Option<i64>>) {
+        if lower
+            .iter()
+            .zip(&upper)
+            .any(|(&l, &u)| u.is_some_and(|u| u < l))
+        {
+            return;
+        }
+        let Some((bound, x)) = self.relaxation(&lower, &upper) else {
+            return;
+        };
+        // The objective of an integral solution is an integer
+        let bound = (bound + EPS).floor();
+        if self
+            .best
+            .as_ref()
+            .is_some_and(|&(best, _)| bound <= best as f64)
+        {
+            return;
+        }
+
+        let fractional = x
+            .iter()
+            .enumerate()
+            .map(|(var, &value)| (var, value, (value - value.round()).abs()))
+            .filter(|&(_, _, distance)| distance > 1e-6)
+            .max_by(|a, b| a.2.total_cmp(&b.2));
+        let Some((var, value, _)) = fractional else {
+            let values: Vec<i64> = x.iter().map(|&value| value.round() as i64).collect();
+            let objective = dot(&self.objective, &values);
+            if self.best.as_ref().is_none_or(|&(best, _)| objective > best) {
+                self.best = Some((objective, values));
+            }
+            return;
+        };
+
+        let floor = value.floor() as i64;
+        let mut down_upper = upper.clone();
+        down_upper[var] = Some(floor);
+        let mut up_lower = lower.clone();
+        up_lower[var] = floor + 1;
+        if value - value.floor() < 0.5 {
+            self.branch(lower, down_upper);
+            self.branch(up_lower, upper);
+        } else {
+            self.branch(up_lower, upper);
+            self.branch(lower, down_upper);
+        }
+    }
+
+    /// Solves the LP relaxation with the given bounds, returning the optimal
+    /// objective and variable values.
+    fn relaxation(&self, lower: &[i64], upper: &[Option<i64>]) -> Option<(f64, Vec<f64>)> {
+        // Substitute x = lower + y with y >= 0
+        let n = lower.len();
+        let mut rows: Vec<(Vec<f64>, Cmp, f64)> = self
+            .constraints
+            .iter()
+            .map(|constraint| {
+                let coeffs = constraint.coeffs.iter().map(|&c| c as f64).collect();
+                let rhs = constraint.rhs - dot(&constraint.coeffs, lower);
+                (coeffs, constraint.cmp, rhs as f64)
+            })
+            .collect();
+        for var in 0..n {
+            if let Some(upper) = upper[var] {
+                let mut coeffs = vec![0.0; n];
+                coeffs[var] = 1.0;
+                rows.push((coeffs, Cmp::Le, (upper - lower[var]) as f64));
+            }
+        }
+        let objective: Vec<f64> = self.objective.iter().map(|&c| c as f64).collect();
+
+        match simplex(n, &rows, &objective) {
+            LpOutcome::Optimal(value, y) => {
+                let x = y.iter().zip(lower).map(|(&y, &l)| y + l as f64).collect();
+                Some((value + dot(&self.objective, lower) as f64, x))
+            }
+            LpOutcome::Infeasible => None,
+            LpOutcome::Unbounded => panic!("objective is unbounded"),
+        }
+    }
+}
+
+fn dot(a: &[i64], b: &[i64]) -> i64 {
+    a.iter().zip(b).map(|(x, y)| x * y).sum()
+}
+
+enum LpOutcome {
+    Optimal(f64, Vec<f64>),
+    Infeasible,
+    Unbounded,
+}
+
+/// Maximises `objective · x` subject to the rows and `x >= 0`, using the
+/// two-phase tableau simplex method with Bland's rule.
+fn simplex(n: usize, rows: &[(Vec<f64>, Cmp, f64)], objective: &[f64]) -> LpOutcome {
+    // Normalise to non-negative right-hand sides
+    let rows: Vec<_> = rows
+        .iter()
+        .map(|(coeffs, cmp, rhs)| {
+            if *rhs >= 0.0 {
+                return (coeffs.clone(), *cmp, *rhs);
+            }
+            let cmp = match cmp {
+                Cmp::Le => Cmp::Ge,
+                Cmp::Ge => Cmp::Le,
+                Cmp::Eq => Cmp::Eq,
+            };
+            (coeffs.iter().map(|&c| -c).collect(), cmp, -rhs)
+        })
+        .collect();
+
+    let m = rows.len();
+    let num_slack = rows.iter().filter(|(_, cmp, _)| *cmp != Cmp::Eq).count();
+    let num_artificial = rows.iter().filter(|(_, cmp, _)| *cmp != Cmp::Le).count();
+    let cols = n + num_slack + num_artificial;
+
+    let mut tableau = vec![vec![0.0; cols + 1]; m];
+    let mut basis = vec![0; m];
+    let (mut slack, mut artificial) = (n, n + num_slack);
+    for (i, (coeffs, cmp, rhs)) in rows.into_iter().enumerate() {
+        let row = &mut tableau[i];
+        row[..n].copy_from_slice(&coeffs);
+        row[cols] = rhs;
+        if cmp != Cmp::Eq {
+            row[slack] = if cmp == Cmp::Le { 1.0 } else { -1.0 };
+            basis[i] = slack;
+            slack += 1;
+        }
+        if cmp != Cmp::Le {
+            row[artificial] = 1.0;
+            basis[i] = artificial;
+            artificial += 1;
+        }
+    }
+
+    // Phase 1: drive the artificial variables to zero
+    let mut cost = vec![0.0; cols];
+    for c in &mut cost[n + num_slack..] {
+        *c = -1.0;
+    }
+    optimize(&mut tableau, &mut basis, &cost, cols);
+    let infeasibility: f64 = (0..m)
+        .filter(|&i| basis[i] >= n + num_slack)
+        .map(|i| tableau[i][cols])
+        .sum();
+    if infeasibility > 1e-7 {
+        return LpOutcome::Infeasible;
+    }
+    for i in 0..m {
+        if basis[i] >= n + num_slack
+            && let Some(j) = (0..n + num_slack).find(|&j| tableau[i][j].abs() > EPS)
+        {
+            pivot(&mut tableau, &mut basis, i, j);
+        }
+    }
+
+    // Phase 2: optimise the real objective without artificial variables
+    let mut cost = vec![0.0; cols];
+    cost[..n].copy_from_slice(objective);
+    if !optimize(&mut tableau, &mut basis, &cost, n + num_slack) {
+        return LpOutcome::Unbounded;
+    }
+
+    let mut x = vec![0.0; n];
+    for (i, &var) in basis.iter().enumerate() {
+        if var < n {
+            x[var] = tableau[i][cols];
+        }
+    }
+    let value = objective.iter().zip(&x).map(|(c, x)| c * x).sum();
+    LpOutcome::Optimal(value, x)
+}
+
+/// Pivots until no column below `allowed` improves `cost`, returning
+/// `false` if the objective is unbounded.
+fn optimize(tableau: &mut [Vec<f64>], basis: &mut [usize], cost: &[f64], allowed: usize) -> bool {
+    let rhs = cost.len();
+    loop {
+        let reduced_cost = |j: usize| {
+            cost[j]
+                - tableau
+                    .iter()
+                    .zip(basis.iter())
+                    .map(|(row, &b)| cost[b] * row[j])
+                    .sum::<f64>()
+        };
+        let Some(entering) = (0..allowed).find(|&j| reduced_cost(j) > EPS) else {
+            return true;
+        };
+
+        let leaving = (0..tableau.len())
+            .filter(|&i| tableau[i][entering] > EPS)
+            .min_by(|&i1, &i2| {
+                let ratio1 = tableau[i1][rhs] / tableau[i1][entering];
+                let ratio2 = tableau[i2][rhs] / tableau[i2][entering];
+                ratio1.total_cmp(&ratio2).then(basis[i1].cmp(&basis[i2]))
+            });
+        let Some(leaving) = leaving else {
+            return false;
+        };
+        pivot(tableau, basis, leaving, entering);
+    }
+}
+
+fn pivot(tableau: &mut [Vec<f64>], basis: &mut [usize], row: usize, col: usize) {
+    let pivot = tableau[row][col];
+    for cell in &mut tableau[row] {
+        *cell /= pivot;
+    }
+    let pivot_row = tableau[row].clone();
+    for (i, other) in tableau.iter_mut().enumerate() {
+        let factor = other[col];
+        if i != row && factor.abs() > EPS {
+            for (cell, &p) in other.iter_mut().zip(&pivot_row) {
+                *cell -= factor * p;
+            }
+        }
+    }
+    basis[row] = col;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rng::Rng;
+
+    #[test]
+    fn knapsack() {
+        let items = [(5, 10), (4, 40), (6, 30), (3, 50)];
+        let mut model = Model::new();
+        let take: Vec<_> = items.iter().map(|_| model.bool_var()).collect();
+        let weight: LinExpr = take.iter().zip(&items).map(|(&x, &(w, _))| x * w).sum();
+        model.add_le(weight, 10);
+        let value: LinExpr = take.iter().zip(&items).map(|(&x, &(_, v))| x * v).sum();
+
+        let solution = model.maximize(value.clone()).unwrap();
+        assert_eq!(solution.objective(), 90);
+        assert_eq!(solution.eval(&value), 90);
+        assert_eq!(
+            take.iter().map(|&x| solution[x]).collect::<Vec<_>>(),
+            [0, 1, 0, 1]
+        );
+    }
+
+    #[test]
+    fn bounds_and_equalities() {
+        let mut model = Model::new();
+        let x = model.int_var(-5..=5);
+        let y = model.int_var(-3..);
+        model.add_eq(2 * x + 3 * y, 1);
+        model.add_ge(x, y - 2);
+
+        let min = model.minimize(x).unwrap();
+        assert_eq!((min[x], min[y]), (-1, 1));
+        let max = model.maximize(x + y).unwrap();
+        assert_eq!((max[x], max[y]), (5, -3));
+        assert_eq!(max.objective(), 2);
+        assert!(model.find_feasible().is_some());
+    }
+
+    #[test]
+    fn infeasible() {
+        let mut model = Model::new();
+        let x = model.int_var(0..10);
+        let y = model.int_var(0..10);
+        // Only fractional solutions exist
+        model.add_eq(2 * x + 2 * y, 7);
+        assert_eq!(model.find_feasible(), None);
+
+        let mut model = Model::new();
+        let x = model.int_var(0..=3);
+        model.add_ge(x, 4);
+        assert_eq!(model.minimize(x), None);
+    }
+
+    #[test]
+    fn matches_brute_force() {
+        let mut rng = Rng::new(40);
+        for _ in 0..200 {
+            let coeff = |rng: &mut Rng| rng.range(0..11) as i64 - 5;
+            let mut model = Model::new();
+            let vars = [0; 3].map(|_| model.int_var(0..=4));
+            let mut constraints = Vec::new();
+            for _ in 0..3 {
+                let coeffs = [0; 3].map(|_| coeff(&mut rng));
+                let rhs = rng.range(0..15) as i64 - 3;
+                let expr: LinExpr = vars.iter().zip(coeffs).map(|(&v, c)| v * c).sum();
+                model.add_le(expr, rhs);
+                constraints.push((coeffs, rhs));
+            }
+            let objective = [0; 3].map(|_| coeff(&mut rng));
+            let expr: LinExpr = vars.iter().zip(objective).map(|(&v, c)| v * c).sum();
+
+            let mut expected = None;
+            for values in (0..125).map(|i| [i % 5, i / 5 % 5, i / 25]) {
+                let feasible = constraints
+                    .iter()
+                    .all(|(coeffs, rhs)| dot(coeffs, &values) <= *rhs);
+                if feasible {
+                    let value = dot(&objective, &values);
+                    expected = expected.max(Some(value));
+                }
+            }
+
+            let actual = model.maximize(expr).map(|solution| solution.objective());
+            assert_eq!(actual, expected);
+        }
+    }
+}
@@ -1,12 +1,39 @@
-use std::{iter::FusedIterator, marker::PhantomData};
+use std::{any, iter::FusedIterator, marker::PhantomData, ops::RangeInclusive};
 
 use anyhow::{Result, anyhow};
 use arrayvec::ArrayVec;
-use num::{Integer, Signed, Unsigned};
+use num::{Integer, PrimInt, Signed, Unsigned};
+
+mod parse;
+mod sections;
+
+#[doc(hidden)]
+pub use parse::__macro_support;
+pub use parse::{Cursor, Line, LineReader, ParseError, parse_lines};
+pub use parse_macro::Parse;
+pub use sections::{Blocks, blocks, grids, sections};
 
 pub trait Input {
     fn unsigned_integers<T>(&self) -> IntegersUnsigned<'_, T>;
     fn signed_integers<T>(&self) -> IntegersSigned<'_, T>;
+    fn signed_integers_with<T>(&self, sign: Sign) -> IntegersSignedWith<'_, T>;
+
+    /// Extracts runs of hexadecimal digits in either case, e.g. `70c710`
+    /// from `#70c710`. Since `a`-`f` are letters, this is meant for inputs
+    /// where no other text contains them.
+    fn hex_integers<T>(&self) -> IntegersHex<'_, T>;
+
+    /// Like [`unsigned_integers`](Self::unsigned_integers), but reports
+    /// integers that overflow `T`.
+    fn checked_unsigned_integers<T>(&self) -> CheckedIntegers<'_, T>;
+
+    /// Like [`signed_integers_with`](Self::signed_integers_with), but
+    /// reports integers that overflow `T`.
+    fn checked_signed_integers<T>(&self, sign: Sign) -> CheckedIntegers<'_, T>;
+
+    /// Extracts ranges written as `start-end`, e.g. `3-7` for `3..=7`,
+    /// skipping integers that are not part of a range.
+    fn ranges<T>(&self) -> IntegerRanges<'_, T>;
 
     fn unsigned_integers_n<T, const N: usize>(&self) -> Result<[T; N]>
     where
@@ -21,6 +48,14 @@ pub trait Input {
     {
         try_collect_n(self.signed_integers())
     }
+
+    /// Extracts exactly `N` integers from every line, with overflow checks.
+    ///
+    /// A `-` is taken as a [sign](Sign::Minus) if `T` is signed. Errors
+    /// are [`ParseError`]s pointing at the offending line.
+    fn lines_integers_n<T, const N: usize>(&self) -> Result<Vec<[T; N]>>
+    where
+        T: PrimInt;
 }
 
 impl<T: AsRef<[u8]> + ?Sized> Input for T {
@@ -31,11 +66,89 @@ impl<T: AsRef<[u8]> + ?Sized> Input for T {
     fn signed_integers<I>(&self) -> IntegersSigned<'_, I> {
         IntegersSigned(self.as_ref(), PhantomData)
     }
+
+    fn signed_integers_with<I>(&self, sign: Sign) -> IntegersSignedWith<'_, I> {
+        IntegersSignedWith(self.as_ref(), sign, PhantomData)
+    }
+
+    fn hex_integers<I>(&self) -> IntegersHex<'_, I> {
+        IntegersHex(self.as_ref(), PhantomData)
+    }
+
+    fn checked_unsigned_integers<I>(&self) -> CheckedIntegers<'_, I> {
+        CheckedIntegers::new(self.as_ref(), None)
+    }
+
+    fn checked_signed_integers<I>(&self, sign: Sign) -> CheckedIntegers<'_, I> {
+        CheckedIntegers::new(self.as_ref(), Some(sign))
+    }
+
+    fn ranges<I>(&self) -> IntegerRanges<'_, I> {
+        IntegerRanges(self.as_ref(), PhantomData)
+    }
+
+    fn lines_integers_n<I, const N: usize>(&self) -> Result<Vec<[I; N]>>
+    where
+        I: PrimInt,
+    {
+        let input = self.as_ref();
+        let sign = (I::min_value() < I::zero()).then_some(Sign::Minus);
+        let mut start = 0;
+        let mut lines = Vec::new();
+        for line in 1.. {
+            if start >= input.len() {
+                break;
+            }
+            let end = input[start..]
+                .iter()
+                .position(|&c| c == b'\n')
+                .map_or(input.len(), |len| start + len);
+            let integers = CheckedIntegers {
+                input,
+                pos: start,
+                end,
+                sign,
+                _marker: PhantomData,
+            };
+            let values = integers.collect::<Result<Vec<_>, _>>()?;
+            let values = collect_n(values).map_err(|message| ParseError::new(line, 1, message))?;
+            lines.push(values);
+            start = end + 1;
+        }
+        Ok(lines)
+    }
+}
+
+/// Whether a `-` in front of the digits makes an integer negative.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sign {
+    /// Any `-` directly before the digits, so `a-5` contains `-5`.
+    Minus,
+    /// Only a `-` that does not follow a letter or digit, so `3-7` and
+    /// `a-5` contain no negative integers but `x=-5` does.
+    Separate,
+}
+
+impl Sign {
+    fn is_negative(self, prefix: &[u8]) -> bool {
+        let Some((&b'-', before)) = prefix.split_last() else {
+            return false;
+        };
+        self == Self::Minus || !before.last().is_some_and(u8::is_ascii_alphanumeric)
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
 pub struct IntegersUnsigned<'a, T>(&'a [u8], PhantomData<T>);
 
+impl<T> IntegersUnsigned<'_, T> {
+    /// Groups consecutive integers into arrays, e.g. coordinate pairs.
+    #[must_use]
+    pub fn chunks<const N: usize>(self) -> Chunks<Self, N> {
+        Chunks(self)
+    }
+}
+
 impl<T: Integer + Unsigned + From<u8>> Iterator for IntegersUnsigned<'_, T> {
     type Item = T;
 
@@ -53,9 +166,19 @@ impl<T: Integer + Unsigned + From<u8>> Iterator for IntegersUnsigned<'_, T> {
 
 impl<T: Integer + Unsigned + From<u8>> FusedIterator for IntegersUnsigned<'_, T> {}
 
+/// Signed integers where any `-` directly before the digits is a sign,
+/// i.e., [`Sign::Minus`] without deciding on the sign per integer.
 #[derive(Debug, Clone, Copy)]
 pub struct IntegersSigned<'a, T>(&'a [u8], PhantomData<T>);
 
+impl<T> IntegersSigned<'_, T> {
+    /// Groups consecutive integers into arrays, e.g. coordinate pairs.
+    #[must_use]
+    pub fn chunks<const N: usize>(self) -> Chunks<Self, N> {
+        Chunks(self)
+    }
+}
+
 impl<T: Integer + Signed + From<u8>> Iterator for IntegersSigned<'_, T> {
     type Item = T;
 
@@ -73,6 +196,201 @@ impl<T: Integer + Signed + From<u8>> Iterator for IntegersSigned<'_, T> {
 
 impl<T: Integer + Signed + From<u8>> FusedIterator for IntegersSigned<'_, T> {}
 
+#[derive(Debug, Clone, Copy)]
+pub struct IntegersSignedWith<'a, T>(&'a [u8], Sign, PhantomData<T>);
+
+impl<T> IntegersSignedWith<'_, T> {
+    /// Groups consecutive integers into arrays, e.g. coordinate pairs.
+    #[must_use]
+    pub fn chunks<const N: usize>(self) -> Chunks<Self, N> {
+        Chunks(self)
+    }
+}
+
+impl<T: Integer + Signed + From<u8>> Iterator for IntegersSignedWith<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.0.iter().position(u8::is_ascii_digit)?;
+        let (mut num, mut rem) = next_unsigned::<T>(&self.0[start..])?;
+        if self.1.is_negative(&self.0[..start]) {
+            num = -num;
+        }
+        if self.1 == Sign::Separate {
+            // A `-` right after the digits cannot be a sign
+            rem = rem.strip_prefix(b"-").unwrap_or(rem);
+        }
+        self.0 = rem;
+        Some(num)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.0.len();
+        (0, Some(len.div_ceil(2)))
+    }
+}
+
+impl<T: Integer + Signed + From<u8>> FusedIterator for IntegersSignedWith<'_, T> {}
+
+#[derive(Debug, Clone, Copy)]
+pub struct IntegersHex<'a, T>(&'a [u8], PhantomData<T>);
+
+impl<T: Integer + Unsigned + From<u8>> Iterator for IntegersHex<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.0.iter().position(u8::is_ascii_hexdigit)?;
+        let mut num = T::zero();
+        let mut s = &self.0[start..];
+        while let Some((digit, tail)) = s
+            .split_first()
+            .and_then(|(&c, tail)| Some((char::from(c).to_digit(16)?, tail)))
+        {
+            // Hex digits are below 16 and therefore fit in a u8
+            num = num * T::from(16_u8) + T::from(digit as u8);
+            s = tail;
+        }
+
+        self.0 = s;
+        Some(num)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.0.len();
+        (0, Some(len.div_ceil(2)))
+    }
+}
+
+impl<T: Integer + Unsigned + From<u8>> FusedIterator for IntegersHex<'_, T> {}
+
+/// Iterator over groups of `N` integers, dropping an incomplete group at
+/// the end.
+#[derive(Debug, Clone, Copy)]
+pub struct Chunks<I, const N: usize>(I);
+
+impl<I: Iterator, const N: usize> Iterator for Chunks<I, N> {
+    type Item = [I::Item; N];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut chunk = ArrayVec::new();
+        for _ in 0..N {
+            chunk.push(self.0.next()?);
+        }
+        chunk.into_inner().ok()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let (min, max) = self.0.size_hint();
+        (min / N, max.map(|max| max / N))
+    }
+}
+
+impl<I: FusedIterator, const N: usize> FusedIterator for Chunks<I, N> {}
+
+#[derive(Debug, Clone, Copy)]
+pub struct IntegerRanges<'a, T>(&'a [u8], PhantomData<T>);
+
+impl<T: Integer + Unsigned + From<u8>> Iterator for IntegerRanges<'_, T> {
+    type Item = RangeInclusive<T>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (start, rem) = next_unsigned::<T>(self.0)?;
+            self.0 = rem;
+            if let [b'-', c, ..] = rem
+                && c.is_ascii_digit()
+            {
+                let (end, rem) = next_unsigned::<T>(&rem[1..])?;
+                self.0 = rem;
+                return Some(start..=end);
+            }
+        }
+    }
+}
+
+impl<T: Integer + Unsigned + From<u8>> FusedIterator for IntegerRanges<'_, T> {}
+
+/// Iterator over integers that fails on values that do not fit in `T`.
+#[derive(Debug, Clone)]
+pub struct CheckedIntegers<'a, T> {
+    input: &'a [u8],
+    pos: usize,
+    end: usize,
+    /// How to interpret `-`, or `None` to ignore it.
+    sign: Option<Sign>,
+    _marker: PhantomData<T>,
+}
+
+impl<'a, T> CheckedIntegers<'a, T> {
+    fn new(input: &'a [u8], sign: Option<Sign>) -> Self {
+        Self {
+            input,
+            pos: 0,
+            end: input.len(),
+            sign,
+            _marker: PhantomData,
+        }
+    }
+
+    fn overflow(&self, start: usize, end: usize) -> ParseError {
+        let line_start = self.input[..start]
+            .iter()
+            .rposition(|&c| c == b'\n')
+            .map_or(0, |i| i + 1);
+        let line = count_newlines(&self.input[..line_start]) + 1;
+        // Count characters rather than UTF-8 continuation bytes
+        let column = self.input[line_start..start]
+            .iter()
+            .filter(|&&c| c & 0xc0 != 0x80)
+            .count()
+            + 1;
+        let text = String::from_utf8_lossy(&self.input[start..end]);
+        let message = format!("{text} does not fit in {}", any::type_name::<T>());
+        ParseError::new(line, column, message)
+    }
+}
+
+#[expect(clippy::naive_bytecount, reason = "only used for error messages")]
+fn count_newlines(s: &[u8]) -> usize {
+    s.iter().filter(|&&c| c == b'\n').count()
+}
+
+impl<T: PrimInt> Iterator for CheckedIntegers<'_, T> {
+    type Item = Result<T, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let s = &self.input[self.pos..self.end];
+        let start = self.pos + s.iter().position(u8::is_ascii_digit)?;
+        let end = self.input[start..self.end]
+            .iter()
+            .position(|c| !c.is_ascii_digit())
+            .map_or(self.end, |len| start + len);
+        self.pos = end;
+
+        let negative = self
+            .sign
+            .is_some_and(|sign| sign.is_negative(&self.input[..start]));
+        // Digits and ten fit in every integer type
+        let ten = T::from(10).unwrap();
+        let mut digits = self.input[start..end]
+            .iter()
+            .map(|&c| T::from(c - b'0').unwrap());
+        let value = if negative {
+            digits.try_fold(T::zero(), |num, digit| {
+                num.checked_mul(&ten)?.checked_sub(&digit)
+            })
+        } else {
+            digits.try_fold(T::zero(), |num, digit| {
+                num.checked_mul(&ten)?.checked_add(&digit)
+            })
+        };
+        let start = start - usize::from(negative);
+        Some(value.ok_or_else(|| self.overflow(start, end)))
+    }
+}
+
+impl<T: PrimInt> FusedIterator for CheckedIntegers<'_, T> {}
+
 fn next_unsigned<T: Integer + From<u8>>(s: &[u8]) -> Option<(T, &[u8])> {
     let start = s.iter().position(u8::is_ascii_digit)?;
     let mut num = T::zero();
@@ -95,13 +413,130 @@ fn next_signed<T: Integer + Signed + From<u8>>(s: &[u8]) -> Option<(T, &[u8])> {
     Some((num, rem))
 }
 
-fn try_collect_n<T, const N: usize>(iter: impl Iterator<Item = T>) -> Result<[T; N]> {
+fn try_collect_n<T, const N: usize>(iter: impl IntoIterator<Item = T>) -> Result<[T; N]> {
+    collect_n(iter).map_err(|message| anyhow!(message))
+}
+
+fn collect_n<T, const N: usize>(iter: impl IntoIterator<Item = T>) -> Result<[T; N], String> {
     let mut arr = ArrayVec::<T, N>::new();
     for num in iter {
         arr.try_push(num)
-            .map_err(|_| anyhow!("Expected only {N} integers, got at least one more"))?;
+            .map_err(|_| format!("Expected only {N} integers, got at least one more"))?;
     }
 
     arr.into_inner()
-        .map_err(|arr| anyhow!("Expected {} integers, got {}", N, arr.len()))
+        .map_err(|arr| format!("Expected {} integers, got {}", N, arr.len()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn integers() {
+        let s = "a-5 x=-12, 3-7 -0 99";
+        assert_eq!(
+            s.unsigned_integers::<u32>().collect::<Vec<_>>(),
+            [5, 12, 3, 7, 0, 99]
+        );
+        assert_eq!(
+            s.signed_integers::<i32>().collect::<Vec<_>>(),
+            [-5, -12, 3, -7, 0, 99]
+        );
+        assert_eq!(
+            s.signed_integers_with::<i32>(Sign::Separate)
+                .collect::<Vec<_>>(),
+            [5, -12, 3, 7, 0, 99]
+        );
+        assert_eq!(s.unsigned_integers_n::<u8, 6>().unwrap()[5], 99);
+        assert!(s.unsigned_integers_n::<u8, 5>().is_err());
+        assert!(s.unsigned_integers_n::<u8, 7>().is_err());
+    }
+
+    #[test]
+    fn checked() {
+        let values: Vec<_> = "1 255 -128 127"
+            .checked_signed_integers::<i16>(Sign::Minus)
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(values, [1, 255, -128, 127]);
+
+        let s = "1 2\n3 é256 -7";
+        let mut iter = s.checked_unsigned_integers::<u8>();
+        assert_eq!(iter.nth(2), Some(Ok(3)));
+        let error = iter.next().unwrap().unwrap_err();
+        assert_eq!((error.line(), error.column()), (2, 4));
+        assert_eq!(error.message(), "256 does not fit in u8");
+        assert_eq!(iter.next(), Some(Ok(7)));
+        assert_eq!(iter.next(), None);
+
+        let mut iter = s.checked_signed_integers::<u8>(Sign::Minus).skip(4);
+        let error = iter.next().unwrap().unwrap_err();
+        assert_eq!(
+            (error.column(), error.message()),
+            (8, "-7 does not fit in u8")
+        );
+
+        let min = i64::MIN.to_string();
+        let mut iter = min.checked_signed_integers::<i64>(Sign::Separate);
+        assert_eq!(iter.next(), Some(Ok(i64::MIN)));
+        assert!(
+            "9223372036854775808"
+                .checked_signed_integers::<i64>(Sign::Minus)
+                .next()
+                .unwrap()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn hex() {
+        let s = "#70c710 (#0DC571) 1f";
+        assert_eq!(
+            s.hex_integers::<u32>().collect::<Vec<_>>(),
+            [0x70_c710, 0x0d_c571, 0x1f]
+        );
+    }
+
+    #[test]
+    fn ranges_and_chunks() {
+        let ranges: Vec<_> = "5-8\n0-2 x 4-7, 10".ranges::<u32>().collect();
+        assert_eq!(ranges, [5..=8, 0..=2, 4..=7]);
+
+        let pairs: Vec<_> = "p=0,4 v=3,-3 7"
+            .signed_integers::<i32>()
+            .chunks::<2>()
+            .collect();
+        assert_eq!(pairs, [[0, 4], [3, -3]]);
+        let triples: Vec<_> = "1,2,3 4,5,6"
+            .unsigned_integers::<u8>()
+            .chunks::<3>()
+            .collect();
+        assert_eq!(triples, [[1, 2, 3], [4, 5, 6]]);
+    }
+
+    #[test]
+    fn lines() {
+        let input = "1, 2\n-3, 4\r\n5,6\n";
+        assert_eq!(
+            input.lines_integers_n::<i8, 2>().unwrap(),
+            [[1, 2], [-3, 4], [5, 6]]
+        );
+        assert_eq!(
+            input.lines_integers_n::<u8, 2>().unwrap(),
+            [[1, 2], [3, 4], [5, 6]]
+        );
+        assert!("".lines_integers_n::<u8, 2>().unwrap().is_empty());
+
+        let error = "1 2\n3\n".lines_integers_n::<u8, 2>().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 2, column 1: Expected 2 integers, got 1"
+        );
+        let error = "1 2\n3 4\n5 300".lines_integers_n::<u8, 2>().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 3, column 3: 300 does not fit in u8"
+        );
+    }
 }
@@ -0,0 +1,608 @@
+//! Line-by-line parsing with errors that point into the input.
+
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+    iter::FusedIterator,
+    str::FromStr,
+};
+
+/// An error pointing at the line and column of the input that failed to
+/// parse.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    line: usize,
+    column: usize,
+    message: String,
+}
+
+impl ParseError {
+    /// Creates an error at a 1-based line and column.
+    pub fn new(line: usize, column: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            column,
+            message: message.into(),
+        }
+    }
+
+    #[must_use]
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// The 1-based column, counted in characters.
+    #[must_use]
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    #[must_use]
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    /// Renders the offending line of `input` with a caret below the
+    /// column, e.g. for printing below the error message.
+    #[must_use]
+    pub fn excerpt(&self, input: &str) -> String {
+        let text = LineReader::new(input)
+            .nth(self.line.saturating_sub(1))
+            .map_or("", |line| line.text);
+        let number = self.line.to_string();
+        let margin = " ".repeat(number.len());
+        let indent = " ".repeat(self.column.saturating_sub(1));
+        format!("{margin} |\n{number} | {text}\n{margin} | {indent}^")
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line, self.column, self.message
+        )
+    }
+}
+
+impl Error for ParseError {}
+
+/// Iterates over the lines of the input like [`str::lines`], keeping track
+/// of line numbers and byte offsets.
+#[derive(Debug, Clone)]
+pub struct LineReader<'a> {
+    input: &'a str,
+    offset: usize,
+    line: usize,
+}
+
+impl<'a> LineReader<'a> {
+    #[must_use]
+    pub fn new(input: &'a str) -> Self {
+        Self {
+            input,
+            offset: 0,
+            line: 0,
+        }
+    }
+
+    /// Number of the last line read, or zero before the first line.
+    #[must_use]
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Byte offset of the next line in the input.
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Reads the next line, failing at the end of the input.
+    pub fn next_line(&mut self) -> Result<Line<'a>, ParseError> {
+        self.next().ok_or_else(|| self.end_of_input())
+    }
+
+    /// Skips `n` lines and reads the one after, like [`Iterator::nth`].
+    pub fn nth_line(&mut self, n: usize) -> Result<Line<'a>, ParseError> {
+        self.nth(n).ok_or_else(|| self.end_of_input())
+    }
+
+    fn end_of_input(&self) -> ParseError {
+        ParseError::new(self.line + 1, 1, "unexpected end of input")
+    }
+}
+
+impl<'a> Iterator for LineReader<'a> {
+    type Item = Line<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let rest = self
+            .input
+            .get(self.offset..)
+            .filter(|rest| !rest.is_empty())?;
+        let (text, len) = match rest.find('\n') {
+            Some(end) => (&rest[..end], end + 1),
+            None => (rest, rest.len()),
+        };
+        self.line += 1;
+        let line = Line {
+            text: text.strip_suffix('\r').unwrap_or(text),
+            number: self.line,
+            offset: self.offset,
+        };
+        self.offset += len;
+        Some(line)
+    }
+}
+
+impl FusedIterator for LineReader<'_> {}
+
+/// A line of the input together with its position.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Line<'a> {
+    text: &'a str,
+    number: usize,
+    offset: usize,
+}
+
+impl<'a> Line<'a> {
+    /// The text of the line, without the line terminator.
+    #[must_use]
+    pub fn text(&self) -> &'a str {
+        self.text
+    }
+
+    /// The 1-based line number.
+    #[must_use]
+    pub fn number(&self) -> usize {
+        self.number
+    }
+
+    /// Byte offset of the start of the line in the input.
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty()
+    }
+
+    #[must_use]
+    pub fn cursor(&self) -> Cursor<'a> {
+        Cursor {
+            text: self.text,
+            line: self.number,
+            offset: self.offset,
+            pos: 0,
+        }
+    }
+
+    /// An error at the start of the line.
+    pub fn error(&self, message: impl Into<String>) -> ParseError {
+        self.cursor().error(message)
+    }
+
+    /// Parses the whole line.
+    pub fn parse<T: FromStr>(&self) -> Result<T, ParseError>
+    where
+        anyhow::Error: From<T::Err>,
+    {
+        self.parse_with(str::parse)
+    }
+
+    /// Parses the line with any function, attributing its errors to this
+    /// line.
+    pub fn parse_with<T, E>(&self, f: impl FnOnce(&'a str) -> Result<T, E>) -> Result<T, ParseError>
+    where
+        anyhow::Error: From<E>,
+    {
+        f(self.text).map_err(|error| self.cursor().nested(0, error.into()))
+    }
+}
+
+/// A position within a line, for parsing it piece by piece.
+#[derive(Debug, Clone)]
+pub struct Cursor<'a> {
+    text: &'a str,
+    line: usize,
+    /// Byte offset of `text` in the input.
+    offset: usize,
+    /// Byte offset of the cursor in `text`.
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    /// Creates a cursor at the start of a standalone line, e.g. in
+    /// [`FromStr`] impls.
+    #[must_use]
+    pub fn new(text: &'a str) -> Self {
+        Self {
+            text,
+            line: 1,
+            offset: 0,
+            pos: 0,
+        }
+    }
+
+    /// The 1-based line number.
+    #[must_use]
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Byte offset of the cursor in the input.
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        self.offset + self.pos
+    }
+
+    /// The remaining text of the line.
+    #[must_use]
+    pub fn rest(&self) -> &'a str {
+        &self.text[self.pos..]
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.pos == self.text.len()
+    }
+
+    /// An error at the current position.
+    pub fn error(&self, message: impl Into<String>) -> ParseError {
+        self.error_at(self.pos, message)
+    }
+
+    fn error_at(&self, pos: usize, message: impl Into<String>) -> ParseError {
+        ParseError::new(self.line, self.column_of(pos), message)
+    }
+
+    fn column_of(&self, pos: usize) -> usize {
+        self.text[..pos].chars().count() + 1
+    }
+
+    /// Converts an error from parsing the text starting at `pos`, keeping
+    /// the position of nested `ParseError`s.
+    fn nested(&self, pos: usize, error: anyhow::Error) -> ParseError {
+        match error.downcast::<ParseError>() {
+            Ok(error) if error.line == 1 => ParseError {
+                line: self.line,
+                column: error.column + self.column_of(pos) - 1,
+                ..error
+            },
+            Ok(error) => ParseError {
+                line: error.line + self.line - 1,
+                ..error
+            },
+            Err(error) => self.error_at(pos, format!("{error:#}")),
+        }
+    }
+
+    pub fn skip_whitespace(&mut self) {
+        let rest = self.rest();
+        self.pos += rest.len() - rest.trim_start_matches([' ', '\t']).len();
+    }
+
+    /// Consumes `literal` if the rest of the line starts with it.
+    pub fn eat(&mut self, literal: &str) -> bool {
+        let found = self.rest().starts_with(literal);
+        if found {
+            self.pos += literal.len();
+        }
+        found
+    }
+
+    pub fn expect(&mut self, literal: &str) -> Result<(), ParseError> {
+        if !self.eat(literal) {
+            return Err(self.error(format!("expected `{literal}`")));
+        }
+        Ok(())
+    }
+
+    /// Takes the next whitespace-separated word.
+    pub fn word(&mut self) -> Result<&'a str, ParseError> {
+        self.skip_whitespace();
+        let rest = self.rest();
+        let len = rest.find([' ', '\t']).unwrap_or(rest.len());
+        if len == 0 {
+            return Err(self.error("expected a word"));
+        }
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+
+    /// Parses the next whitespace-separated word, leaving the cursor in
+    /// place on failure.
+    pub fn parse<T: FromStr>(&mut self) -> Result<T, ParseError>
+    where
+        anyhow::Error: From<T::Err>,
+    {
+        let pos = self.pos;
+        let word = self.word()?;
+        let start = self.pos - word.len();
+        word.parse().map_err(|error: T::Err| {
+            let error = self.nested(start, error.into());
+            self.pos = pos;
+            error
+        })
+    }
+
+    /// Takes the text up to the next occurrence of `until`, or the rest of
+    /// the line if `None`.
+    fn take(&mut self, name: &str, until: Option<&str>) -> Result<&'a str, ParseError> {
+        let rest = self.rest();
+        let len = match until {
+            Some(until) => rest
+                .find(until)
+                .ok_or_else(|| self.error(format!("expected `{until}` after `{name}`")))?,
+            None => rest.len(),
+        };
+        self.pos += len;
+        Ok(&rest[..len])
+    }
+
+    fn parse_named<T: FromStr>(&self, name: &str, text: &str, pos: usize) -> Result<T, ParseError>
+    where
+        anyhow::Error: From<T::Err>,
+    {
+        if text.is_empty() {
+            return Err(self.error_at(pos, format!("missing `{name}`")));
+        }
+        text.parse().map_err(|error: T::Err| {
+            let error = anyhow::Error::from(error).context(format!("invalid `{name}`"));
+            self.nested(pos, error)
+        })
+    }
+
+    /// Parses the text up to `until` as the value called `name`.
+    pub fn field<T: FromStr>(&mut self, name: &str, until: Option<&str>) -> Result<T, ParseError>
+    where
+        anyhow::Error: From<T::Err>,
+    {
+        let start = self.pos;
+        let text = self.take(name, until)?;
+        self.parse_named(name, text, start)
+    }
+
+    /// Parses the text up to `until` as a list of values separated by
+    /// `separator`.
+    pub fn list<T: FromStr>(
+        &mut self,
+        name: &str,
+        separator: &str,
+        until: Option<&str>,
+    ) -> Result<Vec<T>, ParseError>
+    where
+        anyhow::Error: From<T::Err>,
+    {
+        let mut pos = self.pos;
+        let text = self.take(name, until)?;
+        if text.is_empty() {
+            return Ok(Vec::new());
+        }
+        text.split(separator)
+            .map(|item| {
+                let value = self.parse_named(name, item, pos);
+                pos += item.len() + separator.len();
+                value
+            })
+            .collect()
+    }
+
+    /// Checks that the whole line was consumed.
+    pub fn finish(self) -> Result<(), ParseError> {
+        if !self.is_empty() {
+            return Err(self.error(format!("unexpected `{}`", self.rest())));
+        }
+        Ok(())
+    }
+}
+
+/// Parses each line of the input, e.g. into a type deriving
+/// [`Parse`](super::Parse).
+pub fn parse_lines<T: FromStr>(input: &str) -> anyhow::Result<Vec<T>>
+where
+    anyhow::Error: From<T::Err>,
+{
+    Ok(LineReader::new(input)
+        .map(|line| line.parse())
+        .collect::<Result<_, _>>()?)
+}
+
+#[doc(hidden)]
+pub mod __macro_support {
+    pub use super::{Cursor, ParseError};
+
+    pub type Alternative<'a, T> = &'a dyn Fn(&str) -> Result<T, ParseError>;
+
+    /// Returns the first successful parse, or otherwise the error of the
+    /// alternative that got furthest.
+    pub fn first_match<T>(
+        text: &str,
+        alternatives: &[Alternative<'_, T>],
+    ) -> Result<T, ParseError> {
+        let mut furthest: Option<ParseError> = None;
+        for alternative in alternatives {
+            match alternative(text) {
+                Ok(value) => return Ok(value),
+                Err(error) => {
+                    let position = |error: &ParseError| (error.line, error.column);
+                    if furthest
+                        .as_ref()
+                        .is_none_or(|f| position(&error) > position(f))
+                    {
+                        furthest = Some(error);
+                    }
+                }
+            }
+        }
+        Err(furthest.expect("there should be at least one alternative"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::Parse;
+
+    #[derive(Debug, PartialEq, Eq, Parse)]
+    #[parse("{from} to {to} = {distance}")]
+    struct Route {
+        from: String,
+        to: String,
+        distance: u32,
+    }
+
+    #[derive(Debug, PartialEq, Eq, Parse)]
+    #[parse("{0} ({1}) -> {{{2:, }}}")]
+    struct Node(String, u32, Vec<Route>);
+
+    #[derive(Debug, PartialEq, Eq, Parse)]
+    enum Instruction {
+        #[parse("noop")]
+        Noop,
+        #[parse("add {0}")]
+        Add(i32),
+        #[parse("jump {offset} if {register}")]
+        Jump { register: char, offset: i32 },
+    }
+
+    #[test]
+    fn derive_struct() {
+        let route: Route = "London to Dublin = 464".parse().unwrap();
+        assert_eq!(
+            route,
+            Route {
+                from: "London".into(),
+                to: "Dublin".into(),
+                distance: 464,
+            }
+        );
+
+        let error = "London to Dublin = x".parse::<Route>().unwrap_err();
+        assert_eq!(error.column(), 20);
+        assert_eq!(
+            error.to_string(),
+            "line 1, column 20: invalid `distance`: invalid digit found in string"
+        );
+        let error = "London at Dublin = 464".parse::<Route>().unwrap_err();
+        assert_eq!(error.message(), "expected ` to ` after `from`");
+        assert_eq!(error.column(), 1);
+        let error = " to Dublin = 464".parse::<Route>().unwrap_err();
+        assert_eq!(error.message(), "missing `from`");
+    }
+
+    #[test]
+    fn derive_nested_list() {
+        let node: Node = "a (1) -> {x to y = 2, y to z = 3}".parse().unwrap();
+        assert_eq!(node.0, "a");
+        assert_eq!(node.2.len(), 2);
+        assert_eq!(node.2[1].distance, 3);
+        let node: Node = "b (2) -> {}".parse().unwrap();
+        assert!(node.2.is_empty());
+
+        // The column points into the nested route
+        let error = "a (1) -> {x to y = 2, y to z = ?}"
+            .parse::<Node>()
+            .unwrap_err();
+        assert_eq!(error.column(), 32);
+        let error = "a (1) -> {x to y = 2}!".parse::<Node>().unwrap_err();
+        assert_eq!(error.message(), "unexpected `!`");
+        assert_eq!(error.column(), 22);
+    }
+
+    #[test]
+    fn derive_enum() {
+        let instructions: Vec<Instruction> = parse_lines("noop\nadd -3\njump 5 if a\n").unwrap();
+        assert_eq!(
+            instructions,
+            [
+                Instruction::Noop,
+                Instruction::Add(-3),
+                Instruction::Jump {
+                    register: 'a',
+                    offset: 5
+                },
+            ]
+        );
+
+        // Reports the alternative that got furthest
+        let error = parse_lines::<Instruction>("noop\njump 5 if ab")
+            .unwrap_err()
+            .downcast::<ParseError>()
+            .unwrap();
+        assert_eq!((error.line(), error.column()), (2, 11));
+        assert_eq!(
+            error.message(),
+            "invalid `register`: too many characters in string"
+        );
+    }
+
+    #[test]
+    fn parse_lines_with_std_types() {
+        assert_eq!(parse_lines::<u8>("1\n2\n3").unwrap(), [1, 2, 3]);
+        let error = parse_lines::<u8>("1\n256").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "line 2, column 1: number too large to fit in target type"
+        );
+    }
+
+    #[test]
+    fn line_reader() {
+        let input = "first\r\n\nthird line\nfourth";
+        let mut lines = LineReader::new(input);
+        let first = lines.next_line().unwrap();
+        assert_eq!(
+            (first.text(), first.number(), first.offset()),
+            ("first", 1, 0)
+        );
+        assert!(lines.next_line().unwrap().is_empty());
+        let third = lines.next_line().unwrap();
+        assert_eq!((third.number(), third.offset()), (3, 8));
+        assert_eq!(lines.line(), 3);
+        assert_eq!(lines.nth_line(0).unwrap().text(), "fourth");
+        assert_eq!(
+            lines.next_line().unwrap_err().to_string(),
+            "line 5, column 1: unexpected end of input"
+        );
+        assert_eq!(LineReader::new("a\nb\n").count(), 2);
+        assert_eq!(LineReader::new("").count(), 0);
+    }
+
+    #[test]
+    fn cursor() {
+        let line = LineReader::new("\nRegister A: 729 words").nth(1).unwrap();
+        let mut cursor = line.cursor();
+        cursor.expect("Register ").unwrap();
+        assert_eq!(cursor.word().unwrap(), "A:");
+        assert_eq!(cursor.offset(), 12);
+        assert_eq!(cursor.parse::<u32>().unwrap(), 729);
+        assert!(!cursor.eat("!"));
+
+        let error = cursor.parse::<u32>().unwrap_err();
+        assert_eq!((error.line(), error.column()), (2, 17));
+        assert_eq!(
+            error.excerpt("\nRegister A: 729 words"),
+            "  |\n2 | Register A: 729 words\n  |                 ^"
+        );
+        assert_eq!(cursor.clone().finish().unwrap_err().column(), 16);
+        assert_eq!(cursor.word().unwrap(), "words");
+        assert!(cursor.word().is_err());
+        cursor.finish().unwrap();
+    }
+
+    #[test]
+    fn nested_line_numbers() {
+        let input = "London to Dublin = 464\nLondon to Belfast = 51x";
+        let line = LineReader::new(input).nth(1).unwrap();
+        let error = line.parse::<Route>().unwrap_err();
+        assert_eq!((error.line(), error.column()), (2, 21));
+
+        let error = line.parse_with(str::parse::<u8>).unwrap_err();
+        assert_eq!((error.line(), error.column()), (2, 1));
+    }
+}
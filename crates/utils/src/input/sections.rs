@@ -0,0 +1,132 @@
+//! Splitting the input into blocks of lines separated by blank lines.
+
+use std::iter::FusedIterator;
+
+use anyhow::{Context, Result};
+
+use super::{LineReader, ParseError};
+use crate::grid::Grid;
+
+/// Iterates over blocks of lines separated by one or more blank lines.
+///
+/// Each block is a slice of the input without its final line terminator.
+/// Lines containing only whitespace count as blank, and `\r\n` line
+/// endings are supported.
+#[must_use]
+pub fn blocks(input: &str) -> Blocks<'_> {
+    Blocks {
+        input,
+        lines: LineReader::new(input),
+        line: 0,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Blocks<'a> {
+    input: &'a str,
+    lines: LineReader<'a>,
+    /// Number of the first line of the last block.
+    line: usize,
+}
+
+impl Blocks<'_> {
+    /// Number of the first line of the block returned last, e.g. to
+    /// position errors within it.
+    #[must_use]
+    pub fn line(&self) -> usize {
+        self.line
+    }
+}
+
+impl<'a> Iterator for Blocks<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let is_blank = |text: &str| text.trim().is_empty();
+        let first = self.lines.find(|line| !is_blank(line.text()))?;
+        let mut last = first;
+        for line in self.lines.by_ref() {
+            if is_blank(line.text()) {
+                break;
+            }
+            last = line;
+        }
+
+        self.line = first.number();
+        Some(&self.input[first.offset()..last.offset() + last.text().len()])
+    }
+}
+
+impl FusedIterator for Blocks<'_> {}
+
+/// Splits the input into exactly `N` [blocks](blocks), e.g. rules and
+/// updates.
+pub fn sections<const N: usize>(input: &str) -> Result<[&str; N], ParseError> {
+    let mut blocks = blocks(input);
+    let mut sections = [""; N];
+    for (i, section) in sections.iter_mut().enumerate() {
+        *section = blocks.next().ok_or_else(|| {
+            let line = LineReader::new(input).count() + 1;
+            let message = format!("expected {N} sections separated by blank lines, found {i}");
+            ParseError::new(line, 1, message)
+        })?;
+    }
+    if blocks.next().is_some() {
+        let message = format!("expected only {N} sections separated by blank lines");
+        return Err(ParseError::new(blocks.line(), 1, message));
+    }
+    Ok(sections)
+}
+
+/// Parses every [block](blocks) as a grid, mapping each byte to a cell.
+pub fn grids<T>(input: &str, mut f: impl FnMut(u8) -> T) -> Result<Vec<Grid<T>>> {
+    let mut blocks = blocks(input);
+    let mut grids = Vec::new();
+    while let Some(block) = blocks.next() {
+        let grid = Grid::parse(block, &mut f)
+            .with_context(|| format!("invalid grid starting at line {}", blocks.line()))?;
+        grids.push(grid);
+    }
+    Ok(grids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_blocks() {
+        let input = "\n\na\nb\n\n\n  \nc\r\nd\r\n\r\ne\n";
+        assert_eq!(blocks(input).collect::<Vec<_>>(), ["a\nb", "c\r\nd", "e"]);
+
+        let mut iter = blocks(input);
+        iter.nth(1);
+        assert_eq!(iter.line(), 8);
+        assert_eq!(blocks("").count(), 0);
+        assert_eq!(blocks("\n \n").count(), 0);
+    }
+
+    #[test]
+    fn exact_sections() {
+        assert_eq!(sections("1-2\n3-4\n\n5\n6"), Ok(["1-2\n3-4", "5\n6"]));
+        assert_eq!(
+            sections::<3>("a\n\nb\n").unwrap_err().to_string(),
+            "line 4, column 1: expected 3 sections separated by blank lines, found 2"
+        );
+        assert_eq!(
+            sections::<2>("a\n\nb\n\nc").unwrap_err().to_string(),
+            "line 5, column 1: expected only 2 sections separated by blank lines"
+        );
+    }
+
+    #[test]
+    fn block_grids() {
+        let parsed = grids("#.\n.#\n\n##\r\n##\r\n", |c| c == b'#').unwrap();
+        assert_eq!(parsed.len(), 2);
+        assert!(parsed[0][(1, 1)] && !parsed[0][(1, 0)]);
+        assert!(parsed[1].iter().all(|&c| c));
+
+        let error = grids("#.\n.#\n\n#\n##", |c| c).unwrap_err();
+        assert_eq!(error.to_string(), "invalid grid starting at line 4");
+    }
+}
@@ -1,6 +1,22 @@
+// Lets the tests use derive macros that refer to `::utils`
+#[cfg(test)]
+extern crate self as utils;
+
+mod block_buffer;
+
+pub mod crypto;
+pub mod geom;
+pub mod graph;
 pub mod grid;
 pub mod hash;
+pub mod hex;
+pub mod ilp;
 pub mod input;
+pub mod linalg;
 pub mod md5;
 pub mod num;
 pub mod ocr;
+pub mod rng;
+pub mod search;
+pub mod threads;
+pub mod vm;
@@ -0,0 +1,288 @@
+//! Exact linear algebra over the rationals, for small systems of equations.
+
+use std::ops::RangeInclusive;
+
+use num::{Zero, rational::Ratio};
+
+pub type Rational = Ratio<i128>;
+
+/// A linear system `A x = b`, reduced to reduced row echelon form by
+/// Gaussian elimination.
+#[derive(Debug, Clone)]
+pub struct LinearSystem {
+    /// Augmented rows `[A | b]`, with the first `rank` rows being nonzero.
+    rows: Vec<Vec<Rational>>,
+    /// Pivot column of each of the first `rank` rows.
+    pivots: Vec<usize>,
+    num_vars: usize,
+}
+
+impl LinearSystem {
+    /// Builds the system from the rows of `A` and the right-hand side `b`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rows of `A` differ in length or `b` has a different
+    /// number of rows.
+    pub fn new<T: Copy + Into<i128>>(a: &[impl AsRef<[T]>], b: &[T]) -> Self {
+        assert_eq!(
+            a.len(),
+            b.len(),
+            "A and b must have the same number of rows"
+        );
+        let num_vars = a.first().map_or(0, |row| row.as_ref().len());
+        let rows = a
+            .iter()
+            .zip(b)
+            .map(|(row, &rhs)| {
+                let row = row.as_ref();
+                assert_eq!(row.len(), num_vars, "rows of A must have the same length");
+                row.iter()
+                    .chain([&rhs])
+                    .map(|&x| Rational::from_integer(x.into()))
+                    .collect()
+            })
+            .collect();
+
+        let mut system = Self {
+            rows,
+            pivots: Vec::new(),
+            num_vars,
+        };
+        system.eliminate();
+        system
+    }
+
+    fn eliminate(&mut self) {
+        for col in 0..self.num_vars {
+            let rank = self.pivots.len();
+            let Some(pivot_row) = (rank..self.rows.len()).find(|&r| !self.rows[r][col].is_zero())
+            else {
+                continue;
+            };
+            self.rows.swap(rank, pivot_row);
+
+            let pivot = self.rows[rank][col];
+            for x in &mut self.rows[rank] {
+                *x /= pivot;
+            }
+            for r in 0..self.rows.len() {
+                let factor = self.rows[r][col];
+                if r != rank && !factor.is_zero() {
+                    for c in col..=self.num_vars {
+                        let delta = factor * self.rows[rank][c];
+                        self.rows[r][c] -= delta;
+                    }
+                }
+            }
+            self.pivots.push(col);
+        }
+    }
+
+    #[must_use]
+    pub fn num_vars(&self) -> usize {
+        self.num_vars
+    }
+
+    /// Rank of the coefficient matrix `A`.
+    #[must_use]
+    pub fn rank(&self) -> usize {
+        self.pivots.len()
+    }
+
+    /// Whether the system has at least one solution.
+    #[must_use]
+    pub fn is_consistent(&self) -> bool {
+        self.rows[self.rank()..]
+            .iter()
+            .all(|row| row[self.num_vars].is_zero())
+    }
+
+    /// Indices of the variables that can be chosen freely.
+    #[must_use]
+    pub fn free_vars(&self) -> Vec<usize> {
+        let mut pivots = self.pivots.iter().peekable();
+        (0..self.num_vars)
+            .filter(|&col| pivots.next_if_eq(&&col).is_none())
+            .collect()
+    }
+
+    /// The solution if there is exactly one.
+    #[must_use]
+    pub fn solution(&self) -> Option<Vec<Rational>> {
+        if self.rank() < self.num_vars {
+            return None;
+        }
+        self.solve_with(&[])
+    }
+
+    /// The solution with the given values for the [free
+    /// variables](Self::free_vars), or `None` if the system is
+    /// inconsistent.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of values does not match the number of free
+    /// variables.
+    #[must_use]
+    pub fn solve_with(&self, free_values: &[Rational]) -> Option<Vec<Rational>> {
+        if !self.is_consistent() {
+            return None;
+        }
+        let free_vars = self.free_vars();
+        assert_eq!(
+            free_values.len(),
+            free_vars.len(),
+            "wrong number of free values"
+        );
+
+        let mut solution = vec![Rational::zero(); self.num_vars];
+        for (&var, &value) in free_vars.iter().zip(free_values) {
+            solution[var] = value;
+        }
+        for (row, &pivot) in self.rows.iter().zip(&self.pivots) {
+            solution[pivot] = free_vars.iter().fold(row[self.num_vars], |acc, &var| {
+                acc - row[var] * solution[var]
+            });
+        }
+        Some(solution)
+    }
+
+    /// A basis of the nullspace of `A`, with one vector per free variable.
+    #[must_use]
+    pub fn nullspace(&self) -> Vec<Vec<Rational>> {
+        self.free_vars()
+            .into_iter()
+            .map(|free| {
+                let mut vector = vec![Rational::zero(); self.num_vars];
+                vector[free] = Rational::from_integer(1);
+                for (row, &pivot) in self.rows.iter().zip(&self.pivots) {
+                    vector[pivot] = -row[free];
+                }
+                vector
+            })
+            .collect()
+    }
+
+    /// All integer solutions with each free variable within its bounds,
+    /// given in the order of [`free_vars`](Self::free_vars).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of bounds does not match the number of free
+    /// variables.
+    pub fn integer_solutions(
+        &self,
+        bounds: &[RangeInclusive<i64>],
+    ) -> impl Iterator<Item = Vec<i64>> {
+        assert_eq!(
+            bounds.len(),
+            self.free_vars().len(),
+            "wrong number of bounds"
+        );
+        let empty = !self.is_consistent() || bounds.iter().any(RangeInclusive::is_empty);
+        let mut next = (!empty).then(|| bounds.iter().map(|range| *range.start()).collect());
+
+        std::iter::from_fn(move || {
+            loop {
+                let values: Vec<i64> = next.take()?;
+                next = increment(&values, bounds);
+
+                let free_values: Vec<_> = values
+                    .iter()
+                    .map(|&x| Rational::from_integer(x.into()))
+                    .collect();
+                let solution = self.solve_with(&free_values)?;
+                if let Some(solution) = to_integers(&solution) {
+                    return Some(solution);
+                }
+            }
+        })
+    }
+}
+
+/// Advances `values` like an odometer, returning `None` after the last
+/// combination.
+fn increment(values: &[i64], bounds: &[RangeInclusive<i64>]) -> Option<Vec<i64>> {
+    let mut values = values.to_vec();
+    for (value, range) in values.iter_mut().zip(bounds) {
+        if *value < *range.end() {
+            *value += 1;
+            return Some(values);
+        }
+        *value = *range.start();
+    }
+    None
+}
+
+/// Converts all values to integers, if they are integers that fit an `i64`.
+#[must_use]
+pub fn to_integers(values: &[Rational]) -> Option<Vec<i64>> {
+    values
+        .iter()
+        .map(|x| {
+            x.is_integer()
+                .then(|| x.to_integer())
+                .and_then(|x| i64::try_from(x).ok())
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn r(numer: i128, denom: i128) -> Rational {
+        Rational::new(numer, denom)
+    }
+
+    #[test]
+    fn unique_solution() {
+        let system = LinearSystem::new(&[[2, 1, -1], [-3, -1, 2], [-2, 1, 2]], &[8, -11, -3]);
+        assert_eq!(system.rank(), 3);
+        assert!(system.free_vars().is_empty());
+        let solution = system.solution().unwrap();
+        assert_eq!(to_integers(&solution), Some(vec![2, 3, -1]));
+
+        let system = LinearSystem::new(&[[2, 0], [0, 3]], &[1, 1]);
+        assert_eq!(system.solution(), Some(vec![r(1, 2), r(1, 3)]));
+        assert_eq!(to_integers(&system.solution().unwrap()), None);
+    }
+
+    #[test]
+    fn inconsistent() {
+        let system = LinearSystem::new(&[[1, 1], [2, 2]], &[1, 3]);
+        assert_eq!(system.rank(), 1);
+        assert!(!system.is_consistent());
+        assert_eq!(system.solution(), None);
+        assert_eq!(system.integer_solutions(&[0..=10]).count(), 0);
+    }
+
+    #[test]
+    fn underdetermined() {
+        // x + 2y + z = 4, y - z = 1
+        let system = LinearSystem::new(&[[1, 2, 1], [0, 1, -1]], &[4, 1]);
+        assert_eq!(system.rank(), 2);
+        assert_eq!(system.free_vars(), [2]);
+        assert_eq!(system.solution(), None);
+
+        let nullspace = system.nullspace();
+        assert_eq!(nullspace, [vec![r(-3, 1), r(1, 1), r(1, 1)]]);
+        assert_eq!(
+            system.solve_with(&[r(1, 1)]),
+            Some(vec![r(-1, 1), r(2, 1), r(1, 1)])
+        );
+
+        let solutions: Vec<_> = system.integer_solutions(&[0..=2]).collect();
+        assert_eq!(solutions, [vec![2, 1, 0], vec![-1, 2, 1], vec![-4, 3, 2]]);
+    }
+
+    #[test]
+    fn integer_solutions_filter_fractions() {
+        // 2x = y, so x is only an integer for even y
+        let system = LinearSystem::new(&[[2, -1]], &[0]);
+        assert_eq!(system.free_vars(), [1]);
+        let solutions: Vec<_> = system.integer_solutions(&[0..=5]).collect();
+        assert_eq!(solutions, [vec![0, 0], vec![1, 2], vec![2, 4]]);
+    }
+}
@@ -1,27 +1,159 @@
 #![allow(clippy::unreadable_literal, clippy::many_single_char_names)]
+#![expect(
+    clippy::inline_always,
+    reason = "the lane functions must be inlined into the AVX2 variant to be compiled with it"
+)]
 use std::{
-    array,
+    array, fmt,
     ops::{Deref, DerefMut},
 };
 
-pub type Digest = [u32; 4];
+use crate::block_buffer::{self, BlockBuffer, Endian};
 
+mod lanes;
+
+pub use lanes::{digest_batch, digest_lanes};
+
+/// An MD5 digest.
+///
+/// The words are stored big-endian, so the first word holds the first
+/// eight hex digits of the usual textual representation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Digest([u32; 4]);
+
+impl Digest {
+    #[must_use]
+    pub fn words(self) -> [u32; 4] {
+        self.0
+    }
+
+    #[must_use]
+    pub fn to_bytes(self) -> [u8; 16] {
+        self.to_u128().to_be_bytes()
+    }
+
+    #[must_use]
+    pub fn to_u128(self) -> u128 {
+        self.0
+            .into_iter()
+            .fold(0, |acc, word| acc << 32 | u128::from(word))
+    }
+
+    /// Returns the `i`-th hex digit, starting with the most significant one.
+    #[must_use]
+    pub fn nibble(self, i: usize) -> u8 {
+        (self.0[i / 8] >> (28 - i % 8 * 4) & 0xF) as u8
+    }
+
+    /// Iterates over all 32 hex digits, starting with the most significant
+    /// one.
+    pub fn nibbles(self) -> impl Iterator<Item = u8> {
+        (0..32).map(move |i| self.nibble(i))
+    }
+
+    /// Writes the lowercase hex representation into `buffer`, e.g. to hash
+    /// it again.
+    pub fn write_hex(self, buffer: &mut [u8; 32]) {
+        for (i, byte) in buffer.iter_mut().enumerate() {
+            *byte = hex_digit(self.nibble(i));
+        }
+    }
+
+    #[must_use]
+    pub fn to_hex(self) -> String {
+        format!("{self:x}")
+    }
+}
+
+impl From<Digest> for u128 {
+    fn from(digest: Digest) -> Self {
+        digest.to_u128()
+    }
+}
+
+impl fmt::LowerHex for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:032x}", self.to_u128())
+    }
+}
+
+impl fmt::Display for Digest {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(self, f)
+    }
+}
+
+/// Converts a value below 16 to its lowercase ASCII hex digit.
+#[must_use]
+pub fn hex_digit(nibble: u8) -> u8 {
+    b"0123456789abcdef"[usize::from(nibble)]
+}
+
+/// Streaming MD5 hasher for inputs of any length.
+#[derive(Debug, Clone)]
+pub struct Md5 {
+    state: State,
+    buffer: BlockBuffer,
+}
+
+impl Md5 {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::resume(INIT, 0)
+    }
+
+    /// Hashes `data` in one go.
+    #[must_use]
+    pub fn digest(data: impl AsRef<[u8]>) -> Digest {
+        let mut md5 = Self::new();
+        md5.update(data);
+        md5.finalize()
+    }
+
+    /// Continues hashing after `len` bytes, a multiple of the block size,
+    /// were compressed into `state`.
+    fn resume(state: State, len: usize) -> Self {
+        Self {
+            state,
+            buffer: BlockBuffer::new(len),
+        }
+    }
+
+    pub fn update(&mut self, data: impl AsRef<[u8]>) {
+        let state = &mut self.state;
+        self.buffer
+            .update(data.as_ref(), |block| *state = hash_block(block, *state));
+    }
+
+    #[must_use]
+    pub fn finalize(mut self) -> Digest {
+        let state = &mut self.state;
+        self.buffer
+            .finalize(Endian::Little, |block| *state = hash_block(block, *state));
+        to_digest(self.state)
+    }
+}
+
+impl Default for Md5 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A message of less than 56 bytes, which fits into a single block
+/// together with the padding.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct SingleBlock([u8; 64]);
 
 impl SingleBlock {
     #[must_use]
     pub fn new(len: usize) -> Self {
-        assert!(len < 56);
-        let mut buffer = [0; 64];
-        buffer[len] = 0x80;
-        write_len(buffer.last_chunk_mut().unwrap(), len);
-        Self(buffer)
+        Self(block_buffer::single_block(len, Endian::Little))
     }
 
     #[must_use]
     pub fn digest(&self) -> Digest {
-        digest_to_be(hash_block(&self.0, INIT))
+        to_digest(hash_block(&self.0, INIT))
     }
 }
 
@@ -39,10 +171,12 @@ impl DerefMut for SingleBlock {
     }
 }
 
+/// A message that is modified at its end only, e.g. during a DFS, which
+/// caches the state after every full block.
 #[derive(Debug, Clone, Default)]
 pub struct Stack {
     bytes: Vec<u8>,
-    prefix_digests: Vec<Digest>,
+    prefix_states: Vec<State>,
 }
 
 impl Stack {
@@ -59,9 +193,9 @@ impl Stack {
     pub fn push(&mut self, byte: u8) {
         self.bytes.push(byte);
         if self.bytes.len().is_multiple_of(64) {
-            let digest = self.prefix_digests.last().copied().unwrap_or(INIT);
-            let digest = hash_block(self.bytes.last_chunk().unwrap(), digest);
-            self.prefix_digests.push(digest);
+            let state = self.prefix_states.last().copied().unwrap_or(INIT);
+            let state = hash_block(self.bytes.last_chunk().unwrap(), state);
+            self.prefix_states.push(state);
         }
     }
 
@@ -73,34 +207,25 @@ impl Stack {
 
     pub fn pop(&mut self) {
         if self.bytes.len().is_multiple_of(64) {
-            self.prefix_digests.pop();
+            self.prefix_states.pop();
         }
         self.bytes.pop();
     }
 
-    pub fn digest(&mut self) -> Digest {
-        let original_len = self.bytes.len();
-        self.bytes.push(0x80);
-        self.bytes
-            .resize((self.bytes.len() + 8).next_multiple_of(64), 0);
-        write_len(self.bytes.last_chunk_mut().unwrap(), original_len);
-
-        let mut digest = self.prefix_digests.last().copied().unwrap_or(INIT);
-        let start_block = self.prefix_digests.len() * 64;
-        digest = hash_block(self.bytes[start_block..].first_chunk().unwrap(), digest);
-        if self.bytes.len() > start_block + 64 {
-            digest = hash_block(
-                self.bytes[start_block + 64..].first_chunk().unwrap(),
-                digest,
-            );
-        }
-
-        self.bytes.truncate(original_len);
-        digest_to_be(digest)
+    #[must_use]
+    pub fn digest(&self) -> Digest {
+        let state = self.prefix_states.last().copied().unwrap_or(INIT);
+        let start = self.prefix_states.len() * 64;
+        let mut md5 = Md5::resume(state, start);
+        md5.update(&self.bytes[start..]);
+        md5.finalize()
     }
 }
 
-const INIT: Digest = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+/// The intermediate state between blocks, in little-endian words.
+type State = [u32; 4];
+
+const INIT: State = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
 const K: [u32; 64] = [
     0xd76aa478, 0xe8c7b756, 0x242070db, 0xc1bdceee, 0xf57c0faf, 0x4787c62a, 0xa8304613, 0xfd469501,
     0x698098d8, 0x8b44f7af, 0xffff5bb1, 0x895cd7be, 0x6b901122, 0xfd987193, 0xa679438e, 0x49b40821,
@@ -140,24 +265,110 @@ macro_rules! round16 {
     };
 }
 
-fn digest_to_be(digest: Digest) -> Digest {
-    digest.map(u32::to_be)
+fn to_digest(state: State) -> Digest {
+    Digest(state.map(u32::swap_bytes))
+}
+
+fn read_word(buffer: &[u8; 64], i: usize) -> u32 {
+    u32::from_le_bytes(*buffer[i * 4..].first_chunk().unwrap())
+}
+
+fn hash_block(buffer: &[u8; 64], state: State) -> State {
+    compress(array::from_fn(|i| read_word(buffer, i)), state)
 }
 
-fn write_len(bytes: &mut [u8; 8], num_bytes: usize) {
-    *bytes = (num_bytes * 8).to_le_bytes();
+/// A 32-bit word, or several of them processed in lockstep.
+///
+/// Vector types cannot implement the operator traits because of the orphan
+/// rule, hence the named methods.
+trait Word: Copy {
+    fn splat(x: u32) -> Self;
+    fn add(self, rhs: Self) -> Self;
+    fn and(self, rhs: Self) -> Self;
+    fn or(self, rhs: Self) -> Self;
+    fn xor(self, rhs: Self) -> Self;
+    fn not(self) -> Self;
+    fn rotate(self, s: u32) -> Self;
+}
+
+impl Word for u32 {
+    #[inline(always)]
+    fn splat(x: u32) -> Self {
+        x
+    }
+
+    #[inline(always)]
+    fn add(self, rhs: Self) -> Self {
+        self.wrapping_add(rhs)
+    }
+
+    #[inline(always)]
+    fn and(self, rhs: Self) -> Self {
+        self & rhs
+    }
+
+    #[inline(always)]
+    fn or(self, rhs: Self) -> Self {
+        self | rhs
+    }
+
+    #[inline(always)]
+    fn xor(self, rhs: Self) -> Self {
+        self ^ rhs
+    }
+
+    #[inline(always)]
+    fn not(self) -> Self {
+        !self
+    }
+
+    #[inline(always)]
+    fn rotate(self, s: u32) -> Self {
+        self.rotate_left(s)
+    }
 }
 
-fn hash_block(buffer: &[u8; 64], digest: Digest) -> Digest {
-    let [mut a, mut b, mut c, mut d] = digest;
-    let m: [_; 16] = array::from_fn(|i| {
-        u32::from_le_bytes([
-            buffer[i * 4],
-            buffer[i * 4 + 1],
-            buffer[i * 4 + 2],
-            buffer[i * 4 + 3],
-        ])
-    });
+/// Several words processed in an interleaved fashion.
+impl<W: Word, const N: usize> Word for [W; N] {
+    #[inline(always)]
+    fn splat(x: u32) -> Self {
+        [W::splat(x); N]
+    }
+
+    #[inline(always)]
+    fn add(self, rhs: Self) -> Self {
+        array::from_fn(|i| self[i].add(rhs[i]))
+    }
+
+    #[inline(always)]
+    fn and(self, rhs: Self) -> Self {
+        array::from_fn(|i| self[i].and(rhs[i]))
+    }
+
+    #[inline(always)]
+    fn or(self, rhs: Self) -> Self {
+        array::from_fn(|i| self[i].or(rhs[i]))
+    }
+
+    #[inline(always)]
+    fn xor(self, rhs: Self) -> Self {
+        array::from_fn(|i| self[i].xor(rhs[i]))
+    }
+
+    #[inline(always)]
+    fn not(self) -> Self {
+        self.map(W::not)
+    }
+
+    #[inline(always)]
+    fn rotate(self, s: u32) -> Self {
+        self.map(|x| x.rotate(s))
+    }
+}
+
+#[inline(always)]
+fn compress<W: Word>(m: [W; 16], state: [W; 4]) -> [W; 4] {
+    let [mut a, mut b, mut c, mut d] = state;
 
     round16!(round1, 0, a, b, c, d, m, I, S, K);
     round16!(round2, 16, a, b, c, d, m, I, S, K);
@@ -165,63 +376,119 @@ fn hash_block(buffer: &[u8; 64], digest: Digest) -> Digest {
     round16!(round4, 48, a, b, c, d, m, I, S, K);
 
     [
-        a.wrapping_add(digest[0]),
-        b.wrapping_add(digest[1]),
-        c.wrapping_add(digest[2]),
-        d.wrapping_add(digest[3]),
+        a.add(state[0]),
+        b.add(state[1]),
+        c.add(state[2]),
+        d.add(state[3]),
     ]
 }
 
-fn round1(a: u32, b: u32, c: u32, d: u32, m: u32, s: u32, k: u32) -> u32 {
-    let f = (b & c) | (!b & d);
+#[inline(always)]
+fn round1<W: Word>(a: W, b: W, c: W, d: W, m: W, s: u32, k: u32) -> W {
+    let f = b.and(c).or(b.not().and(d));
     common(f, a, b, m, s, k)
 }
 
-fn round2(a: u32, b: u32, c: u32, d: u32, m: u32, s: u32, k: u32) -> u32 {
-    let f = (b & d) | (c & !d);
+#[inline(always)]
+fn round2<W: Word>(a: W, b: W, c: W, d: W, m: W, s: u32, k: u32) -> W {
+    let f = b.and(d).or(c.and(d.not()));
     common(f, a, b, m, s, k)
 }
 
-fn round3(a: u32, b: u32, c: u32, d: u32, m: u32, s: u32, k: u32) -> u32 {
-    let f = b ^ c ^ d;
+#[inline(always)]
+fn round3<W: Word>(a: W, b: W, c: W, d: W, m: W, s: u32, k: u32) -> W {
+    let f = b.xor(c).xor(d);
     common(f, a, b, m, s, k)
 }
 
-fn round4(a: u32, b: u32, c: u32, d: u32, m: u32, s: u32, k: u32) -> u32 {
-    let f = c ^ (b | !d);
+#[inline(always)]
+fn round4<W: Word>(a: W, b: W, c: W, d: W, m: W, s: u32, k: u32) -> W {
+    let f = c.xor(b.or(d.not()));
     common(f, a, b, m, s, k)
 }
 
-fn common(f: u32, a: u32, b: u32, m: u32, s: u32, k: u32) -> u32 {
-    f.wrapping_add(a)
-        .wrapping_add(k)
-        .wrapping_add(m)
-        .rotate_left(s)
-        .wrapping_add(b)
+#[inline(always)]
+fn common<W: Word>(f: W, a: W, b: W, m: W, s: u32, k: u32) -> W {
+    f.add(a).add(W::splat(k)).add(m).rotate(s).add(b)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    fn to_u128(digest: Digest) -> u128 {
-        u128::from(digest[0]) << 96
-            | u128::from(digest[1]) << 64
-            | u128::from(digest[2]) << 32
-            | u128::from(digest[3])
+    pub(super) const RFC_1321: [(&str, u128); 7] = [
+        ("", 0xd41d8cd98f00b204e9800998ecf8427e),
+        ("a", 0x0cc175b9c0f1b6a831c399e269772661),
+        ("abc", 0x900150983cd24fb0d6963f7d28e17f72),
+        ("message digest", 0xf96b697d7cb7938d525a2f31aaf161d0),
+        (
+            "abcdefghijklmnopqrstuvwxyz",
+            0xc3fcd3d76192e4007dfb496cca67e13b,
+        ),
+        (
+            "ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789",
+            0xd174ab98d277d9f5a5611c2c9f419d9f,
+        ),
+        (
+            "12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+            0x57edf4a22be3c955ac49da2e2107b67a,
+        ),
+    ];
+
+    #[test]
+    fn streaming() {
+        for (input, expected) in RFC_1321 {
+            assert_eq!(
+                Md5::digest(input).to_u128(),
+                expected,
+                "failed for {input:?}"
+            );
+
+            for split in [1, 7, 55, 56, 63, 64, 65] {
+                let mut md5 = Md5::new();
+                for chunk in input.as_bytes().chunks(split) {
+                    md5.update(chunk);
+                }
+                assert_eq!(md5.finalize().to_u128(), expected, "failed for {input:?}");
+            }
+        }
+
+        let long = "a".repeat(1000);
+        assert_eq!(Md5::digest(&long), Md5::digest(long.as_bytes()));
+        assert_eq!(
+            Md5::digest(long).to_u128(),
+            0xcabe45dcc9ae5b66ba86600cca6b8ba8
+        );
+    }
+
+    #[test]
+    fn digest() {
+        let digest = Md5::digest("abc");
+        assert_eq!(digest.to_hex(), "900150983cd24fb0d6963f7d28e17f72");
+        assert_eq!(u128::from(digest), 0x900150983cd24fb0d6963f7d28e17f72);
+        assert_eq!(digest.words()[0], 0x90015098);
+        assert_eq!(digest.to_bytes()[..3], [0x90, 0x01, 0x50]);
+        assert_eq!(
+            (digest.nibble(0), digest.nibble(3), digest.nibble(31)),
+            (9, 1, 2)
+        );
+        assert_eq!(digest.nibbles().count(), 32);
+
+        let mut hex = [0; 32];
+        digest.write_hex(&mut hex);
+        assert_eq!(hex, *digest.to_string().as_bytes());
+        assert_eq!(
+            format!("{:x}", Md5::digest("")),
+            "d41d8cd98f00b204e9800998ecf8427e"
+        );
     }
 
     #[test]
     fn single_block() {
-        let cases = [
-            ("", 0xd41d8cd98f00b204e9800998ecf8427e),
-            ("abc", 0x900150983cd24fb0d6963f7d28e17f72),
-        ];
-        for (input, expected) in cases {
+        for (input, expected) in &RFC_1321[..5] {
             let mut block = SingleBlock::new(input.len());
             block[..input.len()].copy_from_slice(input.as_bytes());
-            let actual = block.digest();
-            assert_eq!(expected, to_u128(actual), "failed for {input:?}");
+            assert_eq!(block.digest().to_u128(), *expected, "failed for {input:?}");
         }
     }
 
@@ -230,21 +497,38 @@ mod tests {
         const A64: &[u8] = &[b'a'; 64];
 
         let mut stack = Stack::new();
-        assert_eq!(0xd41d8cd98f00b204e9800998ecf8427e, to_u128(stack.digest()));
+        assert_eq!(0xd41d8cd98f00b204e9800998ecf8427e, stack.digest().to_u128());
 
         stack.push_slice(A64);
-        assert_eq!(0x014842d480b571495a4a0363793f7367, to_u128(stack.digest()));
+        assert_eq!(0x014842d480b571495a4a0363793f7367, stack.digest().to_u128());
 
         stack.pop();
-        assert_eq!(0xb06521f39153d618550606be297466d5, to_u128(stack.digest()));
+        assert_eq!(0xb06521f39153d618550606be297466d5, stack.digest().to_u128());
 
         stack.push(b'a');
-        assert_eq!(0x014842d480b571495a4a0363793f7367, to_u128(stack.digest()));
+        assert_eq!(0x014842d480b571495a4a0363793f7367, stack.digest().to_u128());
 
         stack.push(b'a');
-        assert_eq!(0xc743a45e0d2e6a95cb859adae0248435, to_u128(stack.digest()));
+        assert_eq!(0xc743a45e0d2e6a95cb859adae0248435, stack.digest().to_u128());
 
         stack.push_slice(A64);
-        assert_eq!(0xb325dc1c6f5e7a2b7cf465b9feab7948, to_u128(stack.digest()));
+        assert_eq!(0xb325dc1c6f5e7a2b7cf465b9feab7948, stack.digest().to_u128());
+    }
+
+    #[test]
+    fn stack_matches_streaming() {
+        let mut stack = Stack::new();
+        let check = |stack: &Stack| assert_eq!(stack.digest(), Md5::digest(stack.bytes()));
+        check(&stack);
+        for len in 1..=130 {
+            stack.push(b'a' + len as u8 % 26);
+            check(&stack);
+        }
+        for _ in 0..70 {
+            stack.pop();
+            check(&stack);
+        }
+        stack.push_slice(&[b'x'; 64]);
+        check(&stack);
     }
 }
@@ -0,0 +1,372 @@
+//! Hashing several independent blocks at once, one per lane of a vector.
+
+#[cfg(target_arch = "x86_64")]
+use std::arch::x86_64::{
+    __m128i, __m256i, _mm_add_epi32, _mm_and_si128, _mm_cvtsi32_si128, _mm_loadu_si128,
+    _mm_or_si128, _mm_set1_epi32, _mm_sll_epi32, _mm_srl_epi32, _mm_storeu_si128, _mm_xor_si128,
+    _mm256_add_epi32, _mm256_and_si256, _mm256_loadu_si256, _mm256_or_si256, _mm256_set1_epi32,
+    _mm256_sll_epi32, _mm256_srl_epi32, _mm256_storeu_si256, _mm256_xor_si256,
+};
+use std::array;
+
+use super::{Digest, INIT, SingleBlock, Word, compress, read_word};
+
+const MAX_LANES: usize = 16;
+
+/// Hashes `L` independent blocks at once, where `L` must be 4, 8 or 16.
+///
+/// On `x86_64`, the lanes are mapped to SSE2 or, if the CPU supports it,
+/// AVX2 vectors. Elsewhere, the blocks are hashed with interleaved scalar
+/// code.
+#[must_use]
+pub fn digest_lanes<const L: usize>(blocks: &[SingleBlock; L]) -> [Digest; L] {
+    const {
+        assert!(
+            matches!(L, 4 | 8 | 16),
+            "only 4, 8 or 16 lanes are supported"
+        );
+    };
+
+    let mut digests = [Digest::default(); L];
+    #[cfg(target_arch = "x86_64")]
+    if L >= 8 && std::arch::is_x86_feature_detected!("avx2") {
+        // SAFETY: We just checked that the CPU supports AVX2.
+        unsafe { digest_avx2(blocks, &mut digests) };
+    } else {
+        digest_sse2(blocks, &mut digests);
+    }
+    #[cfg(not(target_arch = "x86_64"))]
+    digest_words::<u32, L>(blocks, &mut digests);
+
+    digests
+}
+
+/// Hashes any number of blocks, using [`digest_lanes`] for all but the last
+/// few.
+#[must_use]
+pub fn digest_batch(blocks: &[SingleBlock]) -> Vec<Digest> {
+    let mut digests = Vec::with_capacity(blocks.len());
+    let (chunks, remainder) = blocks.as_chunks::<16>();
+    for chunk in chunks {
+        digests.extend(digest_lanes(chunk));
+    }
+    let (chunks, remainder) = remainder.as_chunks::<4>();
+    for chunk in chunks {
+        digests.extend(digest_lanes(chunk));
+    }
+    digests.extend(remainder.iter().map(SingleBlock::digest));
+    digests
+}
+
+#[cfg(target_arch = "x86_64")]
+fn digest_sse2(blocks: &[SingleBlock], digests: &mut [Digest]) {
+    match blocks.len() {
+        4 => digest_words::<__m128i, 1>(blocks, digests),
+        8 => digest_words::<__m128i, 2>(blocks, digests),
+        16 => digest_words::<__m128i, 4>(blocks, digests),
+        _ => unreachable!(),
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+#[target_feature(enable = "avx2")]
+fn digest_avx2(blocks: &[SingleBlock], digests: &mut [Digest]) {
+    match blocks.len() {
+        8 => digest_words::<__m256i, 1>(blocks, digests),
+        16 => digest_words::<__m256i, 2>(blocks, digests),
+        _ => unreachable!(),
+    }
+}
+
+/// Hashes `N * V::LANES` blocks with `N` interleaved vectors per word.
+#[inline(always)]
+fn digest_words<V: Vector, const N: usize>(blocks: &[SingleBlock], digests: &mut [Digest]) {
+    assert_eq!(blocks.len(), N * V::LANES);
+    assert_eq!(digests.len(), blocks.len());
+
+    let mut words = [0; MAX_LANES];
+    let m: [[V; N]; 16] = array::from_fn(|i| {
+        for (word, block) in words.iter_mut().zip(blocks) {
+            *word = read_word(block, i);
+        }
+        array::from_fn(|j| V::load(&words[j * V::LANES..]))
+    });
+
+    let state = compress(m, INIT.map(<[V; N]>::splat));
+    for (i, vectors) in state.into_iter().enumerate() {
+        for (j, vector) in vectors.into_iter().enumerate() {
+            vector.store(&mut words[j * V::LANES..]);
+        }
+        for (digest, word) in digests.iter_mut().zip(words) {
+            digest.0[i] = word.swap_bytes();
+        }
+    }
+}
+
+/// A word holding `LANES` independent 32-bit values.
+trait Vector: Word {
+    const LANES: usize;
+
+    /// Loads the first `LANES` values from `words`.
+    fn load(words: &[u32]) -> Self;
+
+    /// Stores the values into the first `LANES` elements of `words`.
+    fn store(self, words: &mut [u32]);
+}
+
+#[cfg(any(test, not(target_arch = "x86_64")))]
+impl Vector for u32 {
+    const LANES: usize = 1;
+
+    #[inline(always)]
+    fn load(words: &[u32]) -> Self {
+        words[0]
+    }
+
+    #[inline(always)]
+    fn store(self, words: &mut [u32]) {
+        words[0] = self;
+    }
+}
+
+// SAFETY (for all of the SSE2 intrinsics below): SSE2 is part of the
+// `x86_64` baseline.
+#[cfg(target_arch = "x86_64")]
+impl Word for __m128i {
+    #[inline(always)]
+    fn splat(x: u32) -> Self {
+        unsafe { _mm_set1_epi32(x as i32) }
+    }
+
+    #[inline(always)]
+    fn add(self, rhs: Self) -> Self {
+        unsafe { _mm_add_epi32(self, rhs) }
+    }
+
+    #[inline(always)]
+    fn and(self, rhs: Self) -> Self {
+        unsafe { _mm_and_si128(self, rhs) }
+    }
+
+    #[inline(always)]
+    fn or(self, rhs: Self) -> Self {
+        unsafe { _mm_or_si128(self, rhs) }
+    }
+
+    #[inline(always)]
+    fn xor(self, rhs: Self) -> Self {
+        unsafe { _mm_xor_si128(self, rhs) }
+    }
+
+    #[inline(always)]
+    fn not(self) -> Self {
+        unsafe { _mm_xor_si128(self, _mm_set1_epi32(-1)) }
+    }
+
+    #[inline(always)]
+    fn rotate(self, s: u32) -> Self {
+        unsafe {
+            let left = _mm_sll_epi32(self, _mm_cvtsi32_si128(s as i32));
+            let right = _mm_srl_epi32(self, _mm_cvtsi32_si128(32 - s as i32));
+            _mm_or_si128(left, right)
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+impl Vector for __m128i {
+    const LANES: usize = 4;
+
+    #[inline(always)]
+    fn load(words: &[u32]) -> Self {
+        let words: &[u32; 4] = words.first_chunk().unwrap();
+        // SAFETY: `words` is valid for reading 16 bytes, and the load does
+        // not require alignment.
+        unsafe { _mm_loadu_si128(words.as_ptr().cast()) }
+    }
+
+    #[inline(always)]
+    fn store(self, words: &mut [u32]) {
+        let words: &mut [u32; 4] = words.first_chunk_mut().unwrap();
+        // SAFETY: `words` is valid for writing 16 bytes, and the store does
+        // not require alignment.
+        unsafe { _mm_storeu_si128(words.as_mut_ptr().cast(), self) };
+    }
+}
+
+// SAFETY (for all of the AVX2 intrinsics below): `__m256i` words are only
+// used by `digest_avx2`, which is only called if the CPU supports AVX2.
+#[cfg(target_arch = "x86_64")]
+impl Word for __m256i {
+    #[inline(always)]
+    fn splat(x: u32) -> Self {
+        unsafe { _mm256_set1_epi32(x as i32) }
+    }
+
+    #[inline(always)]
+    fn add(self, rhs: Self) -> Self {
+        unsafe { _mm256_add_epi32(self, rhs) }
+    }
+
+    #[inline(always)]
+    fn and(self, rhs: Self) -> Self {
+        unsafe { _mm256_and_si256(self, rhs) }
+    }
+
+    #[inline(always)]
+    fn or(self, rhs: Self) -> Self {
+        unsafe { _mm256_or_si256(self, rhs) }
+    }
+
+    #[inline(always)]
+    fn xor(self, rhs: Self) -> Self {
+        unsafe { _mm256_xor_si256(self, rhs) }
+    }
+
+    #[inline(always)]
+    fn not(self) -> Self {
+        unsafe { _mm256_xor_si256(self, _mm256_set1_epi32(-1)) }
+    }
+
+    #[inline(always)]
+    fn rotate(self, s: u32) -> Self {
+        unsafe {
+            let left = _mm256_sll_epi32(self, _mm_cvtsi32_si128(s as i32));
+            let right = _mm256_srl_epi32(self, _mm_cvtsi32_si128(32 - s as i32));
+            _mm256_or_si256(left, right)
+        }
+    }
+}
+
+#[cfg(target_arch = "x86_64")]
+impl Vector for __m256i {
+    const LANES: usize = 8;
+
+    #[inline(always)]
+    fn load(words: &[u32]) -> Self {
+        let words: &[u32; 8] = words.first_chunk().unwrap();
+        // SAFETY: `words` is valid for reading 32 bytes, and the load does
+        // not require alignment.
+        unsafe { _mm256_loadu_si256(words.as_ptr().cast()) }
+    }
+
+    #[inline(always)]
+    fn store(self, words: &mut [u32]) {
+        let words: &mut [u32; 8] = words.first_chunk_mut().unwrap();
+        // SAFETY: `words` is valid for writing 32 bytes, and the store does
+        // not require alignment.
+        unsafe { _mm256_storeu_si256(words.as_mut_ptr().cast(), self) };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        md5::{Md5, tests::RFC_1321},
+        rng::Rng,
+    };
+
+    fn random_blocks<const L: usize>(rng: &mut Rng) -> [SingleBlock; L] {
+        array::from_fn(|_| {
+            let len = rng.index(56);
+            let mut block = SingleBlock::new(len);
+            for byte in &mut block[..len] {
+                *byte = rng.next_u64() as u8;
+            }
+            block
+        })
+    }
+
+    fn check_lanes<const L: usize>(rng: &mut Rng) {
+        let blocks = random_blocks::<L>(rng);
+        let expected = blocks.map(|block| block.digest());
+        assert_eq!(digest_lanes(&blocks), expected);
+
+        let mut digests = [Digest::default(); L];
+        digest_words::<u32, L>(&blocks, &mut digests);
+        assert_eq!(digests, expected);
+    }
+
+    /// The vectors of `md5::tests` that fit into a single block.
+    const VECTORS: &[(&str, u128)] = RFC_1321.split_at(5).0;
+
+    /// Blocks with the known vectors, rotated by `shift` lanes.
+    fn known_blocks<const L: usize>(shift: usize) -> [(SingleBlock, u128); L] {
+        array::from_fn(|lane| {
+            let (input, expected) = VECTORS[(lane + shift) % VECTORS.len()];
+            let mut block = SingleBlock::new(input.len());
+            block[..input.len()].copy_from_slice(input.as_bytes());
+            (block, expected)
+        })
+    }
+
+    fn check_known<const L: usize>() {
+        // Rotating through at least L positions puts every vector in every lane
+        for shift in 0..L.max(VECTORS.len()) {
+            let (blocks, expected): (Vec<_>, Vec<_>) = known_blocks::<L>(shift).into_iter().unzip();
+            let blocks: [SingleBlock; L] = blocks.try_into().unwrap();
+
+            let digests = digest_lanes(&blocks).map(Digest::to_u128);
+            assert_eq!(digests[..], expected, "digest_lanes, shift {shift}");
+
+            let mut digests = [Digest::default(); L];
+            digest_words::<u32, L>(&blocks, &mut digests);
+            let digests = digests.map(Digest::to_u128);
+            assert_eq!(digests[..], expected, "digest_words, shift {shift}");
+
+            // `digest_lanes` only uses SSE2 for 8 and 16 lanes without AVX2
+            #[cfg(target_arch = "x86_64")]
+            {
+                let mut digests = [Digest::default(); L];
+                digest_sse2(&blocks, &mut digests);
+                let digests = digests.map(Digest::to_u128);
+                assert_eq!(digests[..], expected, "digest_sse2, shift {shift}");
+            }
+        }
+    }
+
+    #[test]
+    fn lanes_known() {
+        check_known::<4>();
+        check_known::<8>();
+        check_known::<16>();
+    }
+
+    #[test]
+    fn batch_known() {
+        // 16 + 4 + 3 blocks, covering every path through `digest_batch`
+        for shift in 0..VECTORS.len() {
+            let (blocks, expected): (Vec<_>, Vec<_>) =
+                known_blocks::<23>(shift).into_iter().unzip();
+            let digests: Vec<_> = digest_batch(&blocks)
+                .into_iter()
+                .map(Digest::to_u128)
+                .collect();
+            assert_eq!(digests, expected, "shift {shift}");
+        }
+    }
+
+    #[test]
+    fn lanes() {
+        let empty = Md5::digest("");
+        assert_eq!(digest_lanes(&[SingleBlock::new(0); 4]), [empty; 4]);
+
+        let mut rng = Rng::new(5);
+        for _ in 0..100 {
+            check_lanes::<4>(&mut rng);
+            check_lanes::<8>(&mut rng);
+            check_lanes::<16>(&mut rng);
+        }
+    }
+
+    #[test]
+    fn batch() {
+        let mut rng = Rng::new(7);
+        let blocks: Vec<_> = (0..5).flat_map(|_| random_blocks::<8>(&mut rng)).collect();
+        let expected: Vec<_> = blocks.iter().map(SingleBlock::digest).collect();
+        assert_eq!(digest_batch(&blocks), expected);
+        assert_eq!(digest_batch(&blocks[..7]), expected[..7]);
+        assert!(digest_batch(&[]).is_empty());
+    }
+}
@@ -1,6 +1,12 @@
-use std::iter;
+//! Bit tricks and number theory.
 
-use num::{PrimInt, one};
+use std::{
+    fmt::{self, Display, Formatter},
+    iter::{self, Product, Sum},
+    ops::{Add, AddAssign, Div, DivAssign, Mul, MulAssign, Neg, Sub, SubAssign},
+};
+
+use num::{Integer, PrimInt, integer::ExtendedGcd, one};
 
 pub fn bits(mut m: impl PrimInt) -> impl Iterator<Item = usize> {
     iter::from_fn(move || {
@@ -11,3 +17,341 @@ pub fn bits(mut m: impl PrimInt) -> impl Iterator<Item = usize> {
         })
     })
 }
+
+/// Least common multiple of all values, or one if there are none.
+pub fn lcm_all<T: Integer>(values: impl IntoIterator<Item = T>) -> T {
+    values
+        .into_iter()
+        .fold(T::one(), |lcm, value| lcm.lcm(&value))
+}
+
+/// Solves a system of congruences `x ≡ a (mod m)` given as `(a, m)` pairs,
+/// where the moduli need not be coprime.
+///
+/// Returns `(x, lcm)` with `0 <= x < lcm` of all moduli, so that the
+/// solutions are exactly `x + k * lcm`, or `None` if the system is
+/// inconsistent or `lcm` does not fit in an `i64`.
+pub fn crt(congruences: impl IntoIterator<Item = (i64, i64)>) -> Option<(i64, i64)> {
+    congruences.into_iter().try_fold((0, 1), |(a, m), (b, n)| {
+        assert!(n > 0, "modulus must be positive: {n}");
+        let b = b.rem_euclid(n);
+        let ExtendedGcd { gcd, x, .. } = m.extended_gcd(&n);
+        if (b - a) % gcd != 0 {
+            return None;
+        }
+
+        // a + m * k solves both when m * k ≡ b - a (mod n)
+        let lcm = (m / gcd).checked_mul(n)?;
+        let k = (i128::from((b - a) / gcd) * i128::from(x)).rem_euclid(i128::from(n / gcd));
+        let solution = (i128::from(a) + i128::from(m) * k).rem_euclid(i128::from(lcm));
+        Some((solution as i64, lcm))
+    })
+}
+
+/// The inverse of `a` modulo `m`, if `a` and `m` are coprime.
+#[must_use]
+pub fn mod_inverse(a: i64, m: i64) -> Option<i64> {
+    let ExtendedGcd { gcd, x, .. } = a.rem_euclid(m).extended_gcd(&m);
+    (gcd == 1).then(|| x.rem_euclid(m))
+}
+
+/// Computes `base^exp mod m` by repeated squaring.
+#[must_use]
+pub fn mod_pow(base: u64, mut exp: u64, m: u64) -> u64 {
+    let m = u128::from(m);
+    let mut base = u128::from(base) % m;
+    let mut result = 1 % m;
+    while exp > 0 {
+        if exp & 1 == 1 {
+            result = result * base % m;
+        }
+        base = base * base % m;
+        exp >>= 1;
+    }
+    result as u64
+}
+
+/// An integer modulo `M`, with `M` below `2^63`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ModInt<const M: u64>(u64);
+
+impl<const M: u64> ModInt<M> {
+    #[must_use]
+    pub const fn new(value: u64) -> Self {
+        Self(value % M)
+    }
+
+    #[must_use]
+    pub const fn value(self) -> u64 {
+        self.0
+    }
+
+    #[must_use]
+    pub fn pow(self, exp: u64) -> Self {
+        Self(mod_pow(self.0, exp, M))
+    }
+
+    /// The multiplicative inverse, if `self` is coprime to `M`.
+    #[must_use]
+    pub fn inverse(self) -> Option<Self> {
+        let inverse = mod_inverse(self.0 as i64, M as i64)?;
+        Some(Self(inverse as u64))
+    }
+}
+
+impl<const M: u64> From<u64> for ModInt<M> {
+    fn from(value: u64) -> Self {
+        Self::new(value)
+    }
+}
+
+impl<const M: u64> From<i64> for ModInt<M> {
+    fn from(value: i64) -> Self {
+        Self(value.rem_euclid(M as i64) as u64)
+    }
+}
+
+impl<const M: u64> Display for ModInt<M> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<const M: u64> Add for ModInt<M> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let sum = self.0 + rhs.0;
+        Self(if sum >= M { sum - M } else { sum })
+    }
+}
+
+impl<const M: u64> Sub for ModInt<M> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Self(if self.0 >= rhs.0 {
+            self.0 - rhs.0
+        } else {
+            self.0 + M - rhs.0
+        })
+    }
+}
+
+impl<const M: u64> Mul for ModInt<M> {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self((u128::from(self.0) * u128::from(rhs.0) % u128::from(M)) as u64)
+    }
+}
+
+impl<const M: u64> Div for ModInt<M> {
+    type Output = Self;
+
+    /// # Panics
+    ///
+    /// Panics if `rhs` has no inverse modulo `M`.
+    #[expect(
+        clippy::suspicious_arithmetic_impl,
+        reason = "multiplies by the inverse"
+    )]
+    fn div(self, rhs: Self) -> Self::Output {
+        self * rhs.inverse().expect("divisor should be invertible")
+    }
+}
+
+impl<const M: u64> Neg for ModInt<M> {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::default() - self
+    }
+}
+
+macro_rules! impl_assign_ops {
+    ($($trait:ident::$method:ident => $op:tt),*) => {
+        $(
+            impl<const M: u64> $trait for ModInt<M> {
+                fn $method(&mut self, rhs: Self) {
+                    *self = *self $op rhs;
+                }
+            }
+        )*
+    };
+}
+
+impl_assign_ops!(
+    AddAssign::add_assign => +,
+    SubAssign::sub_assign => -,
+    MulAssign::mul_assign => *,
+    DivAssign::div_assign => /
+);
+
+impl<const M: u64> Sum for ModInt<M> {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), Add::add)
+    }
+}
+
+impl<const M: u64> Product for ModInt<M> {
+    fn product<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::new(1), Mul::mul)
+    }
+}
+
+/// All primes up to and including `n`, using the sieve of Eratosthenes.
+#[must_use]
+pub fn primes_up_to(n: usize) -> Vec<usize> {
+    let mut is_prime = vec![true; n + 1];
+    let mut primes = Vec::new();
+    for i in 2..=n {
+        if is_prime[i] {
+            primes.push(i);
+            for j in (i * i..=n).step_by(i) {
+                is_prime[j] = false;
+            }
+        }
+    }
+    primes
+}
+
+/// Prime factorisation of `n` by trial division, as `(prime, exponent)`
+/// pairs in increasing order.
+#[must_use]
+pub fn factorize(mut n: u64) -> Vec<(u64, u32)> {
+    let mut factors = Vec::new();
+    for p in iter::once(2).chain((3..).step_by(2)) {
+        if p > n / p {
+            break;
+        }
+        let mut exponent = 0;
+        while n.is_multiple_of(p) {
+            n /= p;
+            exponent += 1;
+        }
+        if exponent > 0 {
+            factors.push((p, exponent));
+        }
+    }
+    if n > 1 {
+        factors.push((n, 1));
+    }
+    factors
+}
+
+/// Sum of all divisors of `n`, including `n` itself, or `None` if it does
+/// not fit in a `u64`.
+#[must_use]
+pub fn divisor_sum(n: u64) -> Option<u64> {
+    factorize(n)
+        .into_iter()
+        .try_fold(1_u64, |sum, (p, exponent)| {
+            // p^(exponent + 1) <= n * p, which fits in a u128
+            let p = u128::from(p);
+            let factor = (p.pow(exponent + 1) - 1) / (p - 1);
+            sum.checked_mul(u64::try_from(factor).ok()?)
+        })
+}
+
+/// Sums of the divisors of all numbers below `n`, computed by a sieve.
+#[must_use]
+pub fn divisor_sums(n: usize) -> Vec<u64> {
+    let mut sums = vec![0; n];
+    for d in 1..n {
+        for multiple in (d..n).step_by(d) {
+            sums[multiple] += d as u64;
+        }
+    }
+    sums
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bit_indices() {
+        assert_eq!(bits(0b1010_0110_u8).collect::<Vec<_>>(), [1, 2, 5, 7]);
+        assert_eq!(bits(0_u32).count(), 0);
+    }
+
+    #[test]
+    fn lcm() {
+        assert_eq!(lcm_all([4, 6, 10]), 60);
+        assert_eq!(lcm_all::<u32>([]), 1);
+    }
+
+    #[test]
+    fn chinese_remainder() {
+        assert_eq!(crt([(2, 3), (3, 5), (2, 7)]), Some((23, 105)));
+        // Non-coprime moduli
+        assert_eq!(crt([(2, 6), (8, 10)]), Some((8, 30)));
+        assert_eq!(crt([(1, 4), (2, 6)]), None);
+        assert_eq!(crt([(-1, 5), (13, 4)]), Some((9, 20)));
+        assert_eq!(crt([]), Some((0, 1)));
+
+        let big = 1_000_000_007;
+        assert_eq!(crt([(big - 1, big), (0, 2)]), Some((big - 1, 2 * big)));
+        assert_eq!(crt([(0, 1 << 40), (1, (1 << 40) + 1)]), None);
+    }
+
+    #[test]
+    fn modular() {
+        assert_eq!(mod_inverse(3, 11), Some(4));
+        assert_eq!(mod_inverse(-3, 11), Some(7));
+        assert_eq!(mod_inverse(4, 10), None);
+        assert_eq!(mod_pow(2, 10, 1000), 24);
+        assert_eq!(mod_pow(5, 0, 1), 0);
+        assert_eq!(mod_pow(u64::MAX, 2, u64::MAX - 1), 1);
+    }
+
+    #[test]
+    fn mod_int() {
+        type M7 = ModInt<7>;
+        let a = M7::new(5);
+        let b = M7::from(-4_i64);
+        assert_eq!(b.value(), 3);
+        assert_eq!((a + b).value(), 1);
+        assert_eq!((b - a).value(), 5);
+        assert_eq!((a * b).value(), 1);
+        assert_eq!((a / b).value(), 4);
+        assert_eq!((-a).value(), 2);
+        assert_eq!(a.pow(6), M7::new(1));
+        assert_eq!(M7::new(0).inverse(), None);
+        assert_eq!((1..7).map(M7::new).product::<M7>(), M7::new(6));
+        assert_eq!((1..7).map(M7::new).sum::<M7>().to_string(), "0");
+
+        let mut c = a;
+        c *= b;
+        c += a;
+        assert_eq!(c, M7::new(6));
+    }
+
+    #[test]
+    fn primes() {
+        assert_eq!(primes_up_to(30), [2, 3, 5, 7, 11, 13, 17, 19, 23, 29]);
+        assert_eq!(primes_up_to(1), []);
+        assert_eq!(primes_up_to(100).len(), 25);
+    }
+
+    #[test]
+    fn factorization() {
+        assert_eq!(factorize(1), []);
+        assert_eq!(factorize(360), [(2, 3), (3, 2), (5, 1)]);
+        assert_eq!(factorize(999_999_937), [(999_999_937, 1)]);
+        assert_eq!(factorize(2 * 999_983), [(2, 1), (999_983, 1)]);
+    }
+
+    #[test]
+    fn divisors() {
+        assert_eq!(divisor_sum(1), Some(1));
+        assert_eq!(divisor_sum(12), Some(1 + 2 + 3 + 4 + 6 + 12));
+        assert_eq!(divisor_sum(1 << 63), Some(u64::MAX));
+        assert_eq!(divisor_sum(3 << 62), None);
+        let sums = divisor_sums(100);
+        for (n, &sum) in sums.iter().enumerate().skip(1) {
+            assert_eq!(Some(sum), divisor_sum(n as u64), "n = {n}");
+        }
+    }
+}
@@ -1,32 +1,225 @@
-use anyhow::{Context, Result};
-
-#[allow(clippy::unreadable_literal)]
-const CHARACTERS: [(u32, u8); 20] = [
-    (0x19297a52, b'A'),
-    (0x392e4a5c, b'B'),
-    (0x1928424c, b'C'),
-    (0x39294a5c, b'D'),
-    (0x3d0e421e, b'E'),
-    (0x3d0e4210, b'F'),
-    (0x19285a4e, b'G'),
-    (0x252f4a52, b'H'),
-    (0x1c42108e, b'I'),
-    (0x0c210a4c, b'J'),
-    (0x254c5292, b'K'),
-    (0x2108421e, b'L'),
-    (0x19294a4c, b'O'),
-    (0x39297210, b'P'),
-    (0x39297292, b'R'),
-    (0x1d08305c, b'S'),
-    (0x1c421084, b'T'),
-    (0x25294a4c, b'U'),
-    (0x23151084, b'Y'),
-    (0x3c22221e, b'Z'),
-];
-
-pub fn character(bits: u32) -> Result<u8> {
-    CHARACTERS
-        .into_iter()
-        .find_map(|(c, ch)| (c == bits).then_some(ch))
-        .context("character not recognized")
+//! Recognition of the block letters some puzzles draw as their answer.
+//!
+//! Two fonts are known: letters 6 rows high in cells 5 columns wide, and
+//! letters 10 rows high in cells 8 columns wide. The font is chosen by the
+//! height of the grid after removing blank rows at the top and bottom. The
+//! first cell starts at column 0.
+
+use std::{
+    fmt::{self, Display, Formatter, Write},
+    ops::Range,
+};
+
+use anyhow::{Result, anyhow, bail};
+
+use crate::grid::Grid;
+
+struct Font {
+    height: usize,
+    pitch: usize,
+    glyphs: &'static [(char, &'static [&'static str])],
+}
+
+const SMALL: Font = Font {
+    height: 6,
+    pitch: 5,
+    glyphs: &[
+        ('A', &[".##.", "#..#", "#..#", "####", "#..#", "#..#"]),
+        ('B', &["###.", "#..#", "###.", "#..#", "#..#", "###."]),
+        ('C', &[".##.", "#..#", "#...", "#...", "#..#", ".##."]),
+        ('D', &["###.", "#..#", "#..#", "#..#", "#..#", "###."]),
+        ('E', &["####", "#...", "###.", "#...", "#...", "####"]),
+        ('F', &["####", "#...", "###.", "#...", "#...", "#..."]),
+        ('G', &[".##.", "#..#", "#...", "#.##", "#..#", ".###"]),
+        ('H', &["#..#", "#..#", "####", "#..#", "#..#", "#..#"]),
+        ('I', &[".###", "..#.", "..#.", "..#.", "..#.", ".###"]),
+        ('J', &["..##", "...#", "...#", "...#", "#..#", ".##."]),
+        ('K', &["#..#", "#.#.", "##..", "#.#.", "#.#.", "#..#"]),
+        ('L', &["#...", "#...", "#...", "#...", "#...", "####"]),
+        ('O', &[".##.", "#..#", "#..#", "#..#", "#..#", ".##."]),
+        ('P', &["###.", "#..#", "#..#", "###.", "#...", "#..."]),
+        ('R', &["###.", "#..#", "#..#", "###.", "#.#.", "#..#"]),
+        ('S', &[".###", "#...", "#...", ".##.", "...#", "###."]),
+        ('T', &[".###", "..#.", "..#.", "..#.", "..#.", "..#."]),
+        ('U', &["#..#", "#..#", "#..#", "#..#", "#..#", ".##."]),
+        ('Y', &["#...#", "#...#", ".#.#.", "..#..", "..#..", "..#.."]),
+        ('Z', &["####", "...#", "..#.", ".#..", "#...", "####"]),
+    ],
+};
+
+#[rustfmt::skip]
+const LARGE: Font = Font {
+    height: 10,
+    pitch: 8,
+    glyphs: &[
+        ('A', &["..##..", ".#..#.", "#....#", "#....#", "#....#", "######", "#....#", "#....#", "#....#", "#....#"]),
+        ('B', &["#####.", "#....#", "#....#", "#....#", "#####.", "#....#", "#....#", "#....#", "#....#", "#####."]),
+        ('C', &[".####.", "#....#", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#....#", ".####."]),
+        ('E', &["######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....", "#.....", "######"]),
+        ('F', &["######", "#.....", "#.....", "#.....", "#####.", "#.....", "#.....", "#.....", "#.....", "#....."]),
+        ('G', &[".####.", "#....#", "#.....", "#.....", "#.....", "#..###", "#....#", "#....#", "#...##", ".###.#"]),
+        ('H', &["#....#", "#....#", "#....#", "#....#", "######", "#....#", "#....#", "#....#", "#....#", "#....#"]),
+        ('J', &["...###", "....#.", "....#.", "....#.", "....#.", "....#.", "....#.", "#...#.", "#...#.", ".###.."]),
+        ('K', &["#....#", "#...#.", "#..#..", "#.#...", "##....", "##....", "#.#...", "#..#..", "#...#.", "#....#"]),
+        ('L', &["#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "#.....", "######"]),
+        ('N', &["#....#", "##...#", "##...#", "#.#..#", "#.#..#", "#..#.#", "#..#.#", "#...##", "#...##", "#....#"]),
+        ('P', &["#####.", "#....#", "#....#", "#....#", "#####.", "#.....", "#.....", "#.....", "#.....", "#....."]),
+        ('R', &["#####.", "#....#", "#....#", "#....#", "#####.", "#..#..", "#...#.", "#...#.", "#....#", "#....#"]),
+        ('X', &["#....#", "#....#", ".#..#.", ".#..#.", "..##..", "..##..", ".#..#.", ".#..#.", "#....#", "#....#"]),
+        ('Z', &["######", ".....#", ".....#", "....#.", "...#..", "..#...", ".#....", "#.....", "#.....", "######"]),
+    ],
+};
+
+/// Reads the letters drawn by the `true` cells of `grid`.
+pub fn read_grid(grid: &Grid<bool>) -> Result<String> {
+    let rows: Vec<&[bool]> = grid.rows().collect();
+    let start = rows.iter().position(|row| row.contains(&true));
+    let end = rows.iter().rposition(|row| row.contains(&true));
+    let rows = match (start, end) {
+        _ if matches!(rows.len(), 6 | 10) => &rows[..],
+        (Some(start), Some(end)) => &rows[start..=end],
+        _ => bail!("no letters found"),
+    };
+
+    let font = match rows.len() {
+        6 => SMALL,
+        10 => LARGE,
+        height => bail!("letters must be 6 or 10 rows high, found {height}"),
+    };
+    debug_assert_eq!(rows.len(), font.height);
+
+    let width = (0..grid.width())
+        .rposition(|x| rows.iter().any(|row| row[x]))
+        .map_or(0, |x| x + 1);
+    (0..width)
+        .step_by(font.pitch)
+        .map(|x| {
+            let cell = Cell::new(rows, x..(x + font.pitch).min(width));
+            font.glyphs
+                .iter()
+                .find_map(|&(letter, glyph)| cell.matches(glyph).then_some(letter))
+                .ok_or_else(|| anyhow!("unrecognized letter at column {x}:\n{cell}"))
+        })
+        .collect()
+}
+
+/// Reads letters drawn as text, e.g. with `#` or `█` on a background of
+/// `.` or spaces.
+///
+/// Lines may differ in length, as trailing background is often trimmed.
+pub fn read_text(text: &str) -> Result<String> {
+    let lines: Vec<Vec<bool>> = text
+        .lines()
+        .map(|line| {
+            line.chars()
+                .map(|c| !matches!(c, '.' | ' ' | '0' | '░'))
+                .collect()
+        })
+        .collect();
+    let width = lines.iter().map(Vec::len).max().unwrap_or(0);
+    let grid = Grid::from_fn(lines.len(), width, |(y, x)| {
+        lines[y].get(x).copied().unwrap_or(false)
+    });
+    read_grid(&grid)
+}
+
+/// The columns of a letter, without trailing empty columns.
+struct Cell<'a> {
+    rows: &'a [&'a [bool]],
+    start: usize,
+    width: usize,
+}
+
+impl<'a> Cell<'a> {
+    fn new(rows: &'a [&'a [bool]], columns: Range<usize>) -> Self {
+        let width = columns
+            .clone()
+            .rposition(|x| rows.iter().any(|row| row[x]))
+            .map_or(0, |x| x + 1);
+        Self {
+            rows,
+            start: columns.start,
+            width,
+        }
+    }
+
+    fn row(&self, y: usize) -> &[bool] {
+        &self.rows[y][self.start..self.start + self.width]
+    }
+
+    fn matches(&self, glyph: &[&str]) -> bool {
+        glyph.iter().enumerate().all(|(y, line)| {
+            line.len() == self.width
+                && line
+                    .bytes()
+                    .zip(self.row(y))
+                    .all(|(c, &on)| (c == b'#') == on)
+        })
+    }
+}
+
+impl Display for Cell<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (y, _) in self.rows.iter().enumerate() {
+            if y > 0 {
+                f.write_char('\n')?;
+            }
+            for &on in self.row(y) {
+                f.write_char(if on { '#' } else { '.' })?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(font: &Font, letters: &[(char, &[&str])]) -> Grid<bool> {
+        let width = letters.len() * font.pitch;
+        Grid::from_fn(font.height, width, |(y, x)| {
+            let (_, glyph) = letters[x / font.pitch];
+            glyph[y].as_bytes().get(x % font.pitch) == Some(&b'#')
+        })
+    }
+
+    #[test]
+    fn all_letters() {
+        for font in [SMALL, LARGE] {
+            let grid = render(&font, font.glyphs);
+            let expected: String = font.glyphs.iter().map(|&(c, _)| c).collect();
+            assert_eq!(read_grid(&grid).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn text() {
+        let hi = "\
+#..#..###
+#..#...#.
+####...#.
+#..#...#.
+#..#...#.
+#..#..###";
+        assert_eq!(read_text(hi).unwrap(), "HI");
+        assert_eq!(
+            read_text(&hi.replace('#', "█").replace('.', " ")).unwrap(),
+            "HI"
+        );
+
+        let padded = format!("\n{}\n   \n", hi.replace('.', " "));
+        assert_eq!(read_text(&padded).unwrap(), "HI");
+    }
+
+    #[test]
+    fn errors() {
+        let error = read_text("##.\n#..\n##.\n#..\n#..\n##.").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "unrecognized letter at column 0:\n##\n#.\n##\n#.\n#.\n##"
+        );
+        assert!(read_text("#\n#\n#").is_err());
+        assert!(read_text("").is_err());
+    }
 }
@@ -0,0 +1,78 @@
+use std::ops::Range;
+
+/// Small deterministic PRNG (xoshiro256**) for generating puzzle inputs.
+///
+/// Not suitable for anything security related, but fast and reproducible
+/// across platforms, which is all we need for fuzzing.
+#[derive(Debug, Clone)]
+#[expect(
+    missing_copy_implementations,
+    reason = "implicit copies would silently repeat random sequences"
+)]
+pub struct Rng([u64; 4]);
+
+impl Rng {
+    #[must_use]
+    pub fn new(seed: u64) -> Self {
+        // Expand the seed with splitmix64, as recommended by the xoshiro
+        // authors. This also guarantees that the state is never all zero.
+        let mut x = seed;
+        Self([(); 4].map(|()| {
+            x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+            let mut z = x;
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^ (z >> 31)
+        }))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let s = &mut self.0;
+        let result = s[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = s[1] << 17;
+        s[2] ^= s[0];
+        s[3] ^= s[1];
+        s[1] ^= s[2];
+        s[0] ^= s[3];
+        s[2] ^= t;
+        s[3] = s[3].rotate_left(45);
+        result
+    }
+
+    /// Uniformly samples from `range`, which must not be empty.
+    pub fn range(&mut self, range: Range<u64>) -> u64 {
+        assert!(!range.is_empty(), "cannot sample from empty range");
+        let width = range.end - range.start;
+
+        // Lemire's nearly divisionless method
+        let mut m = u128::from(self.next_u64()) * u128::from(width);
+        if (m as u64) < width {
+            let threshold = width.wrapping_neg() % width;
+            while (m as u64) < threshold {
+                m = u128::from(self.next_u64()) * u128::from(width);
+            }
+        }
+
+        range.start + (m >> 64) as u64
+    }
+
+    /// Uniformly samples an index into a collection of length `len`.
+    pub fn index(&mut self, len: usize) -> usize {
+        self.range(0..len as u64) as usize
+    }
+
+    /// Returns `true` with probability `p`.
+    pub fn chance(&mut self, p: f64) -> bool {
+        ((self.next_u64() >> 11) as f64) < p * (1_u64 << 53) as f64
+    }
+
+    pub fn choose<'a, T>(&mut self, items: &'a [T]) -> &'a T {
+        &items[self.index(items.len())]
+    }
+
+    pub fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            items.swap(i, self.index(i + 1));
+        }
+    }
+}
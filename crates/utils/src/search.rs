@@ -0,0 +1,472 @@
+//! Shortest path searches over implicit graphs.
+//!
+//! A search is configured with [`Search::new`] and then run with one of
+//! [`Search::bfs`], [`Search::bfs_01`], [`Search::dijkstra`] or
+//! [`Search::astar`]:
+//!
+//! ```
+//! # use utils::search::Search;
+//! let reached = Search::new([1_u32]).bfs(|&n| [n + 1, 2 * n], |&n| n == 10);
+//! assert_eq!(reached.goal(), Some((&10, 4)));
+//! assert_eq!(reached.goal_path().unwrap(), [&1, &2, &4, &5, &10]);
+//! ```
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, VecDeque, hash_map::Entry},
+    hash::Hash,
+    marker::PhantomData,
+};
+
+use num::Zero;
+
+use crate::hash::{FastHashCollectionExt, FastHashMap};
+
+/// Configuration of a search: start states, canonicalisation and whether to
+/// track all shortest-path predecessors.
+#[derive(Debug, Clone)]
+pub struct Search<N, K, F> {
+    starts: Vec<N>,
+    key: F,
+    all_predecessors: bool,
+    _key: PhantomData<fn() -> K>,
+}
+
+impl<N: Clone + Eq + Hash> Search<N, N, fn(&N) -> N> {
+    /// Starts a search from one or multiple states at cost zero.
+    pub fn new(starts: impl IntoIterator<Item = N>) -> Self {
+        Self {
+            starts: starts.into_iter().collect(),
+            key: N::clone,
+            all_predecessors: false,
+            _key: PhantomData,
+        }
+    }
+}
+
+impl<N: Clone, K: Eq + Hash, F: FnMut(&N) -> K> Search<N, K, F> {
+    /// Treats states with the same key as equivalent, only expanding the
+    /// first one reached.
+    pub fn canonicalize<K2: Eq + Hash, F2: FnMut(&N) -> K2>(self, key: F2) -> Search<N, K2, F2> {
+        Search {
+            starts: self.starts,
+            key,
+            all_predecessors: self.all_predecessors,
+            _key: PhantomData,
+        }
+    }
+
+    /// Records all predecessors on shortest paths instead of only the
+    /// first one, and keeps searching until all goals at the optimal cost
+    /// are found.
+    ///
+    /// Required for [`Reached::on_shortest_paths`].
+    #[must_use]
+    pub fn all_predecessors(mut self) -> Self {
+        self.all_predecessors = true;
+        self
+    }
+
+    /// Breadth-first search over unweighted edges.
+    pub fn bfs<I: IntoIterator<Item = N>>(
+        self,
+        mut successors: impl FnMut(&N) -> I,
+        goal: impl FnMut(&N) -> bool,
+    ) -> Reached<N, K, usize> {
+        self.run(
+            Fifo::default(),
+            |state| successors(state).into_iter().map(|next| (next, 1)),
+            |_| 0,
+            goal,
+        )
+    }
+
+    /// Breadth-first search over edges with cost zero or one.
+    pub fn bfs_01<I: IntoIterator<Item = (N, usize)>>(
+        self,
+        mut successors: impl FnMut(&N) -> I,
+        goal: impl FnMut(&N) -> bool,
+    ) -> Reached<N, K, usize> {
+        let successors = |state: &N| {
+            successors(state).into_iter().inspect(|&(_, cost)| {
+                debug_assert!(cost <= 1, "0-1 BFS requires edge costs of 0 or 1");
+            })
+        };
+        self.run(ZeroOne::default(), successors, |_| 0, goal)
+    }
+
+    /// Dijkstra's algorithm over edges with non-negative costs.
+    pub fn dijkstra<C: Copy + Ord + Zero, I: IntoIterator<Item = (N, C)>>(
+        self,
+        successors: impl FnMut(&N) -> I,
+        goal: impl FnMut(&N) -> bool,
+    ) -> Reached<N, K, C> {
+        self.run(Heap::default(), successors, |_| C::zero(), goal)
+    }
+
+    /// A* search, with a `heuristic` that must never overestimate the
+    /// remaining cost and should be consistent.
+    pub fn astar<C: Copy + Ord + Zero, I: IntoIterator<Item = (N, C)>>(
+        self,
+        successors: impl FnMut(&N) -> I,
+        heuristic: impl FnMut(&N) -> C,
+        goal: impl FnMut(&N) -> bool,
+    ) -> Reached<N, K, C> {
+        self.run(Heap::default(), successors, heuristic, goal)
+    }
+
+    fn run<C: Copy + Ord + Zero, I: IntoIterator<Item = (N, C)>>(
+        mut self,
+        mut frontier: impl Frontier<C>,
+        mut successors: impl FnMut(&N) -> I,
+        mut heuristic: impl FnMut(&N) -> C,
+        mut goal: impl FnMut(&N) -> bool,
+    ) -> Reached<N, K, C> {
+        let mut reached = Reached {
+            nodes: Vec::new(),
+            index: FastHashMap::new(),
+            goals: Vec::new(),
+        };
+        for start in self.starts.drain(..) {
+            if let Entry::Vacant(entry) = reached.index.entry((self.key)(&start)) {
+                entry.insert(reached.nodes.len());
+                frontier.push(heuristic(&start), true, reached.nodes.len());
+                reached.nodes.push(Node::new(start, C::zero(), None));
+            }
+        }
+
+        let mut goal_cost = None;
+        while let Some((priority, idx)) = frontier.pop() {
+            if goal_cost.is_some_and(|goal_cost| priority > goal_cost) {
+                break;
+            }
+            let node = &mut reached.nodes[idx];
+            if node.expanded {
+                continue;
+            }
+            node.expanded = true;
+
+            let (state, cost) = (node.state.clone(), node.cost);
+            if goal(&state) {
+                reached.goals.push(idx);
+                goal_cost = Some(cost);
+                if !self.all_predecessors {
+                    break;
+                }
+            }
+
+            for (next, edge_cost) in successors(&state) {
+                let next_cost = cost + edge_cost;
+                match reached.index.entry((self.key)(&next)) {
+                    Entry::Vacant(entry) => {
+                        let next_idx = reached.nodes.len();
+                        entry.insert(next_idx);
+                        frontier.push(next_cost + heuristic(&next), edge_cost.is_zero(), next_idx);
+                        reached.nodes.push(Node::new(next, next_cost, Some(idx)));
+                    }
+                    Entry::Occupied(entry) => {
+                        let next_idx = *entry.get();
+                        let next_node = &mut reached.nodes[next_idx];
+                        if next_cost < next_node.cost {
+                            next_node.cost = next_cost;
+                            next_node.predecessor = Some(idx);
+                            next_node.other_predecessors.clear();
+                            next_node.expanded = false;
+                            frontier.push(
+                                next_cost + heuristic(&next),
+                                edge_cost.is_zero(),
+                                next_idx,
+                            );
+                        } else if self.all_predecessors
+                            && next_cost == next_node.cost
+                            && next_node.predecessor.is_some_and(|pred| pred != idx)
+                        {
+                            next_node.other_predecessors.push(idx);
+                        }
+                    }
+                }
+            }
+        }
+
+        reached
+    }
+}
+
+/// The outcome of a search: all states reached along with their costs and
+/// predecessors.
+///
+/// If the search stopped early at a goal, states that were discovered but
+/// not yet expanded might not have their minimal cost.
+#[derive(Debug, Clone)]
+pub struct Reached<N, K, C> {
+    nodes: Vec<Node<N, C>>,
+    index: FastHashMap<K, usize>,
+    goals: Vec<usize>,
+}
+
+impl<N, K: Eq + Hash, C: Copy> Reached<N, K, C> {
+    /// Cost of the cheapest known path to the state with `key`.
+    pub fn cost(&self, key: &K) -> Option<C> {
+        self.index.get(key).map(|&idx| self.nodes[idx].cost)
+    }
+
+    /// The first goal state found and its cost.
+    #[must_use]
+    pub fn goal(&self) -> Option<(&N, C)> {
+        let &idx = self.goals.first()?;
+        Some((&self.nodes[idx].state, self.nodes[idx].cost))
+    }
+
+    /// All goal states found at the optimal cost, which includes more than
+    /// one only when tracking [all predecessors](Search::all_predecessors).
+    pub fn goals(&self) -> impl Iterator<Item = (&N, C)> {
+        self.goals
+            .iter()
+            .map(|&idx| (&self.nodes[idx].state, self.nodes[idx].cost))
+    }
+
+    /// All states reached and their costs, in discovery order.
+    pub fn iter(&self) -> impl Iterator<Item = (&N, C)> {
+        self.nodes.iter().map(|node| (&node.state, node.cost))
+    }
+
+    /// Reconstructs a cheapest path from a start state to the state with
+    /// `key`.
+    pub fn path(&self, key: &K) -> Option<Vec<&N>> {
+        self.index.get(key).map(|&idx| self.path_from_idx(idx))
+    }
+
+    /// Reconstructs a cheapest path from a start state to the first goal.
+    #[must_use]
+    pub fn goal_path(&self) -> Option<Vec<&N>> {
+        self.goals.first().map(|&idx| self.path_from_idx(idx))
+    }
+
+    /// All states lying on any cheapest path to any of the [`goals`], each
+    /// yielded once.
+    ///
+    /// Without tracking [all predecessors](Search::all_predecessors) this
+    /// only includes the states of a single path per goal.
+    ///
+    /// [`goals`]: Self::goals
+    pub fn on_shortest_paths(&self) -> impl Iterator<Item = &N> {
+        let mut seen = vec![false; self.nodes.len()];
+        let mut stack = self.goals.clone();
+        for &idx in &stack {
+            seen[idx] = true;
+        }
+
+        std::iter::from_fn(move || {
+            let idx = stack.pop()?;
+            let node = &self.nodes[idx];
+            for &pred in node.predecessor.iter().chain(&node.other_predecessors) {
+                if !seen[pred] {
+                    seen[pred] = true;
+                    stack.push(pred);
+                }
+            }
+            Some(&node.state)
+        })
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    fn path_from_idx(&self, mut idx: usize) -> Vec<&N> {
+        let mut path = vec![&self.nodes[idx].state];
+        while let Some(pred) = self.nodes[idx].predecessor {
+            path.push(&self.nodes[pred].state);
+            idx = pred;
+        }
+        path.reverse();
+        path
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Node<N, C> {
+    state: N,
+    cost: C,
+    predecessor: Option<usize>,
+    /// Only filled when tracking all predecessors.
+    other_predecessors: Vec<usize>,
+    expanded: bool,
+}
+
+impl<N, C> Node<N, C> {
+    fn new(state: N, cost: C, predecessor: Option<usize>) -> Self {
+        Self {
+            state,
+            cost,
+            predecessor,
+            other_predecessors: Vec::new(),
+            expanded: false,
+        }
+    }
+}
+
+trait Frontier<C> {
+    fn push(&mut self, priority: C, zero_cost_edge: bool, idx: usize);
+    fn pop(&mut self) -> Option<(C, usize)>;
+}
+
+#[derive(Debug)]
+struct Fifo<C>(VecDeque<(C, usize)>);
+
+impl<C> Default for Fifo<C> {
+    fn default() -> Self {
+        Self(VecDeque::new())
+    }
+}
+
+impl<C> Frontier<C> for Fifo<C> {
+    fn push(&mut self, priority: C, _zero_cost_edge: bool, idx: usize) {
+        self.0.push_back((priority, idx));
+    }
+
+    fn pop(&mut self) -> Option<(C, usize)> {
+        self.0.pop_front()
+    }
+}
+
+#[derive(Debug)]
+struct ZeroOne<C>(VecDeque<(C, usize)>);
+
+impl<C> Default for ZeroOne<C> {
+    fn default() -> Self {
+        Self(VecDeque::new())
+    }
+}
+
+impl<C> Frontier<C> for ZeroOne<C> {
+    fn push(&mut self, priority: C, zero_cost_edge: bool, idx: usize) {
+        if zero_cost_edge {
+            self.0.push_front((priority, idx));
+        } else {
+            self.0.push_back((priority, idx));
+        }
+    }
+
+    fn pop(&mut self) -> Option<(C, usize)> {
+        self.0.pop_front()
+    }
+}
+
+#[derive(Debug)]
+struct Heap<C>(BinaryHeap<Reverse<(C, usize)>>);
+
+impl<C: Ord> Default for Heap<C> {
+    fn default() -> Self {
+        Self(BinaryHeap::new())
+    }
+}
+
+impl<C: Ord> Frontier<C> for Heap<C> {
+    fn push(&mut self, priority: C, _zero_cost_edge: bool, idx: usize) {
+        self.0.push(Reverse((priority, idx)));
+    }
+
+    fn pop(&mut self) -> Option<(C, usize)> {
+        self.0.pop().map(|Reverse(entry)| entry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Grid with walls, weighted by the digit in each cell.
+    const MAZE: [&str; 5] = ["11111", "1###1", "19991", "1#1#1", "11111"];
+
+    fn neighbors((y, x): (usize, usize)) -> impl Iterator<Item = ((usize, usize), u32)> {
+        [
+            (y.wrapping_sub(1), x),
+            (y + 1, x),
+            (y, x.wrapping_sub(1)),
+            (y, x + 1),
+        ]
+        .into_iter()
+        .filter_map(|(y2, x2)| {
+            let cell = *MAZE.get(y2)?.as_bytes().get(x2)?;
+            (cell != b'#').then(|| ((y2, x2), u32::from(cell - b'0')))
+        })
+    }
+
+    #[test]
+    fn bfs_multi_source() {
+        let reached = Search::new([(0, 0), (4, 4)])
+            .bfs(|&pos| neighbors(pos).map(|(pos2, _)| pos2), |_| false);
+        assert_eq!(reached.len(), 20);
+        assert_eq!(reached.cost(&(2, 2)), Some(4));
+        assert_eq!(reached.cost(&(4, 0)), Some(4));
+        assert_eq!(reached.cost(&(1, 1)), None);
+        assert_eq!(reached.path(&(0, 2)).unwrap().len(), 3);
+    }
+
+    #[test]
+    fn weighted() {
+        let goal = |&pos: &(usize, usize)| pos == (2, 2);
+        let dijkstra = Search::new([(2, 0)]).dijkstra(|&pos| neighbors(pos), goal);
+        let astar = Search::new([(2, 0)]).astar(
+            |&pos| neighbors(pos),
+            |&(y, x)| (y.abs_diff(2) + x.abs_diff(2)) as u32,
+            goal,
+        );
+        for reached in [dijkstra, astar] {
+            assert_eq!(reached.goal(), Some((&(2, 2), 5 + 9)));
+            let path = reached.goal_path().unwrap();
+            assert_eq!(path.first(), Some(&&(2, 0)));
+            assert_eq!(path.last(), Some(&&(2, 2)));
+        }
+    }
+
+    #[test]
+    fn zero_one() {
+        // Moving right is free, everything else costs one
+        let successors = |&(y, x): &(usize, usize)| {
+            neighbors((y, x)).map(move |((y2, x2), _)| ((y2, x2), usize::from(x2 <= x)))
+        };
+        let reached = Search::new([(0, 0)]).bfs_01(successors, |&pos| pos == (4, 4));
+        assert_eq!(reached.goal(), Some((&(4, 4), 4)));
+    }
+
+    #[test]
+    fn all_predecessors() {
+        let reached = Search::new([(0, 0)]).all_predecessors().bfs(
+            |&pos| neighbors(pos).map(|(pos2, _)| pos2),
+            |&pos| pos == (4, 4),
+        );
+        assert_eq!(reached.goal().map(|(_, cost)| cost), Some(8));
+        assert_eq!(reached.on_shortest_paths().count(), 20);
+
+        let reached = Search::new([(0, 0)]).bfs(
+            |&pos| neighbors(pos).map(|(pos2, _)| pos2),
+            |&pos| pos == (4, 4),
+        );
+        assert_eq!(reached.on_shortest_paths().count(), 9);
+
+        // Both corners are goals at the same distance
+        let reached = Search::new([(2, 2)]).all_predecessors().bfs(
+            |&pos| neighbors(pos).map(|(pos2, _)| pos2),
+            |&pos| pos == (4, 0) || pos == (4, 4),
+        );
+        assert_eq!(reached.goals().count(), 2);
+    }
+
+    #[test]
+    fn canonicalize() {
+        // Explores residues only, so the search terminates
+        let reached = Search::new([1_u64])
+            .canonicalize(|&n| n % 7)
+            .bfs(|&n| [n * 3], |_| false);
+        assert_eq!(reached.len(), 6);
+        assert_eq!(reached.cost(&5), Some(5));
+    }
+}
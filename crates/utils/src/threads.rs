@@ -0,0 +1,44 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use rayon::ThreadPoolBuilder;
+
+/// Stack size of the pool threads, matching the runner's solution threads
+/// and the usual main thread stack size on Linux (some solutions recurse
+/// deeply).
+const STACK_SIZE: usize = 8 << 20;
+
+/// Configured thread count, or zero to use all logical CPUs.
+static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Number of threads multithreaded solutions should use.
+///
+/// Defaults to the number of logical CPUs unless overridden by
+/// [`with_count`].
+#[must_use]
+pub fn count() -> usize {
+    match COUNT.load(Ordering::Relaxed) {
+        0 => num_cpus::get(),
+        n => n,
+    }
+}
+
+/// Runs `f` with [`count`] returning `threads` and inside a rayon pool of the
+/// same size, so that solutions using rayon are limited as well.
+pub fn with_count<T: Send>(threads: usize, f: impl FnOnce() -> T + Send) -> T {
+    struct Restore(usize);
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            COUNT.store(self.0, Ordering::Relaxed);
+        }
+    }
+
+    assert!(threads > 0, "thread count must be positive");
+    let pool = ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .stack_size(STACK_SIZE)
+        .build()
+        .expect("failed to create thread pool");
+    let _restore = Restore(COUNT.swap(threads, Ordering::Relaxed));
+    pool.install(f)
+}
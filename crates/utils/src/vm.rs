@@ -0,0 +1,545 @@
+//! Interpreters for the small assembly languages of some puzzles.
+//!
+//! A dialect is an [`Instruction`] type, usually an enum deriving
+//! [`Parse`](crate::input::Parse) with one format per mnemonic, that knows
+//! how to execute a single instruction. A [`Machine`] holds the program, the
+//! [`Registers`] and the program counter, and runs instructions until the
+//! program halts, waits for input, hits a breakpoint or exceeds its step
+//! limit. Instructions that communicate with the outside do so through an
+//! [`Io`] implementation passed to [`Machine::run`].
+
+use std::{
+    fmt::{self, Display, Formatter},
+    ops::{ControlFlow, Index, IndexMut},
+    str::FromStr,
+};
+
+use anyhow::{Error, Result, bail};
+
+use crate::input::parse_lines;
+
+/// A register named by a lowercase letter, starting with `a`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Register(u8);
+
+impl Register {
+    /// The register called `name`, which must be a lowercase ASCII letter.
+    #[must_use]
+    pub const fn named(name: u8) -> Self {
+        assert!(
+            name.is_ascii_lowercase(),
+            "register names are lowercase letters"
+        );
+        Self(name - b'a')
+    }
+
+    /// The position in the register file, i.e. 0 for `a`.
+    #[must_use]
+    pub const fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+impl FromStr for Register {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.as_bytes() {
+            &[c] if c.is_ascii_lowercase() => Ok(Self::named(c)),
+            _ => bail!("invalid register: {s:?}"),
+        }
+    }
+}
+
+impl Display for Register {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", char::from(b'a' + self.0))
+    }
+}
+
+/// An instruction argument that is either a constant or read from a
+/// register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Operand<W> {
+    Immediate(W),
+    Register(Register),
+}
+
+impl<W: Copy> Operand<W> {
+    /// The register the operand reads from, if any.
+    #[must_use]
+    pub fn register(self) -> Option<Register> {
+        match self {
+            Self::Immediate(_) => None,
+            Self::Register(register) => Some(register),
+        }
+    }
+
+    #[inline]
+    pub fn eval<const N: usize>(self, registers: &Registers<W, N>) -> W {
+        match self {
+            Self::Immediate(value) => value,
+            Self::Register(register) => registers[register],
+        }
+    }
+}
+
+impl<W: FromStr> FromStr for Operand<W>
+where
+    Error: From<W::Err>,
+{
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if let Ok(register) = s.parse() {
+            Ok(Self::Register(register))
+        } else {
+            Ok(Self::Immediate(s.parse()?))
+        }
+    }
+}
+
+/// The values of `N` registers named `a` onwards.
+///
+/// Indexing with a register beyond the first `N` panics, which
+/// [`Machine::new`] rules out for the registers of its program.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Registers<W, const N: usize>([W; N]);
+
+impl<W, const N: usize> Registers<W, N> {
+    #[must_use]
+    pub fn new(values: [W; N]) -> Self {
+        Self(values)
+    }
+
+    #[must_use]
+    pub fn values(&self) -> &[W; N] {
+        &self.0
+    }
+}
+
+impl<W: Copy + Default, const N: usize> Default for Registers<W, N> {
+    fn default() -> Self {
+        Self([W::default(); N])
+    }
+}
+
+impl<W, const N: usize> Index<Register> for Registers<W, N> {
+    type Output = W;
+
+    #[inline]
+    fn index(&self, register: Register) -> &W {
+        &self.0[register.index()]
+    }
+}
+
+impl<W, const N: usize> IndexMut<Register> for Registers<W, N> {
+    #[inline]
+    fn index_mut(&mut self, register: Register) -> &mut W {
+        &mut self.0[register.index()]
+    }
+}
+
+/// Hooks for instructions that send values to or receive values from the
+/// outside world.
+pub trait Io<W> {
+    /// Takes a value sent by the program. Breaking asks the machine to stop
+    /// after the sending instruction.
+    fn output(&mut self, value: W) -> ControlFlow<()>;
+
+    /// Provides a value for a receiving instruction, given the current value
+    /// of its register. `Continue(None)` leaves the register unchanged, and
+    /// breaking makes the machine wait before the instruction, so that it
+    /// is retried once the machine is resumed.
+    fn input(&mut self, current: W) -> ControlFlow<(), Option<W>>;
+}
+
+/// I/O for programs that do not communicate: output is discarded and input
+/// never arrives.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoIo;
+
+impl<W> Io<W> for NoIo {
+    fn output(&mut self, _value: W) -> ControlFlow<()> {
+        ControlFlow::Continue(())
+    }
+
+    fn input(&mut self, _current: W) -> ControlFlow<(), Option<W>> {
+        ControlFlow::Break(())
+    }
+}
+
+/// Where a [`Machine`] continues after an instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Control {
+    /// Continue with the next instruction.
+    Next,
+    /// Continue at the given offset from the current instruction.
+    Jump(isize),
+    /// Stop before the current instruction, which is executed again when
+    /// the machine is resumed.
+    Wait,
+    /// Stop after the current instruction.
+    Stop,
+}
+
+/// The instruction set of a dialect.
+pub trait Instruction: Copy {
+    /// The type of register values.
+    type Word: Copy + Default;
+
+    /// Executes the instruction at `pc` on a machine with `N` registers.
+    ///
+    /// Dialects with self-modifying code can change `program`, which still
+    /// contains the instruction itself.
+    fn execute<const N: usize>(
+        self,
+        pc: usize,
+        program: &mut [Self],
+        registers: &mut Registers<Self::Word, N>,
+        io: &mut impl Io<Self::Word>,
+    ) -> Result<Control>;
+
+    /// The registers the instruction reads or writes.
+    fn registers(self) -> impl IntoIterator<Item = Register>;
+}
+
+/// Why [`Machine::run`] returned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Exit {
+    /// The program counter moved past the end of the program.
+    Halted,
+    /// An instruction returned [`Control::Wait`].
+    Waiting,
+    /// An instruction returned [`Control::Stop`].
+    Stopped,
+    /// The program counter reached a breakpoint.
+    Breakpoint,
+    /// The machine executed as many instructions as its step limit allows.
+    StepLimit,
+}
+
+/// A program loaded into a machine with `N` registers.
+#[derive(Debug, Clone)]
+pub struct Machine<I: Instruction, const N: usize> {
+    program: Vec<I>,
+    registers: Registers<I::Word, N>,
+    pc: usize,
+    steps: u64,
+    step_limit: u64,
+    breakpoints: Vec<usize>,
+}
+
+impl<I: Instruction, const N: usize> Machine<I, N> {
+    /// Loads `program`, with all registers set to their default value.
+    ///
+    /// Fails if an instruction uses a register beyond the first `N`.
+    pub fn new(program: Vec<I>) -> Result<Self> {
+        for (pc, instruction) in program.iter().enumerate() {
+            if let Some(register) = instruction
+                .registers()
+                .into_iter()
+                .find(|register| register.index() >= N)
+            {
+                bail!("instruction {pc} uses register {register}, but there are only {N}");
+            }
+        }
+
+        Ok(Self {
+            program,
+            registers: Registers::default(),
+            pc: 0,
+            steps: 0,
+            step_limit: u64::MAX,
+            breakpoints: Vec::new(),
+        })
+    }
+
+    /// Loads a program with one instruction per line.
+    pub fn parse(input: &str) -> Result<Self>
+    where
+        I: FromStr,
+        Error: From<I::Err>,
+    {
+        Self::new(parse_lines(input)?)
+    }
+
+    #[must_use]
+    pub fn program(&self) -> &[I] {
+        &self.program
+    }
+
+    pub fn program_mut(&mut self) -> &mut [I] {
+        &mut self.program
+    }
+
+    #[must_use]
+    pub fn registers(&self) -> &Registers<I::Word, N> {
+        &self.registers
+    }
+
+    pub fn registers_mut(&mut self) -> &mut Registers<I::Word, N> {
+        &mut self.registers
+    }
+
+    /// The index of the next instruction.
+    #[must_use]
+    pub fn pc(&self) -> usize {
+        self.pc
+    }
+
+    pub fn set_pc(&mut self, pc: usize) {
+        self.pc = pc;
+    }
+
+    /// The number of instructions executed so far.
+    #[must_use]
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// Makes [`run`](Self::run) return once `limit` instructions were
+    /// executed in total.
+    pub fn set_step_limit(&mut self, limit: u64) {
+        self.step_limit = limit;
+    }
+
+    /// Makes [`run`](Self::run) return before executing the instruction at
+    /// `pc`, unless it is the first instruction of the run.
+    pub fn set_breakpoint(&mut self, pc: usize) {
+        if !self.breakpoints.contains(&pc) {
+            self.breakpoints.push(pc);
+        }
+    }
+
+    pub fn clear_breakpoint(&mut self, pc: usize) {
+        self.breakpoints.retain(|&breakpoint| breakpoint != pc);
+    }
+
+    /// Resets the registers, the program counter and the step count, but
+    /// keeps the program, breakpoints and step limit.
+    pub fn reset(&mut self) {
+        self.registers = Registers::default();
+        self.pc = 0;
+        self.steps = 0;
+    }
+
+    /// Runs the program from the current instruction until it halts or
+    /// stops for one of the other reasons in [`Exit`].
+    ///
+    /// Jumping past the end of the program halts it, but jumping before
+    /// the start is an error.
+    pub fn run(&mut self, io: &mut impl Io<I::Word>) -> Result<Exit> {
+        // Checking for breakpoints slows down tight loops considerably, so
+        // it is only compiled in when there are any.
+        if self.breakpoints.is_empty() {
+            self.run_until::<false>(io)
+        } else {
+            self.run_until::<true>(io)
+        }
+    }
+
+    fn run_until<const BREAKPOINTS: bool>(&mut self, io: &mut impl Io<I::Word>) -> Result<Exit> {
+        // Counting in a local keeps the counter in a register.
+        let mut steps = self.steps;
+        let result = self.run_counting::<BREAKPOINTS>(io, &mut steps);
+        self.steps = steps;
+        result
+    }
+
+    #[inline]
+    fn run_counting<const BREAKPOINTS: bool>(
+        &mut self,
+        io: &mut impl Io<I::Word>,
+        steps: &mut u64,
+    ) -> Result<Exit> {
+        let start = *steps;
+        while let Some(&instruction) = self.program.get(self.pc) {
+            if *steps == self.step_limit {
+                return Ok(Exit::StepLimit);
+            }
+            if BREAKPOINTS && *steps != start && self.breakpoints.contains(&self.pc) {
+                return Ok(Exit::Breakpoint);
+            }
+
+            // Unlike `with_context`, this keeps the error path out of the
+            // loop.
+            let control =
+                match instruction.execute(self.pc, &mut self.program, &mut self.registers, io) {
+                    Ok(control) => control,
+                    Err(error) => {
+                        return Err(
+                            error.context(format!("failed to execute instruction {}", self.pc))
+                        );
+                    }
+                };
+            match control {
+                Control::Next => self.pc += 1,
+                Control::Jump(offset) => {
+                    let Some(pc) = self.pc.checked_add_signed(offset) else {
+                        bail!(
+                            "jump by {offset} from instruction {} leaves the program",
+                            self.pc
+                        );
+                    };
+                    self.pc = pc;
+                }
+                Control::Wait => return Ok(Exit::Waiting),
+                Control::Stop => {
+                    *steps += 1;
+                    self.pc += 1;
+                    return Ok(Exit::Stopped);
+                }
+            }
+            *steps += 1;
+        }
+
+        Ok(Exit::Halted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::Parse;
+
+    #[derive(Debug, Clone, Copy, Parse)]
+    enum Test {
+        #[parse("set {0} {1}")]
+        Set(Register, Operand<i32>),
+        #[parse("add {0} {1}")]
+        Add(Register, Operand<i32>),
+        #[parse("jnz {0} {1}")]
+        JumpNotZero(Operand<i32>, isize),
+        #[parse("out {0}")]
+        Out(Operand<i32>),
+        #[parse("in {0}")]
+        In(Register),
+    }
+
+    impl Instruction for Test {
+        type Word = i32;
+
+        fn execute<const N: usize>(
+            self,
+            _pc: usize,
+            _program: &mut [Self],
+            registers: &mut Registers<i32, N>,
+            io: &mut impl Io<i32>,
+        ) -> Result<Control> {
+            match self {
+                Self::Set(register, value) => registers[register] = value.eval(registers),
+                Self::Add(register, value) => registers[register] += value.eval(registers),
+                Self::JumpNotZero(value, offset) => {
+                    if value.eval(registers) != 0 {
+                        return Ok(Control::Jump(offset));
+                    }
+                }
+                Self::Out(value) => {
+                    if io.output(value.eval(registers)).is_break() {
+                        return Ok(Control::Stop);
+                    }
+                }
+                Self::In(register) => match io.input(registers[register]) {
+                    ControlFlow::Continue(value) => {
+                        registers[register] = value.unwrap_or(registers[register]);
+                    }
+                    ControlFlow::Break(()) => return Ok(Control::Wait),
+                },
+            }
+            Ok(Control::Next)
+        }
+
+        fn registers(self) -> impl IntoIterator<Item = Register> {
+            match self {
+                Self::Set(register, value) | Self::Add(register, value) => {
+                    [Some(register), value.register()]
+                }
+                Self::JumpNotZero(value, _) | Self::Out(value) => [value.register(), None],
+                Self::In(register) => [Some(register), None],
+            }
+            .into_iter()
+            .flatten()
+        }
+    }
+
+    #[derive(Default)]
+    struct Queue {
+        input: Vec<i32>,
+        output: Vec<i32>,
+    }
+
+    impl Io<i32> for Queue {
+        fn output(&mut self, value: i32) -> ControlFlow<()> {
+            self.output.push(value);
+            if self.output.len() == 2 {
+                ControlFlow::Break(())
+            } else {
+                ControlFlow::Continue(())
+            }
+        }
+
+        fn input(&mut self, _current: i32) -> ControlFlow<(), Option<i32>> {
+            self.input.pop().map_or(ControlFlow::Break(()), |value| {
+                ControlFlow::Continue(Some(value))
+            })
+        }
+    }
+
+    const COUNTDOWN: &str = "set a 3\nadd b a\nadd a -1\njnz a -2";
+
+    #[test]
+    fn run() {
+        let mut machine = Machine::<Test, 2>::parse(COUNTDOWN).unwrap();
+        assert_eq!(machine.run(&mut NoIo).unwrap(), Exit::Halted);
+        assert_eq!(machine.registers().values(), &[0, 6]);
+        assert_eq!(machine.steps(), 10);
+
+        machine.reset();
+        machine.set_step_limit(4);
+        assert_eq!(machine.run(&mut NoIo).unwrap(), Exit::StepLimit);
+        assert_eq!((machine.pc(), machine.registers().values()), (1, &[2, 3]));
+
+        machine.set_step_limit(u64::MAX);
+        machine.set_breakpoint(1);
+        assert_eq!(machine.run(&mut NoIo).unwrap(), Exit::Breakpoint);
+        assert_eq!((machine.pc(), machine.registers().values()), (1, &[1, 5]));
+
+        machine.clear_breakpoint(1);
+        assert_eq!(machine.run(&mut NoIo).unwrap(), Exit::Halted);
+        assert_eq!(machine.registers().values(), &[0, 6]);
+    }
+
+    #[test]
+    fn io() {
+        let mut machine = Machine::<Test, 1>::parse("in a\nout a\nout 7\nout 8").unwrap();
+        let mut queue = Queue::default();
+        assert_eq!(machine.run(&mut queue).unwrap(), Exit::Waiting);
+        assert_eq!(machine.pc(), 0);
+
+        queue.input.push(5);
+        assert_eq!(machine.run(&mut queue).unwrap(), Exit::Stopped);
+        assert_eq!(queue.output, [5, 7]);
+        assert_eq!(machine.run(&mut queue).unwrap(), Exit::Halted);
+        assert_eq!(queue.output, [5, 7, 8]);
+    }
+
+    #[test]
+    fn errors() {
+        let mut machine = Machine::<Test, 1>::parse("jnz 1 -2").unwrap();
+        let error = machine.run(&mut NoIo).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "jump by -2 from instruction 0 leaves the program"
+        );
+
+        assert!(Machine::<Test, 1>::parse("set 1 a").is_err());
+        assert!(Machine::<Test, 1>::parse("mul a 2").is_err());
+        let error = Machine::<Test, 2>::parse("set a 1\nadd b c").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "instruction 1 uses register c, but there are only 2"
+        );
+        assert_eq!(Register::named(b'c').to_string(), "c");
+    }
+}